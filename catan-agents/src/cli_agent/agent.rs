@@ -9,7 +9,7 @@ use catan_core::{
             ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
             MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
         },
-        agent::PlayerRuntime,
+        agent::{AgentError, PlayerRuntime},
     },
     gameplay::{
         game::{
@@ -61,65 +61,93 @@ impl PlayerRuntime for CliAgent {
         self.player_id
     }
 
-    fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction {
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Initial placement", &context);
-        InitStageAction {
+        Ok(InitStageAction {
             establishment_position: TerminalUi::read_intersection("settlement (h1 h2 h3): "),
             road: Road {
                 pos: TerminalUi::read_path("road (h1 h2): "),
             },
-        }
+        })
     }
 
-    fn init_action(&mut self, context: PlayerDecisionContext<'_>) -> InitAction {
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Before dice", &context);
-        InitAction::RollDice
+        Ok(InitAction::RollDice)
     }
 
-    fn after_dice_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDiceAction {
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("After dice", &context);
-        PostDiceAction::RegularAction(TerminalUi::read_regular_action())
+        Ok(PostDiceAction::RegularAction(
+            TerminalUi::read_regular_action(),
+        ))
     }
 
-    fn after_dev_card_action(&mut self, _context: PlayerDecisionContext<'_>) -> PostDevCardAction {
-        PostDevCardAction::RollDice
+    fn after_dev_card_action(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
+        Ok(PostDevCardAction::RollDice)
     }
 
-    fn regular_action(&mut self, context: PlayerDecisionContext<'_>) -> RegularAction {
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Action", &context);
-        TerminalUi::read_regular_action()
+        Ok(TerminalUi::read_regular_action())
     }
 
-    fn move_robbers(&mut self, context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Move robber", &context);
-        MoveRobbersAction(TerminalUi::read_hex("robber hex: "))
+        Ok(MoveRobbersAction(TerminalUi::read_hex("robber hex: ")))
     }
 
     fn choose_player_to_rob(
         &mut self,
         context: PlayerDecisionContext<'_>,
         _robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction {
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Choose player to rob", &context);
-        ChoosePlayerToRobAction(TerminalUi::read_player_id("player id: "))
+        Ok(ChoosePlayerToRobAction(TerminalUi::read_player_id(
+            "player id: ",
+        )))
     }
 
-    fn answer_trade(&mut self, _context: PlayerDecisionContext<'_>) -> TradeAnswer {
-        TradeAnswer::Decline
+    fn answer_trade(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        Ok(TradeAnswer::Decline)
     }
 
-    fn drop_half(&mut self, context: PlayerDecisionContext<'_>) -> DropHalfAction {
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
         let _guard = self.terminal.inner.lock().expect("terminal mutex poisoned");
         TerminalUi::print_decision_context("Discard half", &context);
-        DropHalfAction(TerminalUi::read_resource_collection(
+        Ok(DropHalfAction(TerminalUi::read_resource_collection(
             "drop brick wood wheat sheep ore: ",
-        ))
+        )))
     }
 }
 
@@ -6,7 +6,7 @@ use catan_core::{
             ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
             MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
         },
-        agent::PlayerRuntime,
+        agent::{AgentError, PlayerRuntime},
     },
     gameplay::{
         constants,
@@ -28,23 +28,126 @@ use catan_core::{
     },
     topology::Hex,
 };
-use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
 use crate::{lazy, legal};
 
 #[derive(Debug, Default)]
 pub struct GreedyAgent {
     id: PlayerId,
+    weights: GreedyWeights,
     first_initial_resources: Option<BTreeSet<Resource>>,
 }
 
 impl GreedyAgent {
     pub fn new(id: PlayerId) -> Self {
+        Self::with_weights(id, GreedyWeights::default())
+    }
+
+    pub fn with_weights(id: PlayerId, weights: GreedyWeights) -> Self {
         Self {
             id,
+            weights,
             first_initial_resources: None,
         }
     }
+
+    pub fn weights(&self) -> &GreedyWeights {
+        &self.weights
+    }
+}
+
+/// Tunable heuristics of [`GreedyAgent`].
+///
+/// The defaults reproduce the original hard-coded behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GreedyWeights {
+    pub priorities: ObjectivePriorities,
+    pub initial_settlement: InitialSettlementWeights,
+    /// Extra score of a river tile when choosing where to move the robber.
+    pub river_robber_bonus: f64,
+}
+
+/// Objectives are attempted from the highest priority to the lowest.
+/// Ties keep the default order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObjectivePriorities {
+    pub city: f64,
+    pub settlement: f64,
+    pub road: f64,
+    pub dev_card: f64,
+    pub bank_trade: f64,
+}
+
+/// Linear weights of the initial placement score components.
+///
+/// Default weights rank placements lexicographically by new resource types,
+/// new resource probability points, resource types and probability points.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InitialSettlementWeights {
+    pub new_resource_types: f64,
+    pub new_resource_pts: f64,
+    pub resource_types: f64,
+    pub resource_pts: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    City,
+    Settlement,
+    Road,
+    DevCard,
+    BankTrade,
+}
+
+impl Default for ObjectivePriorities {
+    fn default() -> Self {
+        Self {
+            city: 5.0,
+            settlement: 4.0,
+            road: 3.0,
+            dev_card: 2.0,
+            bank_trade: 1.0,
+        }
+    }
+}
+
+impl ObjectivePriorities {
+    pub fn ordered(&self) -> [Objective; 5] {
+        let mut objectives = [
+            (Objective::City, self.city),
+            (Objective::Settlement, self.settlement),
+            (Objective::Road, self.road),
+            (Objective::DevCard, self.dev_card),
+            (Objective::BankTrade, self.bank_trade),
+        ];
+        objectives.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+        objectives.map(|(objective, _)| objective)
+    }
+}
+
+impl Default for InitialSettlementWeights {
+    fn default() -> Self {
+        Self {
+            new_resource_types: 1024.0,
+            new_resource_pts: 64.0,
+            resource_types: 16.0,
+            resource_pts: 1.0,
+        }
+    }
+}
+
+impl Default for GreedyWeights {
+    fn default() -> Self {
+        Self {
+            priorities: ObjectivePriorities::default(),
+            initial_settlement: InitialSettlementWeights::default(),
+            river_robber_bonus: 3.0,
+        }
+    }
 }
 
 impl PlayerNotification for GreedyAgent {}
@@ -54,52 +157,80 @@ impl PlayerRuntime for GreedyAgent {
         self.id
     }
 
-    fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction {
-        let action =
-            greedy_init_stage_action(&context, self.id, self.first_initial_resources.as_ref());
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
+        let action = greedy_init_stage_action(
+            &context,
+            self.id,
+            self.first_initial_resources.as_ref(),
+            &self.weights,
+        );
         if self.first_initial_resources.is_none() {
             self.first_initial_resources = Some(initial_settlement_resources(
                 action.establishment_position,
                 context.public.board,
             ));
         }
-        action
+        Ok(action)
     }
 
-    fn init_action(&mut self, context: PlayerDecisionContext<'_>) -> InitAction {
-        greedy_init_action(context, self.id)
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
+        Ok(greedy_init_action(context, self.id))
     }
 
-    fn after_dice_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDiceAction {
-        greedy_after_dice_action(context, self.id)
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
+        Ok(greedy_after_dice_action(context, self.id, &self.weights))
     }
 
-    fn after_dev_card_action(&mut self, _context: PlayerDecisionContext<'_>) -> PostDevCardAction {
-        PostDevCardAction::RollDice
+    fn after_dev_card_action(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
+        Ok(PostDevCardAction::RollDice)
     }
 
-    fn regular_action(&mut self, context: PlayerDecisionContext<'_>) -> RegularAction {
-        greedy_regular_action(&context, self.id)
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
+        Ok(greedy_regular_action(&context, self.id, &self.weights))
     }
 
-    fn move_robbers(&mut self, context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
-        greedy_move_robbers(context)
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
+        Ok(greedy_move_robbers(context, &self.weights))
     }
 
     fn choose_player_to_rob(
         &mut self,
         context: PlayerDecisionContext<'_>,
         robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction {
-        greedy_choose_player_to_rob(context, robber_pos)
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
+        Ok(greedy_choose_player_to_rob(context, robber_pos))
     }
 
-    fn answer_trade(&mut self, _context: PlayerDecisionContext<'_>) -> TradeAnswer {
-        TradeAnswer::Decline
+    fn answer_trade(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        Ok(TradeAnswer::Decline)
     }
 
-    fn drop_half(&mut self, context: PlayerDecisionContext<'_>) -> DropHalfAction {
-        greedy_drop_half(context)
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
+        Ok(greedy_drop_half(context))
     }
 }
 
@@ -114,73 +245,93 @@ pub fn greedy_choose_player_to_rob(
     lazy::lazy_choose_player_to_rob(context, robber_pos) // TODO: try to peek the most wanted card
 }
 
-pub fn greedy_move_robbers(context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
+pub fn greedy_move_robbers(
+    context: PlayerDecisionContext<'_>,
+    weights: &GreedyWeights,
+) -> MoveRobbersAction {
     let hex = match context.counting() {
         // blocking max amount of players with the most producing hex
-        CountingMode::Human => most_occupied_producing_tile(context),
-        CountingMode::Counting => most_occupied_producing_tile(context), // TODO: try to peek the most wanted card
+        CountingMode::Human => most_occupied_producing_tile(context, weights),
+        CountingMode::Counting => most_occupied_producing_tile(context, weights), // TODO: try to peek the most wanted card
     };
 
     MoveRobbersAction(hex)
 }
 
-pub fn most_occupied_producing_tile(context: PlayerDecisionContext<'_>) -> Hex {
+pub fn most_occupied_producing_tile(
+    context: PlayerDecisionContext<'_>,
+    weights: &GreedyWeights,
+) -> Hex {
     use catan_core::gameplay::primitives::Tile;
 
+    let score = |hex: Hex| {
+        (
+            context
+                .public
+                .players_on_hex(hex)
+                .iter()
+                .filter(|&&id| id != context.actor)
+                .count(),
+            match context.public.board.arrangement[hex] {
+                Tile::Resource { number, .. } => number.prob_pts() as f64,
+                Tile::River { number } => number.prob_pts() as f64 + weights.river_robber_bonus,
+                Tile::Desert => 0.0,
+            },
+        )
+    };
+
     context
         .public
         .board
         .arrangement
         .hex_iter()
         .filter(|&h| h != context.public.board_state.robber_pos)
-        .sorted_by_key(|&hex| {
-            std::cmp::Reverse((
-                context
-                    .public
-                    .players_on_hex(hex)
-                    .iter()
-                    .filter(|&&id| id != context.actor)
-                    .count(),
-                match context.public.board.arrangement[hex] {
-                    Tile::Resource { number, .. } => number.prob_pts(),
-                    Tile::River { number } => number.prob_pts() + 3, /* some random *magic* */
-                    Tile::Desert => 0,
-                },
-            ))
+        .map(|hex| (hex, score(hex)))
+        .reduce(|best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
         })
-        .next()
+        .map(|(hex, _)| hex)
         .expect("some hex must be occupied by at least one other player")
 }
 
 pub fn greedy_after_dice_action(
     context: PlayerDecisionContext<'_>,
     player_id: PlayerId,
+    weights: &GreedyWeights,
 ) -> PostDiceAction {
     if let Some(usage) = legal::legal_dev_card_usages(&context).into_iter().next() {
         PostDiceAction::UseDevCard(usage)
     } else {
-        PostDiceAction::RegularAction(greedy_regular_action(&context, player_id))
+        PostDiceAction::RegularAction(greedy_regular_action(&context, player_id, weights))
     }
 }
 
 pub fn greedy_regular_action(
     context: &PlayerDecisionContext<'_>,
     player_id: PlayerId,
+    weights: &GreedyWeights,
 ) -> RegularAction {
-    if let Some(build) = best_city_build(context, player_id) {
-        return RegularAction::Build(build);
-    }
-    if let Some(build) = best_settlement_build(context, player_id) {
-        return RegularAction::Build(build);
-    }
-    if let Some(build) = best_road_build(context, player_id) {
-        return RegularAction::Build(build);
-    }
-    if legal::can_buy_dev_card(context) {
-        return RegularAction::BuyDevCard;
-    }
-    if let Some(trade) = best_bank_trade(context, player_id) {
-        return RegularAction::TradeWithBank(trade);
+    for objective in weights.priorities.ordered() {
+        let action = match objective {
+            Objective::City => best_city_build(context, player_id).map(RegularAction::Build),
+            Objective::Settlement => {
+                best_settlement_build(context, player_id).map(RegularAction::Build)
+            }
+            Objective::Road => best_road_build(context, player_id).map(RegularAction::Build),
+            Objective::DevCard => {
+                legal::can_buy_dev_card(context).then_some(RegularAction::BuyDevCard)
+            }
+            Objective::BankTrade => {
+                best_bank_trade(context, player_id, weights).map(RegularAction::TradeWithBank)
+            }
+        };
+        if let Some(action) = action {
+            return action;
+        }
     }
     RegularAction::EndMove
 }
@@ -197,6 +348,7 @@ pub fn greedy_init_stage_action(
     context: &PlayerDecisionContext<'_>,
     player_id: PlayerId,
     already_acquired: Option<&BTreeSet<Resource>>,
+    weights: &GreedyWeights,
 ) -> InitStageAction {
     let (establishment, road) = context
         .public
@@ -204,9 +356,17 @@ pub fn greedy_init_stage_action(
         .query()
         .possible_initial_placements(context.public.board, player_id)
         .into_iter()
-        .max_by_key(|(establishment, _)| {
-            initial_settlement_score(context.public.board, *establishment, already_acquired)
+        .map(|(establishment, road)| {
+            let score = initial_settlement_score(
+                context.public.board,
+                establishment,
+                already_acquired,
+                &weights.initial_settlement,
+            );
+            (establishment, road, score)
         })
+        .max_by(|(_, _, lhs), (_, _, rhs)| lhs.total_cmp(rhs))
+        .map(|(establishment, road, _)| (establishment, road))
         .expect("there must be an initial placement");
 
     InitStageAction {
@@ -236,79 +396,92 @@ fn best_settlement_build(
 }
 
 fn best_road_build(context: &PlayerDecisionContext<'_>, player_id: PlayerId) -> Option<Build> {
-    let roads = legal::legal_road_spots(context, player_id);
-    let Some(search) = &context.search else {
-        return roads.into_iter().next();
-    };
-    let seed = search.make_owned();
+    best_by_lookahead(
+        context,
+        player_id,
+        legal::legal_road_spots(context, player_id),
+        RegularAction::Build,
+        |context| legal::legal_settlement_spots(context, player_id).len(),
+    )
+}
 
-    roads.into_iter().max_by_key(|build| {
-        let mut state = seed.state.clone();
-        if state.build(player_id, *build).is_err() {
-            return 0;
-        }
-        let index = GameIndex::rebuild(&state);
-        let visibility = VisibilityConfig::default();
-        let factory = ContextFactory {
-            state: &state,
-            index: &index,
-            visibility: &visibility,
-        };
-        let search = Some(SearchFactory::new(
-            &state,
-            visibility.player_policy(player_id),
-            player_id,
-        ));
-        let context = factory.player_decision_context(player_id, search);
-        legal::legal_settlement_spots(&context, player_id).len()
-    })
+fn best_bank_trade(
+    context: &PlayerDecisionContext<'_>,
+    player_id: PlayerId,
+    weights: &GreedyWeights,
+) -> Option<BankTrade> {
+    best_by_lookahead(
+        context,
+        player_id,
+        legal::legal_bank_trades(context),
+        RegularAction::TradeWithBank,
+        |context| next_objective_score(context, player_id, weights),
+    )
 }
 
-fn best_bank_trade(context: &PlayerDecisionContext<'_>, player_id: PlayerId) -> Option<BankTrade> {
-    let trades = legal::legal_bank_trades(context);
+/// Picks the candidate whose resulting position scores best. Each candidate
+/// is applied to one owned copy of the state and undone afterwards; the index
+/// is refreshed for the acting player only instead of being rebuilt.
+fn best_by_lookahead<T: Copy, K: Ord + Default>(
+    context: &PlayerDecisionContext<'_>,
+    player_id: PlayerId,
+    candidates: Vec<T>,
+    action: impl Fn(T) -> RegularAction,
+    score: impl Fn(&PlayerDecisionContext<'_>) -> K,
+) -> Option<T> {
     let Some(search) = &context.search else {
-        return trades.into_iter().next();
+        return candidates.into_iter().next();
     };
-    let seed = search.make_owned();
+    let mut state = search.make_owned().state;
+    let mut index = GameIndex::rebuild(&state);
+    let visibility = VisibilityConfig::default();
 
-    trades.into_iter().max_by_key(|trade| {
-        let mut state = seed.state.clone();
-        if state.trade_with_bank(player_id, *trade).is_err() {
-            return (0, 0);
-        }
-        let index = GameIndex::rebuild(&state);
-        let visibility = VisibilityConfig::default();
+    candidates.into_iter().max_by_key(|candidate| {
+        let Ok(undo) = state.apply_reversible(player_id, action(*candidate)) else {
+            return K::default();
+        };
+        index.refresh_player(&state, player_id);
         let factory = ContextFactory {
             state: &state,
             index: &index,
             visibility: &visibility,
         };
-        let search = Some(SearchFactory::new(
-            &state,
-            visibility.player_policy(player_id),
-            player_id,
-        ));
-        let context = factory.player_decision_context(player_id, search);
-        next_objective_score(&context, player_id)
+        let search = SearchFactory::new(&state, visibility.player_policy(player_id), player_id);
+        let key = score(&factory.player_decision_context(player_id, Some(search)));
+        state.undo(undo);
+        index.refresh_player(&state, player_id);
+        key
     })
 }
 
-fn next_objective_score(context: &PlayerDecisionContext<'_>, player_id: PlayerId) -> (u8, usize) {
-    if !legal::legal_city_spots(context, player_id).is_empty() {
-        return (4, legal::legal_city_spots(context, player_id).len());
-    }
-    if !legal::legal_settlement_spots(context, player_id).is_empty() {
-        return (3, legal::legal_settlement_spots(context, player_id).len());
-    }
-    if !legal::legal_road_spots(context, player_id).is_empty() {
-        return (2, legal::legal_road_spots(context, player_id).len());
-    }
-    if context
-        .private
-        .resources
-        .has_enough(&constants::costs::DEV_CARD)
-    {
-        return (1, 1);
+fn next_objective_score(
+    context: &PlayerDecisionContext<'_>,
+    player_id: PlayerId,
+    weights: &GreedyWeights,
+) -> (usize, usize) {
+    let objectives = weights
+        .priorities
+        .ordered()
+        .into_iter()
+        .filter(|objective| *objective != Objective::BankTrade)
+        .collect::<Vec<_>>();
+
+    for (rank, objective) in objectives.iter().enumerate() {
+        let count = match objective {
+            Objective::City => legal::legal_city_spots(context, player_id).len(),
+            Objective::Settlement => legal::legal_settlement_spots(context, player_id).len(),
+            Objective::Road => legal::legal_road_spots(context, player_id).len(),
+            Objective::DevCard => usize::from(
+                context
+                    .private
+                    .resources
+                    .has_enough(&constants::costs::DEV_CARD),
+            ),
+            Objective::BankTrade => unreachable!("bank trades are filtered out above"),
+        };
+        if count > 0 {
+            return (objectives.len() - rank, count);
+        }
     }
     (0, 0)
 }
@@ -317,7 +490,8 @@ fn initial_settlement_score(
     board: &catan_core::gameplay::field::state::BoardLayout,
     establishment: Establishment,
     already_acquired: Option<&BTreeSet<Resource>>,
-) -> (usize, u16, usize, u16) {
+    weights: &InitialSettlementWeights,
+) -> f64 {
     let resources = settlement_resource_scores(board, establishment);
     let new_resources = resources
         .iter()
@@ -328,12 +502,10 @@ fn initial_settlement_score(
         })
         .collect::<Vec<_>>();
 
-    (
-        new_resources.len(),
-        new_resources.iter().map(|(_, pts)| *pts).sum::<u16>(),
-        resources.len(),
-        resources.iter().map(|(_, pts)| *pts).sum::<u16>(),
-    )
+    weights.new_resource_types * new_resources.len() as f64
+        + weights.new_resource_pts * new_resources.iter().map(|(_, pts)| *pts).sum::<u16>() as f64
+        + weights.resource_types * resources.len() as f64
+        + weights.resource_pts * resources.iter().map(|(_, pts)| *pts).sum::<u16>() as f64
 }
 
 fn production_score_for_settlement(
@@ -377,3 +549,58 @@ fn settlement_resource_scores(
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{GreedyWeights, Objective, ObjectivePriorities};
+
+    #[test]
+    fn default_priorities_keep_original_order() {
+        assert_eq!(
+            ObjectivePriorities::default().ordered(),
+            [
+                Objective::City,
+                Objective::Settlement,
+                Objective::Road,
+                Objective::DevCard,
+                Objective::BankTrade,
+            ]
+        );
+    }
+
+    #[test]
+    fn priorities_reorder_objectives_and_ties_keep_default_order() {
+        let priorities = ObjectivePriorities {
+            dev_card: 10.0,
+            road: 4.0,
+            ..ObjectivePriorities::default()
+        };
+
+        assert_eq!(
+            priorities.ordered(),
+            [
+                Objective::DevCard,
+                Objective::City,
+                Objective::Settlement,
+                Objective::Road,
+                Objective::BankTrade,
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_weights_config_falls_back_to_defaults() {
+        let weights: GreedyWeights = serde_json::from_str(
+            r#"{ "priorities": { "bank_trade": 9.5 }, "river_robber_bonus": 0.0 }"#,
+        )
+        .unwrap();
+
+        assert_eq!(weights.priorities.bank_trade, 9.5);
+        assert_eq!(weights.priorities.city, ObjectivePriorities::default().city);
+        assert_eq!(weights.river_robber_bonus, 0.0);
+        assert_eq!(
+            weights.initial_settlement,
+            GreedyWeights::default().initial_settlement
+        );
+    }
+}
@@ -5,7 +5,7 @@ use catan_core::{
             ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
             MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
         },
-        agent::PlayerRuntime,
+        agent::{AgentError, PlayerRuntime},
     },
     gameplay::{
         game::{event::PlayerNotification, view::PlayerDecisionContext},
@@ -14,22 +14,36 @@ use catan_core::{
     topology::{Hex, HexIndex},
 };
 use rand::{
-    RngExt,
-    rngs::ThreadRng,
+    Rng, RngExt, SeedableRng,
+    rngs::SmallRng,
     seq::{IndexedRandom, IteratorRandom},
 };
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct RandomAgent {
     id: PlayerId,
-    rng: ThreadRng,
+    rng: SmallRng,
+}
+
+impl Default for RandomAgent {
+    fn default() -> Self {
+        Self::new(PlayerId::default())
+    }
 }
 
 impl RandomAgent {
     pub fn new(id: PlayerId) -> Self {
         Self {
             id,
-            rng: rand::rng(),
+            rng: SmallRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Agent whose choices are fully determined by `seed`.
+    pub fn with_seed(id: PlayerId, seed: u64) -> Self {
+        Self {
+            id,
+            rng: SmallRng::seed_from_u64(seed),
         }
     }
 }
@@ -41,50 +55,78 @@ impl PlayerRuntime for RandomAgent {
         self.id
     }
 
-    fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction {
-        rand_init_stage_action(context, &mut self.rng)
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
+        Ok(rand_init_stage_action(context, &mut self.rng))
     }
 
-    fn init_action(&mut self, context: PlayerDecisionContext<'_>) -> InitAction {
-        rand_init_action(context, &mut self.rng)
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
+        Ok(rand_init_action(context, &mut self.rng))
     }
 
-    fn after_dice_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDiceAction {
-        rand_after_dice_action(context, &mut self.rng)
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
+        Ok(rand_after_dice_action(context, &mut self.rng))
     }
 
-    fn after_dev_card_action(&mut self, _context: PlayerDecisionContext<'_>) -> PostDevCardAction {
-        PostDevCardAction::RollDice
+    fn after_dev_card_action(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
+        Ok(PostDevCardAction::RollDice)
     }
 
-    fn regular_action(&mut self, context: PlayerDecisionContext<'_>) -> RegularAction {
-        rand_regular_action(context, &mut self.rng)
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
+        Ok(rand_regular_action(context, &mut self.rng))
     }
 
-    fn move_robbers(&mut self, context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
-        rand_move_robbers(context, &mut self.rng)
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
+        Ok(rand_move_robbers(context, &mut self.rng))
     }
 
     fn choose_player_to_rob(
         &mut self,
         context: PlayerDecisionContext<'_>,
         robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction {
-        rand_choose_player_to_rob(context, robber_pos, &mut self.rng)
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
+        Ok(rand_choose_player_to_rob(
+            context,
+            robber_pos,
+            &mut self.rng,
+        ))
     }
 
-    fn answer_trade(&mut self, _context: PlayerDecisionContext<'_>) -> TradeAnswer {
-        TradeAnswer::Decline
+    fn answer_trade(
+        &mut self,
+        _context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        Ok(TradeAnswer::Decline)
     }
 
-    fn drop_half(&mut self, context: PlayerDecisionContext<'_>) -> DropHalfAction {
-        rand_drop_half(context, &mut self.rng)
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
+        Ok(rand_drop_half(context, &mut self.rng))
     }
 }
 
 pub fn rand_init_stage_action(
     context: PlayerDecisionContext<'_>,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
 ) -> InitStageAction {
     let (establishment, road) = context
         .public
@@ -101,7 +143,7 @@ pub fn rand_init_stage_action(
     }
 }
 
-pub fn rand_init_action(context: PlayerDecisionContext<'_>, rng: &mut ThreadRng) -> InitAction {
+pub fn rand_init_action(context: PlayerDecisionContext<'_>, rng: &mut impl Rng) -> InitAction {
     if let usages = legal::legal_dev_card_usages(&context)
         && !usages.is_empty()
         && rng.random_bool(0.8)
@@ -114,7 +156,7 @@ pub fn rand_init_action(context: PlayerDecisionContext<'_>, rng: &mut ThreadRng)
 
 pub fn rand_after_dice_action(
     context: PlayerDecisionContext<'_>,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
 ) -> PostDiceAction {
     if let usages = legal::legal_dev_card_usages(&context)
         && !usages.is_empty()
@@ -128,7 +170,7 @@ pub fn rand_after_dice_action(
 
 pub fn rand_regular_action(
     context: PlayerDecisionContext<'_>,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
 ) -> RegularAction {
     legal::legal_regular_action(&context)
         .choose(rng)
@@ -138,7 +180,7 @@ pub fn rand_regular_action(
 
 pub fn rand_move_robbers(
     context: PlayerDecisionContext<'_>,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
 ) -> MoveRobbersAction {
     let n = context.public.board.arrangement.len();
     let tile_index = match rng.random_range(0..n - 1) {
@@ -152,7 +194,7 @@ pub fn rand_move_robbers(
 pub fn rand_choose_player_to_rob(
     context: PlayerDecisionContext<'_>,
     robber_pos: Hex,
-    rng: &mut ThreadRng,
+    rng: &mut impl Rng,
 ) -> ChoosePlayerToRobAction {
     let id = context
         .public
@@ -169,7 +211,7 @@ pub fn rand_answer_trade(_context: PlayerDecisionContext<'_>) -> TradeAnswer {
     TradeAnswer::Decline
 }
 
-pub fn rand_drop_half(context: PlayerDecisionContext<'_>, _rng: &mut ThreadRng) -> DropHalfAction {
+pub fn rand_drop_half(context: PlayerDecisionContext<'_>, rng: &mut impl Rng) -> DropHalfAction {
     let number_to_drop = context.private.resources.total() / 2;
 
     match context.search {
@@ -185,7 +227,7 @@ pub fn rand_drop_half(context: PlayerDecisionContext<'_>, _rng: &mut ThreadRng)
 
             for _ in 0..number_to_drop {
                 let card = res
-                    .pop_random()
+                    .pop_random(rng)
                     .expect(&format!("must contain {} cards", number_to_drop));
                 to_drop[card] += 1;
             }
@@ -1,25 +1,37 @@
 mod client;
+mod delta;
 mod model;
 mod protocol;
+mod stream;
 
 pub use client::{RemoteCliAgent, RemoteCliObserver};
+pub use delta::{KEYFRAME_INTERVAL, UiChange, UiModelDelta, ViewDecoder, ViewEncoder};
 pub use model::{
     UiBoard, UiModel, UiOmniscient, UiPlayerBuilds, UiPrivatePlayer, UiPublicBank,
     UiPublicBankResources, UiPublicGame, UiPublicPlayer, UiPublicPlayerResources, ui_model_summary,
 };
 pub use protocol::{
-    CliRole, CliToHost, DecisionRequestEnvelope, DecisionRequestFrame, DecisionResponseFrame,
-    HostToCli, LegalBuildOptions, LegalDecisionOptions, MAX_FRAME_LEN, NonblockingFrameReader,
-    RemoteLogLevel, read_frame, write_frame,
+    Capability, CliRole, CliToHost, DecisionRequestEnvelope, DecisionRequestFrame,
+    DecisionResponseFrame, Encoding, HOST_CAPABILITIES, HostToCli, LegalBuildOptions,
+    LegalDecisionOptions, MAX_FRAME_LEN, MIN_PROTOCOL_VERSION, NonblockingFrameReader,
+    PROTOCOL_VERSION, RemoteLogLevel, SOCKET_HOST_CAPABILITIES, negotiate, read_encoded_frame,
+    read_frame, write_encoded_frame, write_frame,
 };
+pub use stream::CliStream;
 
 #[cfg(test)]
 mod tests {
     use super::{
-        CliRole, CliToHost, HostToCli, LegalDecisionOptions, NonblockingFrameReader,
-        RemoteCliObserver, RemoteLogLevel, UiBoard, UiModel, read_frame, write_frame,
+        Capability, CliRole, CliToHost, DecisionResponseFrame, Encoding, HOST_CAPABILITIES,
+        HostToCli, LegalDecisionOptions, NonblockingFrameReader, PROTOCOL_VERSION, RemoteCliAgent,
+        RemoteCliObserver, RemoteLogLevel, UiBoard, UiModel, read_encoded_frame, read_frame,
+        write_encoded_frame, write_frame,
     };
     use catan_core::gameplay::{
+        agent::{
+            action::{InitAction, PostDiceAction, RegularAction},
+            agent::{AgentError, PlayerRuntime},
+        },
         game::{
             event::{GameEvent, GameObserver, ObserverKind, ObserverNotificationContext},
             index::GameIndex,
@@ -40,6 +52,20 @@ mod tests {
         assert_eq!(value, "hello");
     }
 
+    #[test]
+    fn message_pack_frame_round_trip() {
+        let mut bytes = Vec::new();
+        let hello = HostToCli::socket_hello(CliRole::Player { player_id: 1 });
+        write_encoded_frame(&mut bytes, &hello, Encoding::MessagePack).unwrap();
+        let value: HostToCli =
+            read_encoded_frame(&mut bytes.as_slice(), Encoding::MessagePack).unwrap();
+        assert_eq!(
+            serde_json::to_value(value).unwrap(),
+            serde_json::to_value(hello).unwrap()
+        );
+        assert!(read_frame::<HostToCli>(&mut bytes.as_slice()).is_err());
+    }
+
     #[test]
     fn nonblocking_frame_reader_round_trip() {
         let mut bytes = Vec::new();
@@ -117,9 +143,7 @@ mod tests {
             visibility: &visibility,
         };
         let model = UiModel::from_decision(&factory.player_decision_context(0, None));
-        let msg = HostToCli::Hello {
-            role: CliRole::Spectator,
-        };
+        let msg = HostToCli::hello(CliRole::Spectator, None);
 
         serde_json::to_vec(&board).unwrap();
         serde_json::to_vec(&model).unwrap();
@@ -405,4 +429,170 @@ mod tests {
         }
         writer.join().unwrap();
     }
+
+    #[test]
+    fn version_1_frames_are_still_understood() {
+        // a version 1 host greets without version and capabilities
+        let hello =
+            serde_json::from_str::<HostToCli>(r#"{"Hello":{"role":{"Player":{"player_id":2}}}}"#)
+                .unwrap();
+        let HostToCli::Hello {
+            version,
+            capabilities,
+            ..
+        } = hello
+        else {
+            panic!("expected hello, got {hello:?}");
+        };
+        assert_eq!(version, 1);
+        assert_eq!(capabilities, [Capability::Trades]);
+        assert!(matches!(
+            CliToHost::answer_hello(version, vec![Capability::Trades]),
+            CliToHost::Ready
+        ));
+
+        // and a version 1 client answers with a bare ready
+        let ready = serde_json::from_str::<CliToHost>(r#""Ready""#).unwrap();
+        assert_eq!(
+            ready.negotiate(HOST_CAPABILITIES),
+            Some(Ok(vec![Capability::Trades]))
+        );
+    }
+
+    #[test]
+    fn capabilities_are_negotiated_and_incompatible_clients_refused() {
+        let reply = serde_json::from_str::<CliToHost>(
+            r#"{"Hello":{"version":2,"capabilities":["diffs","telepathy"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            reply.negotiate(HOST_CAPABILITIES),
+            Some(Ok(vec![Capability::Diffs]))
+        );
+        assert_eq!(reply.negotiate(&[Capability::Trades]), Some(Ok(Vec::new())));
+
+        let future = CliToHost::Hello {
+            version: PROTOCOL_VERSION + 1,
+            capabilities: Vec::new(),
+        };
+        let refusal = future.negotiate(HOST_CAPABILITIES).unwrap().unwrap_err();
+        assert!(refusal.contains(&format!("version {}", PROTOCOL_VERSION + 1)));
+        assert!(
+            CliToHost::Error {
+                message: String::new()
+            }
+            .negotiate(HOST_CAPABILITIES)
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn remote_agents_refuse_incompatible_clients_with_a_reason() {
+        let (host, mut client) = std::os::unix::net::UnixStream::pair().unwrap();
+        let cli = std::thread::spawn(move || {
+            let hello = read_frame::<HostToCli>(&mut client).unwrap();
+            assert!(matches!(
+                hello,
+                HostToCli::Hello {
+                    version: PROTOCOL_VERSION,
+                    ..
+                }
+            ));
+            write_frame(
+                &mut client,
+                &CliToHost::Hello {
+                    version: 0,
+                    capabilities: Vec::new(),
+                },
+            )
+            .unwrap();
+            read_frame::<HostToCli>(&mut client).unwrap()
+        });
+
+        let err = RemoteCliAgent::new(0, host).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        match cli.join().unwrap() {
+            HostToCli::Shutdown { reason } => assert_eq!(reason, err.to_string()),
+            other => panic!("expected shutdown, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn remote_agents_fail_decisions_of_closed_terminals() {
+        let (host, mut client) = std::os::unix::net::UnixStream::pair().unwrap();
+        let cli = std::thread::spawn(move || {
+            read_frame::<HostToCli>(&mut client).unwrap();
+            write_frame(
+                &mut client,
+                &CliToHost::answer_hello(PROTOCOL_VERSION, Vec::new()),
+            )
+            .unwrap();
+        });
+        let mut agent = RemoteCliAgent::new(0, host).unwrap();
+        cli.join().unwrap();
+
+        let state = GameInitializationState::default().finish();
+        let index = GameIndex::rebuild(&state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state: &state,
+            index: &index,
+            visibility: &visibility,
+        };
+        let context = factory.player_decision_context(0, None);
+
+        assert!(matches!(
+            agent.init_action(context),
+            Err(AgentError::Disconnected(_))
+        ));
+    }
+
+    #[test]
+    fn remote_agents_give_up_on_their_clock_and_skip_the_late_answer() {
+        let (host, mut client) = std::os::unix::net::UnixStream::pair().unwrap();
+        let cli = std::thread::spawn(move || {
+            read_frame::<HostToCli>(&mut client).unwrap();
+            write_frame(
+                &mut client,
+                &CliToHost::answer_hello(PROTOCOL_VERSION, Vec::new()),
+            )
+            .unwrap();
+            let HostToCli::DecisionRequest(_) = read_frame::<HostToCli>(&mut client).unwrap()
+            else {
+                panic!("expected a decision request");
+            };
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            for response in [
+                DecisionResponseFrame::InitAction(InitAction::RollDice),
+                DecisionResponseFrame::PostDice(PostDiceAction::RegularAction(
+                    RegularAction::EndMove,
+                )),
+            ] {
+                write_frame(&mut client, &CliToHost::DecisionResponse(response)).unwrap();
+            }
+        });
+        let mut agent = RemoteCliAgent::new(0, host).unwrap();
+
+        let state = GameInitializationState::default().finish();
+        let index = GameIndex::rebuild(&state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state: &state,
+            index: &index,
+            visibility: &visibility,
+        };
+        let mut context = factory.player_decision_context(0, None);
+        context.public.players[0].clock = Some(std::time::Duration::from_millis(20));
+        assert!(matches!(
+            agent.init_action(context),
+            Err(AgentError::TimedOut)
+        ));
+
+        let context = factory.player_decision_context(0, None);
+        assert!(matches!(
+            agent.after_dice_action(context),
+            Ok(PostDiceAction::RegularAction(RegularAction::EndMove))
+        ));
+        cli.join().unwrap();
+    }
 }
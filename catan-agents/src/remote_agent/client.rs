@@ -1,4 +1,7 @@
-use std::{io, os::unix::net::UnixStream};
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 use catan_core::{
     agent::{
@@ -6,7 +9,7 @@ use catan_core::{
             ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
             MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
         },
-        agent::PlayerRuntime,
+        agent::{AgentError, PlayerRuntime},
     },
     gameplay::{
         game::{
@@ -22,56 +25,118 @@ use catan_core::{
 };
 
 use super::{
+    delta::ViewEncoder,
     model::{UiModel, ui_model_summary},
     protocol::{
-        CliRole, CliToHost, DecisionRequestEnvelope, DecisionRequestFrame, DecisionResponseFrame,
-        HostToCli, LegalDecisionOptions, read_frame, write_frame,
+        Capability, CliRole, CliToHost, DecisionRequestEnvelope, DecisionRequestFrame,
+        DecisionResponseFrame, Encoding, HostToCli, LegalDecisionOptions, SOCKET_HOST_CAPABILITIES,
+        read_encoded_frame, read_frame, write_encoded_frame, write_frame,
     },
+    stream::CliStream,
 };
 
 pub struct RemoteCliAgent {
     player_id: PlayerId,
-    stream: UnixStream,
+    stream: CliStream,
+    /// Negotiated in the handshake.
+    capabilities: Vec<Capability>,
+    encoding: Encoding,
+    /// Set when the client wants event views as deltas.
+    views: Option<ViewEncoder>,
     next_request_id: u64,
+    /// Requests that ran out of time; their answers are skipped if they
+    /// come in after all.
+    late_answers: u64,
 }
 
 impl RemoteCliAgent {
-    pub fn new(player_id: PlayerId, mut stream: UnixStream) -> io::Result<Self> {
+    pub fn new(player_id: PlayerId, stream: impl Into<CliStream>) -> io::Result<Self> {
+        let mut stream = stream.into();
         write_frame(
             &mut stream,
-            &HostToCli::Hello {
-                role: CliRole::Player { player_id },
-            },
+            &HostToCli::socket_hello(CliRole::Player { player_id }),
         )?;
-        expect_ready(&mut stream)?;
+        let capabilities = handshake(&mut stream)?;
         Ok(Self {
             player_id,
             stream,
+            encoding: Encoding::negotiated(&capabilities),
+            views: view_encoder(&capabilities),
+            capabilities,
             next_request_id: 0,
+            late_answers: 0,
         })
     }
 
-    fn request(&mut self, request: DecisionRequestFrame) -> DecisionResponseFrame {
+    /// Sends `request` and waits for the answer, for at most `clock` in
+    /// timed games. A lost connection or a client that answers out of turn
+    /// fails the decision instead of the host.
+    fn request(
+        &mut self,
+        request: DecisionRequestFrame,
+        clock: Option<Duration>,
+    ) -> Result<DecisionResponseFrame, AgentError> {
+        if clock.is_some_and(|clock| clock.is_zero()) {
+            return Err(AgentError::TimedOut);
+        }
+        let deadline = clock.map(|clock| Instant::now() + clock);
         let request_id = request.request_id();
         let kind = request.kind();
         log::trace!(
             target: "catan_agents::remote_agent",
             "sending CLI decision request id={request_id} kind={kind}"
         );
-        write_frame(&mut self.stream, &HostToCli::DecisionRequest(request))
-            .expect("failed to write CLI decision request");
-        loop {
-            match read_frame::<CliToHost>(&mut self.stream).expect("failed to read CLI response") {
-                CliToHost::DecisionResponse(response) => {
-                    log::trace!(
+        write_encoded_frame(
+            &mut self.stream,
+            &HostToCli::DecisionRequest(request),
+            self.encoding,
+        )
+        .map_err(|err| disconnected("failed to write CLI decision request", err))?;
+        let frame = loop {
+            let timeout = deadline.map(|deadline| {
+                // a zero timeout would mean none at all
+                deadline
+                    .saturating_duration_since(Instant::now())
+                    .max(Duration::from_millis(1))
+            });
+            self.stream
+                .set_read_timeout(timeout)
+                .map_err(|err| disconnected("failed to time the CLI response", err))?;
+            match read_encoded_frame::<CliToHost>(&mut self.stream, self.encoding) {
+                Ok(CliToHost::DecisionResponse(_)) if self.late_answers > 0 => {
+                    self.late_answers -= 1;
+                    log::debug!(
                         target: "catan_agents::remote_agent",
-                        "received CLI decision response id={request_id} kind={kind}"
+                        "skipped a CLI response that came in after its clock ran out"
                     );
-                    return response;
                 }
-                CliToHost::Error { message } => panic!("remote CLI error: {message}"),
-                other => panic!("unexpected CLI frame on game socket: {other:?}"),
+                Ok(frame) => break frame,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    self.late_answers += 1;
+                    return Err(AgentError::TimedOut);
+                }
+                Err(err) => return Err(disconnected("failed to read CLI response", err)),
             }
+        };
+        match frame {
+            CliToHost::DecisionResponse(response) => {
+                log::trace!(
+                    target: "catan_agents::remote_agent",
+                    "received CLI decision response id={request_id} kind={kind}"
+                );
+                Ok(response)
+            }
+            CliToHost::Error { message } => {
+                Err(AgentError::Protocol(format!("remote CLI error: {message}")))
+            }
+            other => Err(AgentError::Protocol(format!(
+                "unexpected CLI frame on game socket: {other:?}"
+            ))),
         }
     }
 
@@ -93,13 +158,8 @@ impl RemoteCliAgent {
 impl PlayerNotification for RemoteCliAgent {
     fn on_event(&mut self, event: &GameEvent, context: PlayerNotificationContext<'_>) {
         let model = UiModel::from_player_notification(&context);
-        let _ = write_frame(
-            &mut self.stream,
-            &HostToCli::Event {
-                event: event.clone(),
-                view: model,
-            },
-        );
+        let frame = event_frame(&mut self.views, event, model);
+        let _ = write_encoded_frame(&mut self.stream, &frame, self.encoding);
     }
 }
 
@@ -108,51 +168,69 @@ impl PlayerRuntime for RemoteCliAgent {
         self.player_id
     }
 
-    fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction {
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::InitStage(envelope)) {
-            DecisionResponseFrame::InitStage(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::InitStage(envelope), context.clock())? {
+            DecisionResponseFrame::InitStage(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn init_action(&mut self, context: PlayerDecisionContext<'_>) -> InitAction {
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::InitAction(envelope)) {
-            DecisionResponseFrame::InitAction(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::InitAction(envelope), context.clock())? {
+            DecisionResponseFrame::InitAction(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn after_dice_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDiceAction {
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::PostDice(envelope)) {
-            DecisionResponseFrame::PostDice(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::PostDice(envelope), context.clock())? {
+            DecisionResponseFrame::PostDice(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn after_dev_card_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDevCardAction {
+    fn after_dev_card_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::PostDevCard(envelope)) {
-            DecisionResponseFrame::PostDevCard(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::PostDevCard(envelope), context.clock())? {
+            DecisionResponseFrame::PostDevCard(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn regular_action(&mut self, context: PlayerDecisionContext<'_>) -> RegularAction {
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::Regular(envelope)) {
-            DecisionResponseFrame::Regular(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::Regular(envelope), context.clock())? {
+            DecisionResponseFrame::Regular(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn move_robbers(&mut self, context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::MoveRobbers(envelope)) {
-            DecisionResponseFrame::MoveRobbers(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::MoveRobbers(envelope), context.clock())? {
+            DecisionResponseFrame::MoveRobbers(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
@@ -160,49 +238,64 @@ impl PlayerRuntime for RemoteCliAgent {
         &mut self,
         context: PlayerDecisionContext<'_>,
         robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction {
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
         let envelope = self.envelope(&context, Some(robber_pos));
-        match self.request(DecisionRequestFrame::ChoosePlayerToRob(envelope)) {
-            DecisionResponseFrame::ChoosePlayerToRob(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(
+            DecisionRequestFrame::ChoosePlayerToRob(envelope),
+            context.clock(),
+        )? {
+            DecisionResponseFrame::ChoosePlayerToRob(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn answer_trade(&mut self, context: PlayerDecisionContext<'_>) -> TradeAnswer {
+    fn answer_trade(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        if !self.capabilities.contains(&Capability::Trades) {
+            return Ok(TradeAnswer::Decline);
+        }
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::AnswerTrade(envelope)) {
-            DecisionResponseFrame::AnswerTrade(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::AnswerTrade(envelope), context.clock())? {
+            DecisionResponseFrame::AnswerTrade(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 
-    fn drop_half(&mut self, context: PlayerDecisionContext<'_>) -> DropHalfAction {
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
         let envelope = self.envelope(&context, None);
-        match self.request(DecisionRequestFrame::DropHalf(envelope)) {
-            DecisionResponseFrame::DropHalf(action) => action,
-            other => panic!("unexpected CLI response: {other:?}"),
+        match self.request(DecisionRequestFrame::DropHalf(envelope), context.clock())? {
+            DecisionResponseFrame::DropHalf(action) => Ok(action),
+            other => Err(unexpected_response(other)),
         }
     }
 }
 
 impl Drop for RemoteCliAgent {
     fn drop(&mut self) {
-        let _ = write_frame(
+        let _ = write_encoded_frame(
             &mut self.stream,
             &HostToCli::Shutdown {
                 reason: "host dropped remote CLI agent".to_owned(),
             },
+            self.encoding,
         );
     }
 }
 
 pub struct RemoteCliObserver {
     role: CliRole,
-    stream: UnixStream,
+    stream: CliStream,
+    encoding: Encoding,
+    views: Option<ViewEncoder>,
 }
 
 impl RemoteCliObserver {
-    pub fn new(kind: ObserverKind, stream: UnixStream) -> io::Result<Self> {
+    pub fn new(kind: ObserverKind, stream: impl Into<CliStream>) -> io::Result<Self> {
         let role = match kind {
             ObserverKind::Spectator => CliRole::Spectator,
             ObserverKind::Player(player_id) => CliRole::PlayerObserver { player_id },
@@ -211,25 +304,36 @@ impl RemoteCliObserver {
         Self::new_with_role(role, stream)
     }
 
-    pub fn new_snapshot(stream: UnixStream) -> io::Result<Self> {
+    pub fn new_snapshot(stream: impl Into<CliStream>) -> io::Result<Self> {
         Self::new_with_role(CliRole::SnapshotObserver, stream)
     }
 
-    pub fn new_with_role(role: CliRole, mut stream: UnixStream) -> io::Result<Self> {
+    pub fn new_with_role(role: CliRole, stream: impl Into<CliStream>) -> io::Result<Self> {
         if matches!(role, CliRole::Player { .. }) {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "remote CLI observer requires an observer role",
             ));
         }
-        write_frame(&mut stream, &HostToCli::Hello { role: role.clone() })?;
-        expect_ready(&mut stream)?;
-        Ok(Self { role, stream })
+        let mut stream = stream.into();
+        write_frame(&mut stream, &HostToCli::socket_hello(role.clone()))?;
+        let capabilities = handshake(&mut stream)?;
+        Ok(Self {
+            role,
+            stream,
+            encoding: Encoding::negotiated(&capabilities),
+            views: view_encoder(&capabilities),
+        })
     }
 
     #[cfg(test)]
-    pub(crate) fn from_connected_role(role: CliRole, stream: UnixStream) -> Self {
-        Self { role, stream }
+    pub(crate) fn from_connected_role(role: CliRole, stream: impl Into<CliStream>) -> Self {
+        Self {
+            role,
+            stream: stream.into(),
+            encoding: Encoding::Json,
+            views: None,
+        }
     }
 }
 
@@ -253,13 +357,8 @@ impl GameObserver for RemoteCliObserver {
             include_snapshot_state,
             summary
         );
-        if let Err(err) = write_frame(
-            &mut self.stream,
-            &HostToCli::Event {
-                event: event.clone(),
-                view: model,
-            },
-        ) {
+        let frame = event_frame(&mut self.views, event, model);
+        if let Err(err) = write_encoded_frame(&mut self.stream, &frame, self.encoding) {
             log::warn!(
                 target: "catan_agents::remote_observer_flow",
                 "send failed role={} event={:?} elapsed_ms={} err={err}",
@@ -281,21 +380,59 @@ impl GameObserver for RemoteCliObserver {
 
 impl Drop for RemoteCliObserver {
     fn drop(&mut self) {
-        let _ = write_frame(
+        let _ = write_encoded_frame(
             &mut self.stream,
             &HostToCli::Shutdown {
                 reason: "host dropped remote CLI observer".to_owned(),
             },
+            self.encoding,
         );
     }
 }
 
-fn expect_ready(stream: &mut UnixStream) -> io::Result<()> {
-    match read_frame::<CliToHost>(stream)? {
-        CliToHost::Ready => Ok(()),
-        other => Err(io::Error::new(
+/// Waits for the client's answer to the hello and negotiates the
+/// capabilities in use; incompatible clients are told why before they are
+/// turned away.
+fn handshake(stream: &mut CliStream) -> io::Result<Vec<Capability>> {
+    let reply = read_frame::<CliToHost>(stream)?;
+    match reply.negotiate(SOCKET_HOST_CAPABILITIES) {
+        Some(Ok(capabilities)) => Ok(capabilities),
+        Some(Err(reason)) => {
+            let _ = write_frame(
+                stream,
+                &HostToCli::Shutdown {
+                    reason: reason.clone(),
+                },
+            );
+            Err(io::Error::new(io::ErrorKind::InvalidData, reason))
+        }
+        None => Err(io::Error::new(
             io::ErrorKind::InvalidData,
-            format!("expected ready, got {other:?}"),
+            format!("expected ready, got {reply:?}"),
         )),
     }
 }
+
+fn disconnected(what: &str, err: io::Error) -> AgentError {
+    AgentError::Disconnected(format!("{what}: {err}"))
+}
+
+fn unexpected_response(response: DecisionResponseFrame) -> AgentError {
+    AgentError::Protocol(format!("unexpected CLI response: {response:?}"))
+}
+
+fn view_encoder(capabilities: &[Capability]) -> Option<ViewEncoder> {
+    capabilities
+        .contains(&Capability::Diffs)
+        .then(ViewEncoder::default)
+}
+
+fn event_frame(views: &mut Option<ViewEncoder>, event: &GameEvent, view: UiModel) -> HostToCli {
+    match views {
+        Some(views) => views.encode(event.clone(), view),
+        None => HostToCli::Event {
+            event: event.clone(),
+            view,
+        },
+    }
+}
@@ -0,0 +1,391 @@
+//! Views sent as changes to the previous one.
+//!
+//! Clients that negotiated [`Capability::Diffs`](super::Capability::Diffs)
+//! get the full view with the first event and with every keyframe after that;
+//! the events in between carry a [`UiModelDelta`]. Each delta names the
+//! checksum of the view it produces, so a client that went out of sync notices
+//! and waits for the next keyframe.
+
+use catan_core::gameplay::{
+    field::state::BoardState,
+    game::{event::GameEvent, state::GameState},
+    primitives::{
+        build::{Establishment, Road},
+        player::PlayerId,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    model::{UiModel, UiOmniscient, UiPrivatePlayer, UiPublicBank, UiPublicPlayer},
+    protocol::HostToCli,
+};
+
+/// Events between two full views.
+pub const KEYFRAME_INTERVAL: u32 = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum UiChange {
+    /// The turn passed on.
+    Actor(Option<PlayerId>),
+    /// The robber moved.
+    BoardState(BoardState),
+    Bank(UiPublicBank),
+    /// Hand or development cards of a player changed.
+    Player(UiPublicPlayer),
+    /// Establishments and roads a player built. A city replaces the
+    /// settlement at its position.
+    Built {
+        player_id: PlayerId,
+        establishments: Vec<Establishment>,
+        roads: Vec<Road>,
+    },
+    LongestRoadOwner(Option<PlayerId>),
+    LargestArmyOwner(Option<PlayerId>),
+    Private(Option<UiPrivatePlayer>),
+    Omniscient(Option<UiOmniscient>),
+    SnapshotState(Option<Box<GameState>>),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiModelDelta {
+    pub changes: Vec<UiChange>,
+    /// [`UiModel::checksum`] of the view the changes produce.
+    pub checksum: u64,
+}
+
+impl UiModel {
+    /// FNV-1a hash of the JSON encoding, the same on both ends of a
+    /// connection.
+    pub fn checksum(&self) -> u64 {
+        serde_json::to_vec(self)
+            .expect("views are serializable")
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+                (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// Changes turning `previous` into this view, `None` when they can't be
+    /// expressed as a delta: another board, other players or builds that
+    /// disappeared.
+    pub fn diff(&self, previous: &UiModel) -> Option<UiModelDelta> {
+        let (public, before) = (&self.public, &previous.public);
+        if !same(&public.board, &before.board)
+            || public.players.len() != before.players.len()
+            || public.builds.len() != before.builds.len()
+        {
+            return None;
+        }
+
+        let mut changes = Vec::new();
+        if self.actor != previous.actor {
+            changes.push(UiChange::Actor(self.actor));
+        }
+        if !same(&public.board_state, &before.board_state) {
+            changes.push(UiChange::BoardState(public.board_state));
+        }
+        if !same(&public.bank, &before.bank) {
+            changes.push(UiChange::Bank(public.bank.clone()));
+        }
+        for (player, before) in public.players.iter().zip(&before.players) {
+            if !same(player, before) {
+                changes.push(UiChange::Player(player.clone()));
+            }
+        }
+        for (builds, before) in public.builds.iter().zip(&before.builds) {
+            let kept = builds.player_id == before.player_id
+                && before.establishments.iter().all(|establishment| {
+                    builds
+                        .establishments
+                        .iter()
+                        .any(|built| built.pos == establishment.pos)
+                })
+                && before.roads.iter().all(|road| builds.roads.contains(road));
+            if !kept {
+                return None;
+            }
+            let establishments = builds
+                .establishments
+                .iter()
+                .filter(|establishment| !before.establishments.contains(establishment))
+                .copied()
+                .collect::<Vec<_>>();
+            let roads = builds
+                .roads
+                .iter()
+                .filter(|road| !before.roads.contains(road))
+                .copied()
+                .collect::<Vec<_>>();
+            if !establishments.is_empty() || !roads.is_empty() {
+                changes.push(UiChange::Built {
+                    player_id: builds.player_id,
+                    establishments,
+                    roads,
+                });
+            }
+        }
+        if public.longest_road_owner != before.longest_road_owner {
+            changes.push(UiChange::LongestRoadOwner(public.longest_road_owner));
+        }
+        if public.largest_army_owner != before.largest_army_owner {
+            changes.push(UiChange::LargestArmyOwner(public.largest_army_owner));
+        }
+        if !same(&self.private, &previous.private) {
+            changes.push(UiChange::Private(self.private.clone()));
+        }
+        if !same(&self.omniscient, &previous.omniscient) {
+            changes.push(UiChange::Omniscient(self.omniscient.clone()));
+        }
+        if !same(&self.snapshot_state, &previous.snapshot_state) {
+            changes.push(UiChange::SnapshotState(
+                self.snapshot_state.clone().map(Box::new),
+            ));
+        }
+
+        Some(UiModelDelta {
+            changes,
+            checksum: self.checksum(),
+        })
+    }
+
+    /// Applies `delta` and checks the result against its checksum. The view
+    /// is left half updated on errors and has to be replaced by a keyframe.
+    pub fn apply(&mut self, delta: &UiModelDelta) -> Result<(), String> {
+        let public = &mut self.public;
+        for change in &delta.changes {
+            match change {
+                UiChange::Actor(actor) => self.actor = *actor,
+                UiChange::BoardState(board_state) => public.board_state = *board_state,
+                UiChange::Bank(bank) => public.bank = bank.clone(),
+                UiChange::Player(player) => {
+                    *public
+                        .players
+                        .iter_mut()
+                        .find(|known| known.player_id == player.player_id)
+                        .ok_or_else(|| unknown_player(player.player_id))? = player.clone();
+                }
+                UiChange::Built {
+                    player_id,
+                    establishments,
+                    roads,
+                } => {
+                    let builds = public
+                        .builds
+                        .iter_mut()
+                        .find(|builds| builds.player_id == *player_id)
+                        .ok_or_else(|| unknown_player(*player_id))?;
+                    for establishment in establishments {
+                        builds
+                            .establishments
+                            .retain(|built| built.pos != establishment.pos);
+                        builds.establishments.push(*establishment);
+                    }
+                    builds.establishments.sort();
+                    builds.roads.extend(roads);
+                    builds.roads.sort();
+                }
+                UiChange::LongestRoadOwner(owner) => public.longest_road_owner = *owner,
+                UiChange::LargestArmyOwner(owner) => public.largest_army_owner = *owner,
+                UiChange::Private(private) => self.private = private.clone(),
+                UiChange::Omniscient(omniscient) => self.omniscient = omniscient.clone(),
+                UiChange::SnapshotState(state) => {
+                    self.snapshot_state = state.as_deref().cloned();
+                }
+            }
+        }
+
+        let checksum = self.checksum();
+        if checksum != delta.checksum {
+            return Err(format!(
+                "view checksum {checksum:016x} doesn't match the host's {:016x}",
+                delta.checksum
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn same<T: Serialize>(a: &T, b: &T) -> bool {
+    serde_json::to_vec(a).expect("views are serializable")
+        == serde_json::to_vec(b).expect("views are serializable")
+}
+
+fn unknown_player(player_id: PlayerId) -> String {
+    format!("delta names unknown player {player_id}")
+}
+
+/// Host side: turns the views of consecutive events into keyframes and
+/// deltas.
+#[derive(Debug, Default)]
+pub struct ViewEncoder {
+    previous: Option<UiModel>,
+    since_keyframe: u32,
+}
+
+impl ViewEncoder {
+    pub fn encode(&mut self, event: GameEvent, view: UiModel) -> HostToCli {
+        let delta = match &self.previous {
+            Some(previous) if self.since_keyframe < KEYFRAME_INTERVAL => view.diff(previous),
+            _ => None,
+        };
+        let frame = match delta {
+            Some(delta) => {
+                self.since_keyframe += 1;
+                HostToCli::EventDelta { event, delta }
+            }
+            None => {
+                self.since_keyframe = 0;
+                HostToCli::Event {
+                    event,
+                    view: view.clone(),
+                }
+            }
+        };
+        self.previous = Some(view);
+        frame
+    }
+}
+
+/// Client side: keeps the view the host's deltas apply to.
+#[derive(Debug, Default)]
+pub struct ViewDecoder {
+    current: Option<UiModel>,
+}
+
+impl ViewDecoder {
+    /// Takes the full view of an `Event` frame.
+    pub fn keyframe(&mut self, view: UiModel) -> &UiModel {
+        self.current.insert(view)
+    }
+
+    /// Applies the delta of an `EventDelta` frame. Once out of sync, deltas
+    /// are refused until the next keyframe.
+    pub fn apply(&mut self, delta: &UiModelDelta) -> Result<&UiModel, String> {
+        let Some(current) = self.current.as_mut() else {
+            return Err("no keyframe to apply the delta to".to_owned());
+        };
+        if let Err(err) = current.apply(delta) {
+            self.current = None;
+            return Err(err);
+        }
+        Ok(self.current.as_ref().expect("view was just updated"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use catan_core::{
+        agent::Agent,
+        gameplay::game::{
+            controller::{GameController, RunOptions},
+            event::{GameObserver, ObserverKind, ObserverNotificationContext},
+            init::GameInitializationState,
+        },
+        math::dice::RandomDiceRoller,
+        topology::Hex,
+    };
+
+    use super::*;
+    use crate::greedy::GreedyAgent;
+
+    struct Recorder {
+        kind: ObserverKind,
+        views: Rc<RefCell<Vec<(GameEvent, UiModel)>>>,
+    }
+
+    impl GameObserver for Recorder {
+        fn kind(&self) -> ObserverKind {
+            self.kind
+        }
+
+        fn on_event(&mut self, event: &GameEvent, context: ObserverNotificationContext<'_>) {
+            let include_snapshot_state = self.kind == ObserverKind::Omniscient;
+            self.views.borrow_mut().push((
+                event.clone(),
+                UiModel::from_observer(context, include_snapshot_state),
+            ));
+        }
+    }
+
+    fn recorded_views(kind: ObserverKind) -> Vec<(GameEvent, UiModel)> {
+        let views = Rc::new(RefCell::new(Vec::new()));
+        let mut observers = vec![Box::new(Recorder {
+            kind,
+            views: views.clone(),
+        }) as Box<dyn GameObserver>];
+        let init = GameInitializationState::default();
+        let mut agents = (0..init.board.n_players)
+            .map(|id| Box::new(GreedyAgent::new(id)) as Box<dyn Agent>)
+            .collect::<Vec<_>>();
+        let state = GameController::init_with_observers(init, &mut agents, &mut observers).unwrap();
+        let mut controller = GameController::new(state, agents);
+        for observer in observers {
+            controller.add_observer(observer);
+        }
+        controller.run_with_options(
+            &mut RandomDiceRoller::with_seed(3),
+            RunOptions {
+                max_turns: Some(12),
+                ..RunOptions::default()
+            },
+        );
+        drop(controller);
+        views.take()
+    }
+
+    #[test]
+    fn deltas_reproduce_every_view_of_a_game() {
+        for kind in [ObserverKind::Player(1), ObserverKind::Omniscient] {
+            let views = recorded_views(kind);
+            let mut encoder = ViewEncoder::default();
+            let mut decoder = ViewDecoder::default();
+            let (mut full_bytes, mut sent_bytes, mut keyframes) = (0, 0, 0);
+            for (event, view) in views.iter().cloned() {
+                let checksum = view.checksum();
+                full_bytes += serde_json::to_vec(&view).unwrap().len();
+                let frame = encoder.encode(event, view);
+                sent_bytes += serde_json::to_vec(&frame).unwrap().len();
+                let decoded = match frame {
+                    HostToCli::Event { view, .. } => {
+                        keyframes += 1;
+                        decoder.keyframe(view)
+                    }
+                    HostToCli::EventDelta { delta, .. } => decoder.apply(&delta).unwrap(),
+                    other => panic!("expected an event frame, got {other:?}"),
+                };
+                assert_eq!(decoded.checksum(), checksum);
+            }
+            assert_eq!(
+                keyframes,
+                views.len().div_ceil(KEYFRAME_INTERVAL as usize + 1)
+            );
+            assert!(
+                sent_bytes * 2 < full_bytes,
+                "{kind:?}: sent {sent_bytes} bytes for {full_bytes} bytes of views"
+            );
+        }
+    }
+
+    #[test]
+    fn out_of_sync_views_wait_for_the_next_keyframe() {
+        let views = recorded_views(ObserverKind::Player(0));
+        let (first, second, third) = (&views[0].1, &views[1].1, &views[2].1);
+        let mut decoder = ViewDecoder::default();
+        assert!(decoder.apply(&second.diff(first).unwrap()).is_err());
+
+        let mut stale = first.clone();
+        stale.public.board_state.robber_pos = Hex::new(9, 9);
+        decoder.keyframe(stale);
+        let err = decoder.apply(&second.diff(first).unwrap()).unwrap_err();
+        assert!(err.contains("checksum"), "{err}");
+        // the view is dropped, so later deltas don't pile up on it
+        assert!(decoder.apply(&third.diff(second).unwrap()).is_err());
+
+        decoder.keyframe(second.clone());
+        let view = decoder.apply(&third.diff(second).unwrap()).unwrap();
+        assert_eq!(view.checksum(), third.checksum());
+    }
+}
@@ -78,6 +78,9 @@ pub struct UiPublicPlayer {
     pub active_dev_cards: u16,
     pub played_dev_cards: UsableDevCardCollection,
     pub victory_points: Option<u16>,
+    /// Milliseconds left on the player's clock in timed games.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock_ms: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -215,15 +218,24 @@ impl UiPublicGame {
                         PublicVpKnowledge::Hidden => None,
                         PublicVpKnowledge::Exact(points) => Some(points),
                     },
+                    clock_ms: player
+                        .clock
+                        .map(|clock| u64::try_from(clock.as_millis()).unwrap_or(u64::MAX)),
                 })
                 .collect(),
             builds: public
                 .builds
                 .players_indexed()
-                .map(|(player_id, builds)| UiPlayerBuilds {
-                    player_id,
-                    establishments: builds.establishments.iter().copied().collect(),
-                    roads: builds.roads.iter().collect(),
+                .map(|(player_id, builds)| {
+                    // sorted like the establishments, so views compare and
+                    // hash the same however the roads are stored
+                    let mut roads = builds.roads.iter().collect::<Vec<_>>();
+                    roads.sort();
+                    UiPlayerBuilds {
+                        player_id,
+                        establishments: builds.establishments.iter().copied().collect(),
+                        roads,
+                    }
                 })
                 .collect(),
             longest_road_owner: public.longest_road_owner,
@@ -20,10 +20,73 @@ use catan_core::{
 };
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-use super::model::UiModel;
+use super::{delta::UiModelDelta, model::UiModel};
 
 pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
 
+/// Version of the frames below, bumped with every change an older peer
+/// can't read.
+pub const PROTOCOL_VERSION: u32 = 2;
+/// Oldest version hosts still serve. Version 1 had no version in its
+/// handshake and clients answered a bare `Ready`.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+/// Capabilities hosts honour.
+pub const HOST_CAPABILITIES: &[Capability] = &[Capability::Trades, Capability::Diffs];
+/// Capabilities of hosts serving clients over a socket, which can also switch
+/// to the binary encoding.
+pub const SOCKET_HOST_CAPABILITIES: &[Capability] = &[
+    Capability::Trades,
+    Capability::Diffs,
+    Capability::BinaryEncoding,
+];
+
+/// Optional parts of the protocol. A peer lists the ones it supports in its
+/// hello and both sides use the ones they have in common.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Answers trade offers of other players; without it the host declines
+    /// them on the client's behalf.
+    Trades,
+    /// Wants views as differences to the previous one.
+    Diffs,
+    /// Speaks the binary frame encoding.
+    BinaryEncoding,
+    /// Anything only a newer peer knows about.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Capability {
+    /// What version 1 peers supported without saying so.
+    pub fn legacy() -> Vec<Self> {
+        vec![Self::Trades]
+    }
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+/// Capabilities both sides support, or why a client speaking `version`
+/// can't be served.
+pub fn negotiate(
+    version: u32,
+    ours: &[Capability],
+    theirs: &[Capability],
+) -> Result<Vec<Capability>, String> {
+    if !(MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        return Err(format!(
+            "protocol version {version} is not supported, the host speaks versions {MIN_PROTOCOL_VERSION} to {PROTOCOL_VERSION}"
+        ));
+    }
+    Ok(ours
+        .iter()
+        .filter(|capability| **capability != Capability::Unknown && theirs.contains(capability))
+        .copied()
+        .collect())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CliRole {
     Player { player_id: PlayerId },
@@ -74,15 +137,52 @@ impl CliRole {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum HostToCli {
-    Hello { role: CliRole },
+    Hello {
+        role: CliRole,
+        /// Token a networked player reconnects to its seat with.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>,
+        #[serde(default = "legacy_version")]
+        version: u32,
+        #[serde(default = "Capability::legacy")]
+        capabilities: Vec<Capability>,
+    },
     DecisionRequest(DecisionRequestFrame),
-    Event { event: GameEvent, view: UiModel },
-    Shutdown { reason: String },
+    Event {
+        event: GameEvent,
+        view: UiModel,
+    },
+    /// Event for clients that negotiated [`Capability::Diffs`], with the view
+    /// as changes to the one of the previous event.
+    EventDelta {
+        event: GameEvent,
+        delta: UiModelDelta,
+    },
+    /// Tells the client why its last frame was refused; the outstanding
+    /// decision request, if any, is sent again right after.
+    Error {
+        message: String,
+    },
+    Shutdown {
+        reason: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CliToHost {
+    /// Answer to the host's hello of version 1 clients.
     Ready,
+    /// Answer to the host's hello from version 2 on.
+    Hello {
+        version: u32,
+        capabilities: Vec<Capability>,
+    },
+    /// First frame of clients dialing a host that listens over TCP, naming
+    /// the seat they were handed a join token for; the host greets them once
+    /// the token matched.
+    Join {
+        token: String,
+    },
     DecisionResponse(DecisionResponseFrame),
     Error {
         message: String,
@@ -155,6 +255,56 @@ pub struct LegalBuildOptions {
     pub cities: Vec<Build>,
 }
 
+impl HostToCli {
+    /// Greeting of the current protocol version.
+    pub fn hello(role: CliRole, session_token: Option<String>) -> Self {
+        Self::Hello {
+            role,
+            session_token,
+            version: PROTOCOL_VERSION,
+            capabilities: HOST_CAPABILITIES.to_vec(),
+        }
+    }
+
+    /// Greeting of a socket host, which hands out no session tokens.
+    pub fn socket_hello(role: CliRole) -> Self {
+        Self::Hello {
+            role,
+            session_token: None,
+            version: PROTOCOL_VERSION,
+            capabilities: SOCKET_HOST_CAPABILITIES.to_vec(),
+        }
+    }
+}
+
+impl CliToHost {
+    /// Answer to a host greeting with `host_version`; version 1 hosts only
+    /// understand `Ready`.
+    pub fn answer_hello(host_version: u32, capabilities: Vec<Capability>) -> Self {
+        if host_version < 2 {
+            Self::Ready
+        } else {
+            Self::Hello {
+                version: PROTOCOL_VERSION,
+                capabilities,
+            }
+        }
+    }
+
+    /// Capabilities both sides use once this frame answered the host's
+    /// hello, `None` if it isn't a handshake frame.
+    pub fn negotiate(&self, ours: &[Capability]) -> Option<Result<Vec<Capability>, String>> {
+        match self {
+            Self::Ready => Some(negotiate(1, ours, &Capability::legacy())),
+            Self::Hello {
+                version,
+                capabilities,
+            } => Some(negotiate(*version, ours, capabilities)),
+            _ => None,
+        }
+    }
+}
+
 impl DecisionRequestFrame {
     pub fn request_id(&self) -> u64 {
         self.envelope().request_id
@@ -228,8 +378,57 @@ impl LegalDecisionOptions {
     }
 }
 
+/// Payload encoding of frames. The handshake is always JSON; peers that
+/// negotiated [`Capability::BinaryEncoding`] switch to MessagePack right after
+/// it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+
+impl Encoding {
+    /// Encoding to use once `capabilities` were negotiated.
+    pub fn negotiated(capabilities: &[Capability]) -> Self {
+        if capabilities.contains(&Capability::BinaryEncoding) {
+            Self::MessagePack
+        } else {
+            Self::Json
+        }
+    }
+
+    pub fn encode<T: Serialize>(self, value: &T) -> io::Result<Vec<u8>> {
+        match self {
+            Self::Json => serde_json::to_vec(value).map_err(io::Error::other),
+            // maps keyed by field name keep optional and defaulted fields
+            // working like they do in JSON
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(io::Error::other),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, payload: &[u8]) -> io::Result<T> {
+        match self {
+            Self::Json => serde_json::from_slice(payload).map_err(io::Error::other),
+            Self::MessagePack => rmp_serde::from_slice(payload).map_err(io::Error::other),
+        }
+    }
+}
+
 pub fn write_frame<T: Serialize>(writer: &mut impl Write, value: &T) -> io::Result<()> {
-    let payload = serde_json::to_vec(value).map_err(io::Error::other)?;
+    write_encoded_frame(writer, value, Encoding::Json)
+}
+
+pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T> {
+    read_encoded_frame(reader, Encoding::Json)
+}
+
+pub fn write_encoded_frame<T: Serialize>(
+    writer: &mut impl Write,
+    value: &T,
+    encoding: Encoding,
+) -> io::Result<()> {
+    let payload = encoding.encode(value)?;
     if payload.len() > MAX_FRAME_LEN {
         return Err(frame_too_large());
     }
@@ -237,7 +436,10 @@ pub fn write_frame<T: Serialize>(writer: &mut impl Write, value: &T) -> io::Resu
     writer.write_all(&payload)
 }
 
-pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T> {
+pub fn read_encoded_frame<T: DeserializeOwned>(
+    reader: &mut impl Read,
+    encoding: Encoding,
+) -> io::Result<T> {
     let mut header = [0_u8; 4];
     reader.read_exact(&mut header)?;
     let len = u32::from_be_bytes(header) as usize;
@@ -246,7 +448,7 @@ pub fn read_frame<T: DeserializeOwned>(reader: &mut impl Read) -> io::Result<T>
     }
     let mut payload = vec![0_u8; len];
     reader.read_exact(&mut payload)?;
-    serde_json::from_slice(&payload).map_err(io::Error::other)
+    encoding.decode(&payload)
 }
 
 pub struct NonblockingFrameReader<T> {
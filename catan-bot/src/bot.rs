@@ -0,0 +1,87 @@
+use catan_agents::remote_agent::{Capability, CliRole, DecisionRequestEnvelope, UiModel};
+use catan_core::{
+    agent::action::{
+        ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction, MoveRobbersAction,
+        PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
+    },
+    gameplay::{game::event::GameEvent, primitives::resource::ResourceCollection},
+};
+
+/// Strategy of a remote bot.
+///
+/// Every decision gets the request envelope: the bot's view of the game and
+/// the legal options for the decision. The defaults make the first legal
+/// choice, or pass where passing is allowed, so a bot only overrides the
+/// decisions it has an opinion on. A panicking decision is reported to the
+/// host as an error, which then falls back to its own answer.
+pub trait Bot {
+    /// Optional protocol features the bot supports, offered in the handshake.
+    /// Hosts decline trade offers themselves for bots without
+    /// [`Capability::Trades`].
+    fn capabilities(&self) -> Vec<Capability> {
+        vec![Capability::Trades]
+    }
+
+    /// Called on every handshake, including after reconnects.
+    fn on_hello(&mut self, _role: &CliRole) {}
+
+    fn on_event(&mut self, _event: &GameEvent, _view: &UiModel) {}
+
+    fn init_stage_action(&mut self, request: &DecisionRequestEnvelope) -> InitStageAction {
+        *first(&request.legal.initial_placements, "initial placement")
+    }
+
+    fn init_action(&mut self, _request: &DecisionRequestEnvelope) -> InitAction {
+        InitAction::RollDice
+    }
+
+    fn after_dice_action(&mut self, _request: &DecisionRequestEnvelope) -> PostDiceAction {
+        PostDiceAction::RegularAction(RegularAction::EndMove)
+    }
+
+    fn after_dev_card_action(&mut self, _request: &DecisionRequestEnvelope) -> PostDevCardAction {
+        PostDevCardAction::RollDice
+    }
+
+    fn regular_action(&mut self, _request: &DecisionRequestEnvelope) -> RegularAction {
+        RegularAction::EndMove
+    }
+
+    fn move_robbers(&mut self, request: &DecisionRequestEnvelope) -> MoveRobbersAction {
+        MoveRobbersAction(*first(&request.legal.robber_hexes, "robber hex"))
+    }
+
+    fn choose_player_to_rob(
+        &mut self,
+        request: &DecisionRequestEnvelope,
+    ) -> ChoosePlayerToRobAction {
+        ChoosePlayerToRobAction(*first(&request.legal.rob_targets, "rob target"))
+    }
+
+    fn answer_trade(&mut self, _request: &DecisionRequestEnvelope) -> TradeAnswer {
+        TradeAnswer::Decline
+    }
+
+    fn drop_half(&mut self, request: &DecisionRequestEnvelope) -> DropHalfAction {
+        let resources = request
+            .view
+            .private
+            .as_ref()
+            .expect("players see their own resources")
+            .resources;
+        let mut remaining = resources.total() / 2;
+        let mut to_drop = ResourceCollection::default();
+        for (resource, number) in resources.unroll() {
+            let dropped = remaining.min(number);
+            to_drop[resource] = dropped;
+            remaining -= dropped;
+        }
+        DropHalfAction(to_drop)
+    }
+}
+
+fn first<'a, T>(options: &'a [T], what: &str) -> &'a T {
+    options
+        .first()
+        .unwrap_or_else(|| panic!("host offered no legal {what}"))
+}
@@ -0,0 +1,513 @@
+use std::{
+    fmt, io,
+    panic::{self, AssertUnwindSafe},
+    thread,
+    time::Duration,
+};
+
+use catan_agents::remote_agent::{
+    Capability, CliToHost, DecisionRequestFrame, DecisionResponseFrame, Encoding, HostToCli,
+    ViewDecoder, negotiate,
+};
+
+use crate::{
+    Bot,
+    transport::{Connection, Endpoint},
+};
+
+#[derive(Debug)]
+pub enum ClientError {
+    InvalidEndpoint(String),
+    Io(io::Error),
+    WebSocket(tungstenite::Error),
+    /// The host sent something the protocol doesn't allow.
+    Protocol(String),
+}
+
+impl ClientError {
+    fn is_connection_lost(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::WebSocket(_))
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidEndpoint(endpoint) => write!(
+                f,
+                "invalid endpoint {endpoint}, expected unix:<path>, tcp://<addr> or ws://<url>"
+            ),
+            Self::Io(err) => write!(f, "connection failed: {err}"),
+            Self::WebSocket(err) => write!(f, "websocket failed: {err}"),
+            Self::Protocol(message) => write!(f, "protocol violation: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<io::Error> for ClientError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<tungstenite::Error> for ClientError {
+    fn from(value: tungstenite::Error) -> Self {
+        Self::WebSocket(value)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClientOptions {
+    /// Attempts to reconnect once the connection drops, `0` gives up right
+    /// away.
+    pub reconnect_attempts: u32,
+    /// Pause before the first attempt, doubled after every failed one.
+    pub reconnect_delay: Duration,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            reconnect_attempts: 5,
+            reconnect_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Plays `bot` on `endpoint` with the default options.
+pub fn run(endpoint: &Endpoint, bot: &mut impl Bot) -> Result<(), ClientError> {
+    run_with_options(endpoint, bot, ClientOptions::default())
+}
+
+/// Plays `bot` on `endpoint` until the host shuts down or closes the
+/// connection. A dropped connection is dialed again; websocket seats are
+/// reclaimed with the session token of the last handshake.
+pub fn run_with_options(
+    endpoint: &Endpoint,
+    bot: &mut impl Bot,
+    options: ClientOptions,
+) -> Result<(), ClientError> {
+    let mut connection = endpoint.connect()?;
+    let mut session_token = None;
+    loop {
+        let err = match serve(&mut connection, bot, &mut session_token) {
+            Ok(()) => return Ok(()),
+            Err(err) if err.is_connection_lost() => err,
+            Err(err) => return Err(err),
+        };
+        log::warn!("lost connection to {endpoint}: {err}");
+        let endpoint = match &session_token {
+            Some(token) => endpoint.with_session_token(token),
+            None => endpoint.clone(),
+        };
+        connection = reconnect(&endpoint, options).ok_or(err)?;
+    }
+}
+
+fn reconnect(endpoint: &Endpoint, options: ClientOptions) -> Option<Connection> {
+    let mut delay = options.reconnect_delay;
+    for attempt in 1..=options.reconnect_attempts {
+        thread::sleep(delay);
+        match endpoint.connect() {
+            Ok(connection) => {
+                log::info!("reconnected to {endpoint}");
+                return Some(connection);
+            }
+            Err(err) => log::warn!(
+                "reconnect attempt {attempt}/{} failed: {err}",
+                options.reconnect_attempts
+            ),
+        }
+        delay *= 2;
+    }
+    None
+}
+
+fn serve(
+    connection: &mut Connection,
+    bot: &mut impl Bot,
+    session_token: &mut Option<String>,
+) -> Result<(), ClientError> {
+    let mut views = ViewDecoder::default();
+    loop {
+        let Some(msg) = connection.recv()? else {
+            log::info!("host closed the connection");
+            return Ok(());
+        };
+        match msg {
+            HostToCli::Hello {
+                role,
+                session_token: token,
+                version,
+                capabilities,
+            } => {
+                log::info!(
+                    "connected as {} over protocol version {version}",
+                    role.label()
+                );
+                if token.is_some() {
+                    *session_token = token;
+                }
+                bot.on_hello(&role);
+                // deltas are turned back into full views before the bot
+                // sees them
+                let mut ours = bot.capabilities();
+                ours.push(Capability::Diffs);
+                if connection.supports_binary() {
+                    ours.push(Capability::BinaryEncoding);
+                }
+                connection.send(&CliToHost::answer_hello(version, ours.clone()))?;
+                // the host switches as well, unless it refuses the version
+                // and shuts down in JSON
+                if let Ok(negotiated) = negotiate(version, &ours, &capabilities) {
+                    connection.set_encoding(Encoding::negotiated(&negotiated));
+                }
+            }
+            HostToCli::DecisionRequest(request) => connection.send(&decide(bot, &request))?,
+            HostToCli::Event { event, view } => bot.on_event(&event, views.keyframe(view)),
+            HostToCli::EventDelta { event, delta } => match views.apply(&delta) {
+                Ok(view) => bot.on_event(&event, view),
+                Err(err) => log::warn!("skipped {event:?} until the next full view: {err}"),
+            },
+            HostToCli::Error { message } => log::warn!("host refused our last frame: {message}"),
+            HostToCli::Shutdown { reason } => {
+                log::info!("host shut down: {reason}");
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Asks the bot for a decision; a panicking bot is reported as an error.
+fn decide(bot: &mut impl Bot, request: &DecisionRequestFrame) -> CliToHost {
+    let envelope = request.envelope();
+    let response = panic::catch_unwind(AssertUnwindSafe(|| match request {
+        DecisionRequestFrame::InitStage(_) => {
+            DecisionResponseFrame::InitStage(bot.init_stage_action(envelope))
+        }
+        DecisionRequestFrame::InitAction(_) => {
+            DecisionResponseFrame::InitAction(bot.init_action(envelope))
+        }
+        DecisionRequestFrame::PostDice(_) => {
+            DecisionResponseFrame::PostDice(bot.after_dice_action(envelope))
+        }
+        DecisionRequestFrame::PostDevCard(_) => {
+            DecisionResponseFrame::PostDevCard(bot.after_dev_card_action(envelope))
+        }
+        DecisionRequestFrame::Regular(_) => {
+            DecisionResponseFrame::Regular(bot.regular_action(envelope))
+        }
+        DecisionRequestFrame::MoveRobbers(_) => {
+            DecisionResponseFrame::MoveRobbers(bot.move_robbers(envelope))
+        }
+        DecisionRequestFrame::ChoosePlayerToRob(_) => {
+            DecisionResponseFrame::ChoosePlayerToRob(bot.choose_player_to_rob(envelope))
+        }
+        DecisionRequestFrame::AnswerTrade(_) => {
+            DecisionResponseFrame::AnswerTrade(bot.answer_trade(envelope))
+        }
+        DecisionRequestFrame::DropHalf(_) => {
+            DecisionResponseFrame::DropHalf(bot.drop_half(envelope))
+        }
+    }));
+    match response {
+        Ok(response) => CliToHost::DecisionResponse(response),
+        Err(panic) => {
+            let reason = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("bot panicked");
+            CliToHost::Error {
+                message: format!(
+                    "no decision for request id={} kind={}: {reason}",
+                    request.request_id(),
+                    request.kind()
+                ),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::TcpListener,
+        os::unix::net::UnixListener,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+
+    use catan_agents::remote_agent::{
+        Capability, CliRole, DecisionRequestEnvelope, LegalDecisionOptions, UiModel,
+        read_encoded_frame, read_frame, write_encoded_frame, write_frame,
+    };
+    use catan_core::{
+        agent::action::{InitAction, RegularAction},
+        gameplay::game::{
+            index::GameIndex,
+            init::GameInitializationState,
+            view::{ContextFactory, VisibilityConfig},
+        },
+    };
+    use tungstenite::{
+        Message,
+        handshake::server::{Request, Response},
+    };
+
+    use super::*;
+
+    fn envelope(request_id: u64) -> DecisionRequestEnvelope {
+        let state = GameInitializationState::default().finish();
+        let index = GameIndex::rebuild(&state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state: &state,
+            index: &index,
+            visibility: &visibility,
+        };
+        let context = factory.player_decision_context(0, None);
+        DecisionRequestEnvelope {
+            request_id,
+            view: UiModel::from_decision(&context),
+            legal: LegalDecisionOptions::from_context(&context, None),
+        }
+    }
+
+    fn unique_socket_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rusty-catan-bot-test-{}-{}.sock",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    #[derive(Default)]
+    struct Counting {
+        hellos: usize,
+    }
+
+    impl Bot for Counting {
+        fn on_hello(&mut self, _role: &CliRole) {
+            self.hellos += 1;
+        }
+    }
+
+    struct Broken;
+
+    impl Bot for Broken {
+        fn regular_action(&mut self, _request: &DecisionRequestEnvelope) -> RegularAction {
+            panic!("no strategy yet")
+        }
+    }
+
+    #[test]
+    fn plays_a_socket_host_until_shutdown() {
+        let path = unique_socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            write_frame(
+                &mut stream,
+                &HostToCli::hello(CliRole::Player { player_id: 0 }, None),
+            )
+            .unwrap();
+            let reply = read_frame::<CliToHost>(&mut stream).unwrap();
+            assert_eq!(
+                reply.negotiate(&[Capability::Trades, Capability::Diffs]),
+                Some(Ok(vec![Capability::Trades, Capability::Diffs]))
+            );
+
+            write_frame(
+                &mut stream,
+                &HostToCli::DecisionRequest(DecisionRequestFrame::InitAction(envelope(0))),
+            )
+            .unwrap();
+            let response = read_frame::<CliToHost>(&mut stream).unwrap();
+            write_frame(
+                &mut stream,
+                &HostToCli::Shutdown {
+                    reason: "done".to_owned(),
+                },
+            )
+            .unwrap();
+            response
+        });
+
+        let mut bot = Counting::default();
+        run(&Endpoint::Unix(path.clone()), &mut bot).unwrap();
+        assert_eq!(bot.hellos, 1);
+        assert!(matches!(
+            host.join().unwrap(),
+            CliToHost::DecisionResponse(DecisionResponseFrame::InitAction(InitAction::RollDice))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn reconnects_to_a_websocket_seat_with_its_session_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/rooms/0/seats/1", listener.local_addr().unwrap());
+        let uris = Arc::new(Mutex::new(Vec::new()));
+        let host_uris = uris.clone();
+        let host = thread::spawn(move || {
+            let accept = || {
+                let (stream, _) = listener.accept().unwrap();
+                let uris = host_uris.clone();
+                #[allow(
+                    clippy::result_large_err,
+                    reason = "tungstenite's handshake callback fixes the error type"
+                )]
+                let mut socket =
+                    tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                        uris.lock().unwrap().push(request.uri().to_string());
+                        Ok(response)
+                    })
+                    .unwrap();
+                // a version 1 host, which only understands `Ready`
+                let hello = HostToCli::Hello {
+                    role: CliRole::Player { player_id: 1 },
+                    session_token: Some("secret".to_owned()),
+                    version: 1,
+                    capabilities: Capability::legacy(),
+                };
+                let send = |socket: &mut tungstenite::WebSocket<_>, msg: &HostToCli| {
+                    socket
+                        .send(Message::Text(serde_json::to_string(msg).unwrap().into()))
+                        .unwrap()
+                };
+                send(&mut socket, &hello);
+                let ready = socket.read().unwrap().into_text().unwrap();
+                assert!(matches!(
+                    serde_json::from_str(&ready).unwrap(),
+                    CliToHost::Ready
+                ));
+                (socket, send)
+            };
+
+            // the first connection drops without a closing handshake
+            drop(accept());
+
+            let (mut socket, send) = accept();
+            send(
+                &mut socket,
+                &HostToCli::DecisionRequest(DecisionRequestFrame::Regular(envelope(3))),
+            );
+            let response = socket.read().unwrap().into_text().unwrap();
+            send(
+                &mut socket,
+                &HostToCli::Shutdown {
+                    reason: "done".to_owned(),
+                },
+            );
+            serde_json::from_str::<CliToHost>(&response).unwrap()
+        });
+
+        let options = ClientOptions {
+            reconnect_attempts: 3,
+            reconnect_delay: Duration::from_millis(10),
+        };
+        let mut bot = Counting::default();
+        run_with_options(&url.parse().unwrap(), &mut bot, options).unwrap();
+        assert_eq!(bot.hellos, 2);
+        assert!(matches!(
+            host.join().unwrap(),
+            CliToHost::DecisionResponse(DecisionResponseFrame::Regular(RegularAction::EndMove))
+        ));
+        assert_eq!(
+            *uris.lock().unwrap(),
+            ["/rooms/0/seats/1", "/rooms/0/seats/1?token=secret"]
+        );
+    }
+
+    #[test]
+    fn switches_to_the_binary_encoding_on_sockets() {
+        let path = unique_socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            write_frame(
+                &mut stream,
+                &HostToCli::socket_hello(CliRole::Player { player_id: 0 }),
+            )
+            .unwrap();
+            // the handshake itself stays JSON
+            let reply = read_frame::<CliToHost>(&mut stream).unwrap();
+            assert_eq!(
+                reply.negotiate(&[Capability::Trades, Capability::BinaryEncoding]),
+                Some(Ok(vec![Capability::Trades, Capability::BinaryEncoding]))
+            );
+
+            let binary = Encoding::MessagePack;
+            write_encoded_frame(
+                &mut stream,
+                &HostToCli::DecisionRequest(DecisionRequestFrame::InitAction(envelope(0))),
+                binary,
+            )
+            .unwrap();
+            let response = read_encoded_frame::<CliToHost>(&mut stream, binary).unwrap();
+            write_encoded_frame(
+                &mut stream,
+                &HostToCli::Shutdown {
+                    reason: "done".to_owned(),
+                },
+                binary,
+            )
+            .unwrap();
+            response
+        });
+
+        run(&Endpoint::Unix(path.clone()), &mut Counting::default()).unwrap();
+        assert!(matches!(
+            host.join().unwrap(),
+            CliToHost::DecisionResponse(DecisionResponseFrame::InitAction(InitAction::RollDice))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn gives_up_once_reconnects_fail() {
+        let path = unique_socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let host_path = path.clone();
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            // the host goes away for good
+            drop(listener);
+            std::fs::remove_file(host_path).unwrap();
+            drop(stream);
+        });
+
+        let options = ClientOptions {
+            reconnect_attempts: 2,
+            reconnect_delay: Duration::from_millis(1),
+        };
+        let err =
+            run_with_options(&Endpoint::Unix(path), &mut Counting::default(), options).unwrap_err();
+        assert!(matches!(err, ClientError::Io(_)));
+        host.join().unwrap();
+    }
+
+    #[test]
+    fn panicking_decisions_are_reported_as_errors() {
+        let request = DecisionRequestFrame::Regular(envelope(7));
+        match decide(&mut Broken, &request) {
+            CliToHost::Error { message } => {
+                assert_eq!(
+                    message,
+                    "no decision for request id=7 kind=regular: no strategy yet"
+                );
+            }
+            other => panic!("expected an error, got {other:?}"),
+        }
+        assert!(matches!(
+            decide(&mut Broken, &DecisionRequestFrame::PostDevCard(envelope(8))),
+            CliToHost::DecisionResponse(_)
+        ));
+    }
+}
@@ -0,0 +1,37 @@
+//! Client side of the remote player protocol.
+//!
+//! A bot implements [`Bot`] and hands it to [`run`] together with the
+//! [`Endpoint`] of its host: the Unix socket of a `catan-runtime` game, a TCP
+//! address or a `catan-server` websocket URL. Framing, the versioned
+//! handshake, decision dispatch and reconnection are taken care of.
+//!
+//! ```no_run
+//! use catan_agents::remote_agent::DecisionRequestEnvelope;
+//! use catan_bot::Bot;
+//! use catan_core::agent::action::RegularAction;
+//!
+//! struct Builder;
+//!
+//! impl Bot for Builder {
+//!     fn regular_action(&mut self, request: &DecisionRequestEnvelope) -> RegularAction {
+//!         request
+//!             .legal
+//!             .regular_actions
+//!             .iter()
+//!             .copied()
+//!             .find(|action| matches!(action, RegularAction::Build(_)))
+//!             .unwrap_or(RegularAction::EndMove)
+//!     }
+//! }
+//!
+//! let endpoint = "ws://localhost:8080/ws".parse().unwrap();
+//! catan_bot::run(&endpoint, &mut Builder).unwrap();
+//! ```
+
+mod bot;
+mod client;
+mod transport;
+
+pub use bot::Bot;
+pub use client::{ClientError, ClientOptions, run, run_with_options};
+pub use transport::Endpoint;
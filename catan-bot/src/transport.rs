@@ -0,0 +1,164 @@
+use std::{fmt, io, net::TcpStream, os::unix::net::UnixStream, path::PathBuf, str::FromStr};
+
+use catan_agents::remote_agent::{
+    CliToHost, Encoding, HostToCli, read_encoded_frame, write_encoded_frame,
+};
+use tungstenite::{Message, WebSocket, stream::MaybeTlsStream};
+
+use crate::ClientError;
+
+/// Where a host listens for the bot.
+///
+/// Parsed from `unix:<path>`, `tcp://<host>:<port>` or `ws://<host>:<port>/<path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Socket of a `catan-runtime` host.
+    Unix(PathBuf),
+    Tcp(String),
+    /// A `catan-server` seat, quick game or arena URL.
+    WebSocket(String),
+}
+
+impl Endpoint {
+    /// The endpoint to take back a seat with; only websocket seats can be
+    /// reclaimed, other hosts hand out a seat per connection.
+    pub(crate) fn with_session_token(&self, token: &str) -> Self {
+        match self {
+            Self::WebSocket(url) => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                Self::WebSocket(format!("{url}{separator}token={token}"))
+            }
+            other => other.clone(),
+        }
+    }
+
+    pub(crate) fn connect(&self) -> Result<Connection, ClientError> {
+        let connection = match self {
+            Self::Unix(path) => Connection::Unix(UnixStream::connect(path)?, Encoding::Json),
+            Self::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                stream.set_nodelay(true)?;
+                Connection::Tcp(stream, Encoding::Json)
+            }
+            Self::WebSocket(url) => {
+                let (socket, _) = tungstenite::connect(url.as_str())?;
+                Connection::WebSocket(Box::new(socket))
+            }
+        };
+        Ok(connection)
+    }
+}
+
+impl FromStr for Endpoint {
+    type Err = ClientError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(Self::Unix(path.into()));
+        }
+        if let Some(addr) = s.strip_prefix("tcp://") {
+            return Ok(Self::Tcp(addr.to_owned()));
+        }
+        if s.starts_with("ws://") {
+            return Ok(Self::WebSocket(s.to_owned()));
+        }
+        Err(ClientError::InvalidEndpoint(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unix(path) => write!(f, "unix:{}", path.display()),
+            Self::Tcp(addr) => write!(f, "tcp://{addr}"),
+            Self::WebSocket(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+/// Socket hosts speak length-prefixed frames, in JSON until the handshake
+/// negotiated another encoding, websocket hosts a JSON text message per
+/// frame.
+pub(crate) enum Connection {
+    Unix(UnixStream, Encoding),
+    Tcp(TcpStream, Encoding),
+    WebSocket(Box<WebSocket<MaybeTlsStream<TcpStream>>>),
+}
+
+impl Connection {
+    /// Whether frames can switch to the binary encoding.
+    pub(crate) fn supports_binary(&self) -> bool {
+        !matches!(self, Self::WebSocket(_))
+    }
+
+    /// Encoding of the frames following the handshake.
+    pub(crate) fn set_encoding(&mut self, encoding: Encoding) {
+        match self {
+            Self::Unix(_, current) | Self::Tcp(_, current) => *current = encoding,
+            Self::WebSocket(_) => {}
+        }
+    }
+
+    /// Next host frame, `None` once a websocket host closed the connection.
+    pub(crate) fn recv(&mut self) -> Result<Option<HostToCli>, ClientError> {
+        match self {
+            Self::Unix(stream, encoding) => Ok(Some(read_encoded_frame(stream, *encoding)?)),
+            Self::Tcp(stream, encoding) => Ok(Some(read_encoded_frame(stream, *encoding)?)),
+            Self::WebSocket(socket) => loop {
+                match socket.read() {
+                    Ok(Message::Text(text)) => {
+                        return serde_json::from_str(&text).map(Some).map_err(|err| {
+                            ClientError::Protocol(format!("malformed host frame: {err}"))
+                        });
+                    }
+                    Ok(Message::Close(_))
+                    | Err(
+                        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed,
+                    ) => return Ok(None),
+                    Ok(_) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            },
+        }
+    }
+
+    pub(crate) fn send(&mut self, msg: &CliToHost) -> Result<(), ClientError> {
+        match self {
+            Self::Unix(stream, encoding) => write_encoded_frame(stream, msg, *encoding)?,
+            Self::Tcp(stream, encoding) => write_encoded_frame(stream, msg, *encoding)?,
+            Self::WebSocket(socket) => {
+                let text = serde_json::to_string(msg).map_err(io::Error::other)?;
+                socket.send(Message::Text(text.into()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endpoints_parse_and_display() {
+        for raw in [
+            "unix:/tmp/catan.sock",
+            "tcp://127.0.0.1:7000",
+            "ws://host:8080/ws",
+        ] {
+            assert_eq!(raw.parse::<Endpoint>().unwrap().to_string(), raw);
+        }
+        assert!(matches!(
+            "http://host".parse::<Endpoint>(),
+            Err(ClientError::InvalidEndpoint(_))
+        ));
+
+        let seat: Endpoint = "ws://host/rooms/1/seats/0?name=ada".parse().unwrap();
+        assert_eq!(
+            seat.with_session_token("abc").to_string(),
+            "ws://host/rooms/1/seats/0?name=ada&token=abc"
+        );
+        let socket = Endpoint::Unix("/tmp/catan.sock".into());
+        assert_eq!(socket.with_session_token("abc"), socket);
+    }
+}
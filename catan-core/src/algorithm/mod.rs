@@ -40,17 +40,20 @@ pub fn get_ports_aquired(
     ports: BTreeMap<Intersection, PortKind>,
     builds: &BoardBuildData,
 ) -> Vec<BTreeSet<PortKind>> {
-    let mut result = Vec::new();
-    for id in 0..builds.players().len() {
-        let mut set = BTreeSet::new();
-
-        for est in builds.by_player(id).establishments.iter() {
-            if let Some(port) = ports.get(&est.pos) {
-                set.insert(*port);
-            }
-        }
-        result.push(set);
-    }
+    builds
+        .players()
+        .iter()
+        .map(|builds| get_player_ports_aquired(&ports, builds))
+        .collect()
+}
 
-    result
+pub fn get_player_ports_aquired(
+    ports: &BTreeMap<Intersection, PortKind>,
+    builds: &PlayerBuildData,
+) -> BTreeSet<PortKind> {
+    builds
+        .establishments
+        .iter()
+        .filter_map(|est| ports.get(&est.pos).copied())
+        .collect()
 }
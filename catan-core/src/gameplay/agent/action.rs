@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     gameplay::{
-        agent::agent::PlayerRuntime,
+        agent::agent::{AgentError, PlayerRuntime},
         game::view::PlayerDecisionContext,
         primitives::{
             build::{Build, Road},
@@ -61,56 +61,86 @@ pub enum RegularAction {
     Build(Build),
     BuyDevCard,
     EndMove,
+    /// Concedes the game. It is always allowed, so it's left out of the legal
+    /// options bots pick from.
+    Resign,
 }
 
 pub trait DecisionRequest: Sized {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self;
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError>;
 }
 
 impl DecisionRequest for InitStageAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.init_stage_action(context)
     }
 }
 
 impl DecisionRequest for InitAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.init_action(context)
     }
 }
 
 impl DecisionRequest for TradeAnswer {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.answer_trade(context)
     }
 }
 
 impl DecisionRequest for PostDevCardAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.after_dev_card_action(context)
     }
 }
 
 impl DecisionRequest for PostDiceAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.after_dice_action(context)
     }
 }
 
 impl DecisionRequest for RegularAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.regular_action(context)
     }
 }
 
 impl DecisionRequest for MoveRobbersAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.move_robbers(context)
     }
 }
 
 impl DecisionRequest for DropHalfAction {
-    fn request(player: &mut dyn PlayerRuntime, context: PlayerDecisionContext<'_>) -> Self {
+    fn request(
+        player: &mut dyn PlayerRuntime,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<Self, AgentError> {
         player.drop_half(context)
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 use crate::{
     agent::action::{ChoosePlayerToRobAction, DropHalfAction, InitStageAction, MoveRobbersAction},
     gameplay::{
@@ -9,22 +11,70 @@ use crate::{
 
 use super::action::{InitAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer};
 
+/// Why an agent couldn't come up with a decision. The controller recovers
+/// from it as its [`AgentErrorPolicy`] says.
+///
+/// [`AgentErrorPolicy`]: crate::gameplay::game::controller::AgentErrorPolicy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AgentError {
+    /// The connection to whoever decides for the agent is gone.
+    Disconnected(String),
+    /// Whoever decides for the agent answered with something it can't use.
+    Protocol(String),
+    /// No answer came before the player's clock ran out.
+    TimedOut,
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disconnected(reason) => write!(f, "disconnected: {reason}"),
+            Self::Protocol(reason) => write!(f, "protocol violation: {reason}"),
+            Self::TimedOut => write!(f, "out of time"),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
 pub trait PlayerRuntime: PlayerNotification {
     fn player_id(&self) -> PlayerId;
 
-    fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction;
-    fn init_action(&mut self, context: PlayerDecisionContext<'_>) -> InitAction;
-    fn after_dice_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDiceAction;
-    fn after_dev_card_action(&mut self, context: PlayerDecisionContext<'_>) -> PostDevCardAction;
-    fn regular_action(&mut self, context: PlayerDecisionContext<'_>) -> RegularAction;
-    fn move_robbers(&mut self, context: PlayerDecisionContext<'_>) -> MoveRobbersAction;
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError>;
+    fn init_action(&mut self, context: PlayerDecisionContext<'_>)
+    -> Result<InitAction, AgentError>;
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError>;
+    fn after_dev_card_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError>;
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError>;
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError>;
     fn choose_player_to_rob(
         &mut self,
         context: PlayerDecisionContext<'_>,
         robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction;
-    fn answer_trade(&mut self, context: PlayerDecisionContext<'_>) -> TradeAnswer;
-    fn drop_half(&mut self, context: PlayerDecisionContext<'_>) -> DropHalfAction;
+    ) -> Result<ChoosePlayerToRobAction, AgentError>;
+    fn answer_trade(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError>;
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError>;
 }
 
 pub trait Agent: PlayerRuntime {}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     gameplay::primitives::{PortKind, Tile},
     math::dice::DiceVal,
-    topology::{Hex, HexIndex, Intersection, Path, SignedAxis},
+    topology::{Hex, HexIndex, HexSymmetry, Intersection, Path, SignedAxis, Symmetric},
 };
 
 pub type PortMap = BTreeMap<PortPos, PortKind>;
@@ -29,6 +29,15 @@ impl PortPos {
     }
 }
 
+impl Symmetric for PortPos {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        Self {
+            hex: self.hex.transform(symmetry),
+            orient: self.orient.transform(symmetry),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BoardArrangement {
     field_radius: u8,
@@ -107,6 +116,26 @@ impl BoardArrangement {
     }
 }
 
+/// Symmetries map the board around its central hex.
+impl Symmetric for BoardArrangement {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        let mut tiles = self.tiles.clone();
+        for (hex, tile) in self.hex_enum_iter() {
+            tiles[hex.transform(symmetry).index().to_spiral()] = tile;
+        }
+
+        Self {
+            field_radius: self.field_radius,
+            tiles,
+            port_map: self
+                .port_map
+                .iter()
+                .map(|(pos, port)| (pos.transform(symmetry), *port))
+                .collect(),
+        }
+    }
+}
+
 impl Index<usize> for BoardArrangement {
     type Output = Tile;
 
@@ -147,3 +176,36 @@ impl IndexMut<DiceVal> for HexesByNum {
         &mut self.arr[index as usize]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gameplay::field::state::FieldBuildParam;
+
+    fn tiles(arrangement: &BoardArrangement) -> Vec<String> {
+        arrangement.iter().map(|tile| format!("{tile:?}")).collect()
+    }
+
+    #[test]
+    fn arrangement_symmetries_move_tiles_and_ports_together() {
+        let arrangement = FieldBuildParam::default().arrangement;
+
+        for symmetry in HexSymmetry::all() {
+            let image = arrangement.transform(symmetry);
+
+            for (hex, tile) in arrangement.hex_enum_iter() {
+                assert_eq!(
+                    format!("{:?}", image[hex.transform(symmetry)]),
+                    format!("{tile:?}")
+                );
+            }
+            for (pos, port) in arrangement.ports() {
+                assert_eq!(image.ports().get(&pos.transform(symmetry)), Some(port));
+            }
+            assert_eq!(
+                tiles(&image.transform(symmetry.inverse())),
+                tiles(&arrangement)
+            );
+        }
+    }
+}
@@ -3,36 +3,126 @@ use crate::agent::action::{
     self, ChoosePlayerToRobAction, DecisionRequest, DropHalfAction, InitAction, InitStageAction,
     MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction,
 };
-use crate::gameplay::agent::agent::Agent;
+use crate::gameplay::agent::agent::{Agent, AgentError, PlayerRuntime};
+use crate::gameplay::agent::lazy::LazyAgent;
 use crate::gameplay::game::event::{
     GameEndPlayerStats, GameEvent, GameObserver, ObserverKind, ObserverNotificationContext,
+    RejectedAction, RejectionReason,
 };
 use crate::gameplay::game::index::GameIndex;
 use crate::gameplay::game::init::GameInitializationState;
 use crate::gameplay::game::query::GameQuery;
-use crate::gameplay::game::view::{ContextFactory, SearchFactory, VisibilityConfig};
+use crate::gameplay::game::view::{
+    ContextFactory, PlayerDecisionContext, PublicGameView, SearchFactory, VisibilityConfig,
+};
 use crate::gameplay::primitives::bank::BankResourceExchangeError;
 use crate::gameplay::primitives::build::{BuildingError, Establishment, EstablishmentType};
 use crate::gameplay::primitives::dev_card::{DevCardUsage, UsableDevCard};
 use crate::gameplay::primitives::player::PlayerId;
-use crate::gameplay::primitives::resource::ResourceCollection;
+use crate::gameplay::primitives::resource::{Resource, ResourceCollection};
 use crate::gameplay::primitives::trade::{BankTrade, BankTradeKind};
-use crate::gameplay::primitives::turn::GameTurn;
+use crate::gameplay::primitives::turn::{GameTurn, TurnPhase};
 use crate::gameplay::primitives::{PortKind, Tile};
 use crate::topology::Hex;
 use crate::{math::dice::DiceRoller, math::dice::DiceVal};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum GameResult {
     Win(PlayerId),
-    Interrupted { reason: String },
-    LimitReached { turns: u64 },
+    /// `player_id` conceded and there was no stand-in to take over its seat;
+    /// `winner` led the others on victory points.
+    Resigned {
+        player_id: PlayerId,
+        winner: PlayerId,
+    },
+    /// `player_id` ran out of invalid actions and there was no stand-in to
+    /// take over its seat; `winner` led the others on victory points.
+    Forfeited {
+        player_id: PlayerId,
+        winner: PlayerId,
+    },
+    Interrupted {
+        reason: String,
+    },
+    LimitReached {
+        turns: u64,
+    },
+}
+
+impl GameResult {
+    /// Who won, however the game ended.
+    pub fn winner(&self) -> Option<PlayerId> {
+        match self {
+            Self::Win(winner) | Self::Resigned { winner, .. } | Self::Forfeited { winner, .. } => {
+                Some(*winner)
+            }
+            Self::Interrupted { .. } | Self::LimitReached { .. } => None,
+        }
+    }
+}
+
+/// What the controller does when an agent fails to decide.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AgentErrorPolicy {
+    /// Asks the agent again, up to `attempts` more times in a row, then
+    /// interrupts the game.
+    Retry { attempts: u32 },
+    /// Hands the seat to the stand-in and asks it instead; interrupts the
+    /// game if there is none or it fails too.
+    Fallback,
+    /// Interrupts the game with the error as the reason.
+    #[default]
+    Interrupt,
+}
+
+impl AgentErrorPolicy {
+    /// Whether to ask a seat again after its agent failed `failures` times
+    /// in a row. `fall_back` hands the seat to the stand-in and tells
+    /// whether there was one.
+    fn recovers(self, failures: u32, fall_back: impl FnOnce() -> bool) -> bool {
+        match self {
+            Self::Retry { attempts } => failures <= attempts,
+            Self::Fallback => failures == 1 && fall_back(),
+            Self::Interrupt => false,
+        }
+    }
+}
+
+/// What happens to a player whose clock runs out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutRule {
+    /// The stand-in, or a [`LazyAgent`] if there is none, decides for the
+    /// player and its turns end as soon as they may, until an increment
+    /// puts time back on the clock.
+    #[default]
+    EndTurn,
+    /// Forfeits the seat as if the player ran out of invalid actions.
+    Forfeit,
+}
+
+/// A chess clock per player: everyone starts with `bank` and gets
+/// `increment` more at the end of each of its turns. The clock only runs
+/// while the player's agent decides, from the first turn on. Agents see the
+/// time left in their decision context and fail with
+/// [`AgentError::TimedOut`] once it runs out; an answer that comes in late
+/// anyway is thrown away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub bank: Duration,
+    pub increment: Duration,
+    pub on_timeout: TimeoutRule,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct RunOptions {
     pub max_turns: Option<u64>,
     pub max_invalid_actions: Option<u64>,
+    pub agent_errors: AgentErrorPolicy,
+    pub time_control: Option<TimeControl>,
 }
 
 impl Default for RunOptions {
@@ -40,6 +130,8 @@ impl Default for RunOptions {
         Self {
             max_turns: Some(500),
             max_invalid_actions: Some(10),
+            agent_errors: AgentErrorPolicy::default(),
+            time_control: None,
         }
     }
 }
@@ -48,7 +140,24 @@ enum TurnFlow {
     Continue,
     EndTurn,
     GameEnded(PlayerId),
-    Interrupted { reason: String },
+    /// A player left the game and nobody took over its seat.
+    Left {
+        player_id: PlayerId,
+        forfeit: bool,
+    },
+    Interrupted {
+        reason: String,
+    },
+}
+
+impl TurnFlow {
+    /// Whether the game is over rather than the turn going on.
+    fn is_game_over(&self) -> bool {
+        matches!(
+            self,
+            Self::GameEnded(_) | Self::Left { .. } | Self::Interrupted { .. }
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -57,6 +166,15 @@ enum BankTradeExecutionError {
     Exchange(BankResourceExchangeError),
 }
 
+impl From<&BankTradeExecutionError> for RejectionReason {
+    fn from(err: &BankTradeExecutionError) -> Self {
+        match err {
+            BankTradeExecutionError::MissingPort(port) => Self::MissingPort(*port),
+            BankTradeExecutionError::Exchange(err) => err.into(),
+        }
+    }
+}
+
 impl std::fmt::Display for BankTradeExecutionError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -66,14 +184,27 @@ impl std::fmt::Display for BankTradeExecutionError {
     }
 }
 
+/// Makes the agent taking over a seat whose player left.
+pub type StandIn = Box<dyn FnMut(PlayerId) -> Box<dyn Agent>>;
+
 pub struct GameController {
     observers: Vec<Box<dyn GameObserver>>,
     game: GameState,
     index: GameIndex,
     players: Vec<Box<dyn Agent>>,
     visibility: VisibilityConfig,
-    invalid_actions: u64,
+    /// Invalid actions of every seat since the run started or the seat got
+    /// a new agent.
+    invalid_actions: Vec<u64>,
     max_invalid_actions: Option<u64>,
+    agent_errors: AgentErrorPolicy,
+    /// Makes the agent taking over the seat of a player who left.
+    stand_in: Option<StandIn>,
+    time_control: Option<TimeControl>,
+    /// Time left on the clock of every seat in a timed run.
+    clocks: Vec<Duration>,
+    /// Stand-ins deciding for the seats that ran out of time.
+    autopilots: Vec<Option<Box<dyn Agent>>>,
 }
 
 impl GameController {
@@ -92,12 +223,17 @@ impl GameController {
 
         Self {
             observers: Vec::new(),
+            invalid_actions: vec![0; game.players.count()],
+            autopilots: (0..game.players.count()).map(|_| None).collect(),
             game,
             index,
             players,
             visibility,
-            invalid_actions: 0,
             max_invalid_actions: RunOptions::default().max_invalid_actions,
+            agent_errors: AgentErrorPolicy::default(),
+            stand_in: None,
+            time_control: None,
+            clocks: Vec::new(),
         }
     }
 
@@ -106,19 +242,56 @@ impl GameController {
         self.observers.push(observer);
     }
 
+    /// Lets an agent made by `stand_in` take over the seat of a player who
+    /// resigned or forfeited, instead of ending the game. It's also the
+    /// fallback of [`AgentErrorPolicy::Fallback`].
+    pub fn set_stand_in(&mut self, stand_in: impl FnMut(PlayerId) -> Box<dyn Agent> + 'static) {
+        self.stand_in = Some(Box::new(stand_in));
+    }
+
+    /// Hands the seat of `player_id` to `agent` and returns the agent that
+    /// played it so far. The seat's invalid actions and clock start over.
+    pub fn replace_agent(&mut self, player_id: PlayerId, agent: Box<dyn Agent>) -> Box<dyn Agent> {
+        log::info!("Player#{player_id} is played by a new agent");
+        let previous = std::mem::replace(&mut self.players[player_id], agent);
+        self.invalid_actions[player_id] = 0;
+        if let Some(time_control) = self.time_control {
+            self.clocks[player_id] = time_control.bank;
+        }
+        self.autopilots[player_id] = None;
+        self.notify_observers(&GameEvent::AgentReplaced { player_id });
+        previous
+    }
+
     pub fn init(
         game_init: GameInitializationState,
         players: &mut Vec<Box<dyn Agent>>,
-    ) -> GameState {
+    ) -> Result<GameState, GameResult> {
         Self::init_with_observers(game_init, players, &mut [])
     }
 
     pub fn init_with_observers(
+        game_init: GameInitializationState,
+        players: &mut Vec<Box<dyn Agent>>,
+        observers: &mut [Box<dyn GameObserver>],
+    ) -> Result<GameState, GameResult> {
+        Self::init_with_options(game_init, players, observers, RunOptions::default(), None)
+    }
+
+    /// Plays the initial placements. A game that ends during setup gives its
+    /// result instead of a state. Agents failing to decide are handled by
+    /// the `agent_errors` policy of the options, falling back to `stand_in`;
+    /// running out of invalid actions interrupts the setup, as nobody has
+    /// left the game yet. The turn and time limits don't apply to setup.
+    pub fn init_with_options(
         mut game_init: GameInitializationState,
         players: &mut Vec<Box<dyn Agent>>,
         observers: &mut [Box<dyn GameObserver>],
-    ) -> GameState {
+        options: RunOptions,
+        mut stand_in: Option<&mut StandIn>,
+    ) -> Result<GameState, GameResult> {
         log::trace!("Initializing game with {} players", players.len());
+        let mut invalid_actions = vec![0; players.len()];
         Self::notify_observers_for_state(
             &game_init.clone().finish(),
             observers,
@@ -133,6 +306,7 @@ impl GameController {
                 player_id
             );
 
+            let mut failures = 0;
             loop {
                 let state = game_init.clone().finish();
                 let index = GameIndex::rebuild(&state);
@@ -142,10 +316,41 @@ impl GameController {
                     visibility: &VisibilityConfig::default(),
                 };
 
-                let action = InitStageAction::request(
+                let action = match InitStageAction::request(
                     players[player_id].as_mut(),
                     factory.player_decision_context(player_id, None),
-                );
+                ) {
+                    Ok(action) => action,
+                    Err(err) => {
+                        failures += 1;
+                        log::warn!(
+                            "Player#{player_id} failed to decide during setup ({failures} in a row): {err}"
+                        );
+                        let recovered = options.agent_errors.recovers(failures, || {
+                            let Some(stand_in) = stand_in.as_mut() else {
+                                return false;
+                            };
+                            log::info!("Player#{player_id} is played by a new agent");
+                            players[player_id] = stand_in(player_id);
+                            invalid_actions[player_id] = 0;
+                            Self::notify_observers_for_state(
+                                &state,
+                                observers,
+                                &GameEvent::AgentReplaced { player_id },
+                            );
+                            true
+                        });
+                        if recovered {
+                            continue;
+                        }
+                        return Err(Self::interrupt_setup(
+                            &state,
+                            observers,
+                            format!("Player#{player_id} failed to decide: {err}"),
+                        ));
+                    }
+                };
+                failures = 0;
                 log::trace!(
                     "Player {} requested init placement: road={:?}, settlement={:?}",
                     player_id,
@@ -181,6 +386,32 @@ impl GameController {
                             _ => unreachable!(),
                         };
 
+                        let rejection = GameEvent::ActionRejected {
+                            player_id,
+                            action: RejectedAction::InitialPlacement(action),
+                            reason: RejectionReason::InvalidPlacement,
+                        };
+                        players[player_id]
+                            .on_event(&rejection, factory.player_notification_context(player_id));
+                        Self::notify_for_state(&state, observers, &rejection, Some(player_id));
+
+                        invalid_actions[player_id] += 1;
+                        log::warn!(
+                            "Invalid action count of Player#{} during setup: {}",
+                            player_id,
+                            invalid_actions[player_id]
+                        );
+                        if let Some(limit) = options.max_invalid_actions
+                            && invalid_actions[player_id] >= limit
+                        {
+                            return Err(Self::interrupt_setup(
+                                &state,
+                                observers,
+                                format!(
+                                    "Player#{player_id} reached the invalid action limit ({limit}) during setup"
+                                ),
+                            ));
+                        }
                         continue;
                     }
                     Ok(()) => {
@@ -215,20 +446,50 @@ impl GameController {
         let n_players = game_init.board.n_players as u8;
         log::trace!("Game initialization complete, {} players", n_players);
 
-        GameState {
-            board: game_init.board,
-            board_state: game_init.board_state,
-            turn: GameTurn::new(n_players),
-            bank: game_init.bank,
-            players: game_init.players,
-            builds: game_init.builds,
-        }
+        let mut state = GameState::new(
+            game_init.board,
+            game_init.board_state,
+            GameTurn::new(n_players),
+            game_init.bank,
+            game_init.players,
+            game_init.builds,
+        );
+        state.rng = game_init.rng;
+        Ok(state)
+    }
+
+    /// Tells the observers that setup ended early and why.
+    fn interrupt_setup(
+        game: &GameState,
+        observers: &mut [Box<dyn GameObserver>],
+        reason: String,
+    ) -> GameResult {
+        log::error!("Game interrupted during setup: {reason}");
+        Self::notify_observers_for_state(
+            game,
+            observers,
+            &GameEvent::GameInterrupted {
+                reason: reason.clone(),
+            },
+        );
+        GameResult::Interrupted { reason }
     }
 
     fn notify_observers_for_state(
         game: &GameState,
         observers: &mut [Box<dyn GameObserver>],
         event: &GameEvent,
+    ) {
+        Self::notify_for_state(game, observers, event, None);
+    }
+
+    /// Notifies the observers during setup; with `only`, just those seeing
+    /// the hand of that player.
+    fn notify_for_state(
+        game: &GameState,
+        observers: &mut [Box<dyn GameObserver>],
+        event: &GameEvent,
+        only: Option<PlayerId>,
     ) {
         log::trace!(
             "Notifying {} initialization observers of event: {:?}",
@@ -244,6 +505,11 @@ impl GameController {
         };
 
         for observer in observers.iter_mut() {
+            if let Some(player_id) = only
+                && !observer_sees_hand(observer.kind(), player_id)
+            {
+                continue;
+            }
             log::trace!(
                 "Notifying initialization observer of kind {:?}",
                 observer.kind()
@@ -298,9 +564,19 @@ impl GameController {
     }
 
     fn notify_observers(&mut self, event: &GameEvent) {
+        self.notify(event, None);
+    }
+
+    /// Tells only `player_id` and the observers seeing its hand.
+    fn notify_player(&mut self, player_id: PlayerId, event: &GameEvent) {
+        self.notify(event, Some(player_id));
+    }
+
+    fn notify(&mut self, event: &GameEvent, only: Option<PlayerId>) {
         log::trace!("Notifying observers of event: {:?}", event);
         log::info!("Event: {:?}", event);
 
+        let clocks = self.time_control.map(|_| self.clocks.as_slice());
         let (game, index, visibility, players, observers) = (
             &self.game,
             &self.index,
@@ -317,12 +593,21 @@ impl GameController {
         log::trace!("Notifying {} players", players.len());
         for player in players.iter_mut() {
             let player_id = player.player_id();
-            let cx = factory.player_notification_context(player_id);
+            if only.is_some_and(|only| only != player_id) {
+                continue;
+            }
+            let mut cx = factory.player_notification_context(player_id);
+            cx.public = with_clocks(cx.public, clocks);
             player.on_event(event, cx);
         }
 
         log::trace!("Notifying {} observers", observers.len());
         for observer in observers.iter_mut() {
+            if let Some(player_id) = only
+                && !observer_sees_hand(observer.kind(), player_id)
+            {
+                continue;
+            }
             log::trace!(
                 target: "catan_core::observer_flow",
                 "game emit event={:?} kind={:?} {}",
@@ -332,14 +617,17 @@ impl GameController {
             );
             let cx = match observer.kind() {
                 ObserverKind::Spectator => ObserverNotificationContext::Spectator {
-                    public: factory.spectator_public_view(),
+                    public: with_clocks(factory.spectator_public_view(), clocks),
                 },
                 ObserverKind::Player(player_id) => ObserverNotificationContext::Player {
-                    public: factory.public_view(visibility.player_policy(player_id)),
+                    public: with_clocks(
+                        factory.public_view(visibility.player_policy(player_id)),
+                        clocks,
+                    ),
                     private: factory.private_view(player_id),
                 },
                 ObserverKind::Omniscient => ObserverNotificationContext::Omniscient {
-                    public: factory.spectator_public_view(),
+                    public: with_clocks(factory.spectator_public_view(), clocks),
                     full: factory.omniscient_view(),
                 },
             };
@@ -353,46 +641,167 @@ impl GameController {
         player
     }
 
-    fn request_dispatch<R: action::DecisionRequest>(&mut self, player_id: PlayerId) -> R {
+    fn request_dispatch<R: action::DecisionRequest>(
+        &mut self,
+        player_id: PlayerId,
+    ) -> Result<R, TurnFlow> {
+        self.request_with(player_id, R::request)
+    }
+
+    /// Asks the agent of `player_id` for a decision, recovering from its
+    /// errors as the agent error policy says and from running out of time
+    /// as the timeout rule says. The agent is trusted to give up when its
+    /// clock runs out; the clock is charged once it returns. `Err` holds
+    /// how the game ends instead.
+    fn request_with<R>(
+        &mut self,
+        player_id: PlayerId,
+        mut ask: impl FnMut(&mut dyn PlayerRuntime, PlayerDecisionContext<'_>) -> Result<R, AgentError>,
+    ) -> Result<R, TurnFlow> {
         log::trace!("Dispatching decision request for player {}", player_id);
-        let policy = self.visibility.player_policy(player_id);
-        let search = Some(SearchFactory::new(&self.game, policy, player_id));
-        let factory = ContextFactory {
-            state: &self.game,
-            index: &self.index,
-            visibility: &self.visibility,
-        };
-        let context = factory.player_decision_context(player_id, search);
+        let mut failures = 0;
+        loop {
+            let policy = self.visibility.player_policy(player_id);
+            let search = Some(SearchFactory::new(&self.game, policy, player_id));
+            let factory = ContextFactory {
+                state: &self.game,
+                index: &self.index,
+                visibility: &self.visibility,
+            };
+            let mut context = factory.player_decision_context(player_id, search);
+            context.public = with_clocks(
+                context.public,
+                self.time_control.map(|_| self.clocks.as_slice()),
+            );
+
+            if let Some(autopilot) = self.autopilots[player_id].as_mut() {
+                return ask(autopilot.as_mut(), context).map_err(|err| TurnFlow::Interrupted {
+                    reason: format!("stand-in of Player#{player_id} failed to decide: {err}"),
+                });
+            }
+
+            let started = Instant::now();
+            let answer = ask(self.players[player_id].as_mut(), context);
+            let elapsed = match answer {
+                // the agent stopped waiting because its clock ran out
+                Err(AgentError::TimedOut) => Duration::MAX,
+                _ => started.elapsed(),
+            };
+            if self.charge_clock(player_id, elapsed) {
+                self.run_out_of_time(player_id)?;
+                continue;
+            }
+            match answer {
+                Ok(decision) => return Ok(decision),
+                Err(err) => {
+                    failures += 1;
+                    self.recover_from_agent_error(player_id, err, failures)?;
+                }
+            }
+        }
+    }
+
+    /// `Ok` when the seat should be asked again after its agent failed
+    /// `failures` times in a row.
+    fn recover_from_agent_error(
+        &mut self,
+        player_id: PlayerId,
+        err: AgentError,
+        failures: u32,
+    ) -> Result<(), TurnFlow> {
+        log::warn!("Player#{player_id} failed to decide ({failures} in a row): {err}");
+        let policy = self.agent_errors;
+        if policy.recovers(failures, || self.hand_to_stand_in(player_id)) {
+            return Ok(());
+        }
+        Err(TurnFlow::Interrupted {
+            reason: format!("Player#{player_id} failed to decide: {err}"),
+        })
+    }
+
+    /// Takes `elapsed` off the clock of `player_id`; whether that ran it out.
+    fn charge_clock(&mut self, player_id: PlayerId, elapsed: Duration) -> bool {
+        if self.time_control.is_none() {
+            return false;
+        }
+        let clock = &mut self.clocks[player_id];
+        *clock = clock.saturating_sub(elapsed);
+        clock.is_zero()
+    }
+
+    /// Applies the timeout rule to `player_id`, whose clock ran out. `Ok`
+    /// when the seat should be asked again.
+    fn run_out_of_time(&mut self, player_id: PlayerId) -> Result<(), TurnFlow> {
+        log::warn!("Player#{player_id} ran out of time");
+        self.notify_observers(&GameEvent::PlayerOutOfTime { player_id });
+        let rule = self
+            .time_control
+            .map(|time_control| time_control.on_timeout)
+            .unwrap_or_default();
+        if rule == TimeoutRule::EndTurn {
+            self.autopilots[player_id] = Some(match self.stand_in.as_mut() {
+                Some(stand_in) => stand_in(player_id),
+                None => Box::new(LazyAgent::new(player_id)),
+            });
+            return Ok(());
+        }
+        match self.leave(player_id, true) {
+            Some(flow) => Err(flow),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether a stand-in decides for `player_id` because it ran out of
+    /// time, in which case its turns end as soon as they may.
+    fn on_autopilot(&self, player_id: PlayerId) -> bool {
+        self.autopilots[player_id].is_some()
+    }
 
-        R::request(self.players[player_id].as_mut(), context)
+    /// Puts the increment on the clock of `player_id` after its turn, which
+    /// gives the seat back to its agent if it ran out of time.
+    fn add_increment(&mut self, player_id: PlayerId) {
+        let Some(time_control) = self.time_control else {
+            return;
+        };
+        let clock = &mut self.clocks[player_id];
+        *clock += time_control.increment;
+        if !clock.is_zero() {
+            self.autopilots[player_id] = None;
+        }
     }
 
-    fn request_init_action(&mut self, player_id: PlayerId) -> InitAction {
+    fn request_init_action(&mut self, player_id: PlayerId) -> Result<InitAction, TurnFlow> {
         log::trace!("Requesting init action from player {}", player_id);
         self.request_dispatch::<InitAction>(player_id)
     }
 
-    fn request_post_dice_action(&mut self, player_id: PlayerId) -> PostDiceAction {
+    fn request_post_dice_action(
+        &mut self,
+        player_id: PlayerId,
+    ) -> Result<PostDiceAction, TurnFlow> {
         log::trace!("Requesting post-dice action from player {}", player_id);
         self.request_dispatch::<PostDiceAction>(player_id)
     }
 
-    fn request_post_dev_card_action(&mut self, player_id: PlayerId) -> PostDevCardAction {
+    fn request_post_dev_card_action(
+        &mut self,
+        player_id: PlayerId,
+    ) -> Result<PostDevCardAction, TurnFlow> {
         log::trace!("Requesting post-dev-card action from player {}", player_id);
         self.request_dispatch::<PostDevCardAction>(player_id)
     }
 
-    fn request_regular_action(&mut self, player_id: PlayerId) -> RegularAction {
+    fn request_regular_action(&mut self, player_id: PlayerId) -> Result<RegularAction, TurnFlow> {
         log::trace!("Requesting regular action from player {}", player_id);
         self.request_dispatch::<RegularAction>(player_id)
     }
 
-    fn request_drop_half(&mut self, player_id: PlayerId) -> DropHalfAction {
+    fn request_drop_half(&mut self, player_id: PlayerId) -> Result<DropHalfAction, TurnFlow> {
         log::trace!("Requesting drop-half action from player {}", player_id);
         self.request_dispatch::<DropHalfAction>(player_id)
     }
 
-    fn request_move_robbers(&mut self, player_id: PlayerId) -> MoveRobbersAction {
+    fn request_move_robbers(&mut self, player_id: PlayerId) -> Result<MoveRobbersAction, TurnFlow> {
         log::trace!("Requesting move-robbers action from player {}", player_id);
         self.request_dispatch::<MoveRobbersAction>(player_id)
     }
@@ -401,23 +810,14 @@ impl GameController {
         &mut self,
         player_id: PlayerId,
         robber_pos: Hex,
-    ) -> ChoosePlayerToRobAction {
+    ) -> Result<ChoosePlayerToRobAction, TurnFlow> {
         log::trace!(
             "Requesting choose-player-to-rob action from player {}",
             player_id
         );
-        let policy = self.visibility.player_policy(player_id);
-        let search = Some(SearchFactory::new(&self.game, policy, player_id));
-        let factory = ContextFactory {
-            state: &self.game,
-            index: &self.index,
-            visibility: &self.visibility,
-        };
-        let context = factory.player_decision_context(player_id, search);
-
-        self.players[player_id]
-            .as_mut()
-            .choose_player_to_rob(context, robber_pos)
+        self.request_with(player_id, |player, context| {
+            player.choose_player_to_rob(context, robber_pos)
+        })
     }
 
     pub fn run(&mut self, dice: &mut dyn DiceRoller) -> GameResult {
@@ -437,8 +837,15 @@ impl GameController {
         options: RunOptions,
     ) -> GameResult {
         log::trace!("Starting game run with options: {:?}", options);
-        self.invalid_actions = 0;
+        self.invalid_actions.fill(0);
         self.max_invalid_actions = options.max_invalid_actions;
+        self.agent_errors = options.agent_errors;
+        self.time_control = options.time_control;
+        self.clocks = match options.time_control {
+            Some(time_control) => vec![time_control.bank; self.players.len()],
+            None => Vec::new(),
+        };
+        self.autopilots.fill_with(|| None);
         self.notify_observers(&GameEvent::GameStarted);
         loop {
             let turn_no = self.game.turn.get_turns_played();
@@ -475,6 +882,20 @@ impl GameController {
                     });
                     return GameResult::Win(winner);
                 }
+                TurnFlow::Left { player_id, forfeit } => {
+                    let winner = self.leader_besides(player_id);
+                    log::info!("Player#{player_id} left the game, Player#{winner} wins");
+                    self.notify_observers(&GameEvent::GameEnded {
+                        winner_id: winner,
+                        turn_no,
+                        stats: self.game_end_stats(),
+                    });
+                    return if forfeit {
+                        GameResult::Forfeited { player_id, winner }
+                    } else {
+                        GameResult::Resigned { player_id, winner }
+                    };
+                }
                 TurnFlow::Interrupted { reason } => {
                     log::error!("Game interrupted: {reason}");
                     self.notify_observers(&GameEvent::GameInterrupted {
@@ -489,8 +910,9 @@ impl GameController {
                         player_id,
                         turn_no
                     );
+                    self.add_increment(player_id);
                     self.notify_observers(&GameEvent::TurnEnded { player_id, turn_no });
-                    self.game.turn.next();
+                    self.game.next_turn();
                 }
             }
         }
@@ -500,10 +922,7 @@ impl GameController {
         let current_player = self.curr_player();
         log::trace!("Handling turn for player {}", current_player);
 
-        self.game
-            .players
-            .get_mut(current_player)
-            .dev_cards_reset_queue();
+        self.game.reset_dev_card_queue(current_player);
 
         self.notify_observers(&GameEvent::TurnStarted {
             player_id: current_player,
@@ -515,13 +934,17 @@ impl GameController {
 
     fn handle_move_init(&mut self, dice: &mut dyn DiceRoller) -> TurnFlow {
         log::trace!("Handling move init for player {}", self.curr_player());
-        let answer = self.request_init_action(self.curr_player());
+        let answer = match self.request_init_action(self.curr_player()) {
+            Ok(answer) => answer,
+            Err(flow) => return flow,
+        };
 
         match answer {
             InitAction::RollDice => {
                 log::trace!("Player chose to roll dice");
-                if let TurnFlow::Interrupted { reason } = self.execute_dice_roll(dice) {
-                    return TurnFlow::Interrupted { reason };
+                let flow = self.execute_dice_roll(dice);
+                if flow.is_game_over() {
+                    return flow;
                 }
                 self.handle_dice_rolled()
             }
@@ -534,7 +957,13 @@ impl GameController {
 
     fn handle_dice_rolled(&mut self) -> TurnFlow {
         log::trace!("Handling post-dice state for player {}", self.curr_player());
-        let answer = self.request_post_dice_action(self.curr_player());
+        if self.on_autopilot(self.curr_player()) {
+            return TurnFlow::EndTurn;
+        }
+        let answer = match self.request_post_dice_action(self.curr_player()) {
+            Ok(answer) => answer,
+            Err(flow) => return flow,
+        };
 
         match answer {
             PostDiceAction::UseDevCard(usage) => {
@@ -546,7 +975,12 @@ impl GameController {
                         usage,
                         err
                     );
-                    if let Some(flow) = self.record_invalid_action("post_dice_dev_card") {
+                    if let Some(flow) = self.reject(
+                        self.curr_player(),
+                        RejectedAction::DevCard(usage),
+                        err.into(),
+                        "post_dice_dev_card",
+                    ) {
                         return flow;
                     }
                     log::warn!("Dev card usage failed, retrying post-dice handling");
@@ -568,8 +1002,7 @@ impl GameController {
                         log::trace!("Regular action returned EndTurn");
                         TurnFlow::EndTurn
                     }
-                    TurnFlow::GameEnded(winner) => TurnFlow::GameEnded(winner),
-                    TurnFlow::Interrupted { reason } => TurnFlow::Interrupted { reason },
+                    flow => flow,
                 }
             }
         }
@@ -584,7 +1017,12 @@ impl GameController {
                 usage,
                 err
             );
-            if let Some(flow) = self.record_invalid_action("dev_card") {
+            if let Some(flow) = self.reject(
+                self.curr_player(),
+                RejectedAction::DevCard(usage),
+                err.into(),
+                "dev_card",
+            ) {
                 return flow;
             }
             log::warn!("Dev card usage failed, retrying move init");
@@ -595,10 +1033,13 @@ impl GameController {
         }
 
         log::trace!("Dev card used successfully, requesting post-dev-card action");
-        let _ = self.request_post_dev_card_action(self.curr_player());
+        if let Err(flow) = self.request_post_dev_card_action(self.curr_player()) {
+            return flow;
+        }
 
-        if let TurnFlow::Interrupted { reason } = self.execute_dice_roll(dice) {
-            return TurnFlow::Interrupted { reason };
+        let flow = self.execute_dice_roll(dice);
+        if flow.is_game_over() {
+            return flow;
         }
         self.handle_rest()
     }
@@ -606,7 +1047,13 @@ impl GameController {
     fn handle_rest(&mut self) -> TurnFlow {
         log::trace!("Entering rest loop for player {}", self.curr_player());
         loop {
-            let answer = self.request_regular_action(self.curr_player());
+            if self.on_autopilot(self.curr_player()) {
+                return TurnFlow::EndTurn;
+            }
+            let answer = match self.request_regular_action(self.curr_player()) {
+                Ok(answer) => answer,
+                Err(flow) => return flow,
+            };
             log::trace!("Player chose regular action: {:?}", answer);
 
             match self.execute_regular_action(answer) {
@@ -618,8 +1065,7 @@ impl GameController {
                     log::trace!("Action returned EndTurn, exiting rest loop");
                     return TurnFlow::EndTurn;
                 }
-                TurnFlow::GameEnded(winner) => return TurnFlow::GameEnded(winner),
-                TurnFlow::Interrupted { reason } => return TurnFlow::Interrupted { reason },
+                flow => return flow,
             }
         }
     }
@@ -683,7 +1129,12 @@ impl GameController {
                             build,
                             err
                         );
-                        if let Some(flow) = self.record_invalid_action("build") {
+                        if let Some(flow) = self.reject(
+                            current_player,
+                            RejectedAction::Regular(action),
+                            (&err).into(),
+                            "build",
+                        ) {
                             return flow;
                         }
                     }
@@ -705,7 +1156,12 @@ impl GameController {
                             trade,
                             err
                         );
-                        if let Some(flow) = self.record_invalid_action("bank_trade") {
+                        if let Some(flow) = self.reject(
+                            current_player,
+                            RejectedAction::Regular(action),
+                            (&err).into(),
+                            "bank_trade",
+                        ) {
                             return flow;
                         }
                     }
@@ -729,7 +1185,12 @@ impl GameController {
                             current_player,
                             err
                         );
-                        if let Some(flow) = self.record_invalid_action("buy_dev_card") {
+                        if let Some(flow) = self.reject(
+                            current_player,
+                            RejectedAction::Regular(action),
+                            (&err).into(),
+                            "buy_dev_card",
+                        ) {
                             return flow;
                         }
                     }
@@ -741,7 +1202,12 @@ impl GameController {
                     "Invalid public trade action by Player#{}: P2P trades not implemented",
                     current_player
                 );
-                if let Some(flow) = self.record_invalid_action("public_trade") {
+                if let Some(flow) = self.reject(
+                    current_player,
+                    RejectedAction::Regular(action),
+                    RejectionReason::PlayerTradesUnsupported,
+                    "public_trade",
+                ) {
                     return flow;
                 }
                 TurnFlow::Continue
@@ -751,7 +1217,12 @@ impl GameController {
                     "Invalid personal trade action by Player#{}: P2P trades not implemented",
                     current_player
                 );
-                if let Some(flow) = self.record_invalid_action("personal_trade") {
+                if let Some(flow) = self.reject(
+                    current_player,
+                    RejectedAction::Regular(action),
+                    RejectionReason::PlayerTradesUnsupported,
+                    "personal_trade",
+                ) {
                     return flow;
                 }
                 TurnFlow::Continue
@@ -760,6 +1231,11 @@ impl GameController {
                 log::trace!("Player ending move");
                 TurnFlow::EndTurn
             }
+            RegularAction::Resign => {
+                log::info!("Player#{} resigned", current_player);
+                self.leave(current_player, false)
+                    .unwrap_or(TurnFlow::Continue)
+            }
         }
     }
 
@@ -769,27 +1245,92 @@ impl GameController {
             .map(TurnFlow::GameEnded)
     }
 
-    fn record_invalid_action(&mut self, action_kind: &str) -> Option<TurnFlow> {
-        self.invalid_actions += 1;
+    /// Tells `player_id` why its action was turned down before counting it
+    /// as invalid.
+    fn reject(
+        &mut self,
+        player_id: PlayerId,
+        action: RejectedAction,
+        reason: RejectionReason,
+        action_kind: &str,
+    ) -> Option<TurnFlow> {
+        self.notify_player(
+            player_id,
+            &GameEvent::ActionRejected {
+                player_id,
+                action,
+                reason,
+            },
+        );
+        self.record_invalid_action(player_id, action_kind)
+    }
+
+    fn record_invalid_action(
+        &mut self,
+        player_id: PlayerId,
+        action_kind: &str,
+    ) -> Option<TurnFlow> {
+        self.invalid_actions[player_id] += 1;
         log::warn!(
-            "Invalid action count after {}: {}/{}",
+            "Invalid action count of Player#{} after {}: {}/{}",
+            player_id,
             action_kind,
-            self.invalid_actions,
+            self.invalid_actions[player_id],
             self.max_invalid_actions
                 .map(|limit| limit.to_string())
                 .unwrap_or_else(|| "unlimited".to_owned())
         );
 
         if let Some(limit) = self.max_invalid_actions
-            && self.invalid_actions >= limit
+            && self.invalid_actions[player_id] >= limit
         {
-            let reason = format!("invalid action limit reached: {limit}");
-            return Some(TurnFlow::Interrupted { reason });
+            log::warn!("Player#{player_id} reached the invalid action limit ({limit})");
+            return self.leave(player_id, true);
         }
 
         None
     }
 
+    /// Takes `player_id` out of the game. A stand-in takes over the seat if
+    /// there is one and the game goes on, otherwise it ends.
+    fn leave(&mut self, player_id: PlayerId, forfeit: bool) -> Option<TurnFlow> {
+        self.notify_observers(&if forfeit {
+            GameEvent::PlayerForfeited { player_id }
+        } else {
+            GameEvent::PlayerResigned { player_id }
+        });
+        if self.hand_to_stand_in(player_id) {
+            None
+        } else {
+            Some(TurnFlow::Left { player_id, forfeit })
+        }
+    }
+
+    /// Whether there was a stand-in to take over the seat of `player_id`.
+    fn hand_to_stand_in(&mut self, player_id: PlayerId) -> bool {
+        let Some(stand_in) = self.stand_in.as_mut() else {
+            return false;
+        };
+        let agent = stand_in(player_id);
+        self.replace_agent(player_id, agent);
+        true
+    }
+
+    /// The player with the most victory points besides `player_id`; on a tie
+    /// the one whose turn comes first after it.
+    fn leader_besides(&self, player_id: PlayerId) -> PlayerId {
+        let stats = self.game_end_stats();
+        let mut leader: Option<PlayerId> = None;
+        for id in self.query().player_ids_starting_from(player_id) {
+            if id != player_id
+                && leader.is_none_or(|leader| stats[id].total_vp > stats[leader].total_vp)
+            {
+                leader = Some(id);
+            }
+        }
+        leader.unwrap_or(player_id)
+    }
+
     fn execute_trade_with_bank(
         &mut self,
         player: PlayerId,
@@ -911,21 +1452,31 @@ impl GameController {
             value: roll,
         });
 
-        match roll {
+        let flow = match roll {
             seven if seven == DiceVal::seven() => {
                 log::trace!("Rolled a 7, executing seven handling");
                 self.execute_seven(current_player)
             }
             other => {
                 log::trace!("Rolled {}, executing harvesting", Into::<u8>::into(other));
-                Self::execute_harvesting(&mut self.game, current_player, other);
+                for shortfall in Self::execute_harvesting(&mut self.game, current_player, other) {
+                    self.notify_observers(&shortfall);
+                }
                 self.notify_observers(&GameEvent::ResourcesDistributed);
                 TurnFlow::Continue
             }
+        };
+        if !flow.is_game_over() {
+            self.game.set_phase(TurnPhase::AfterRoll);
         }
+        flow
     }
 
-    fn execute_harvesting(game: &mut GameState, player: PlayerId, num: DiceVal) {
+    /// Pays out the harvest of `num`. A resource the bank can't pay to
+    /// everyone owed it goes to nobody, unless a single player is owed it,
+    /// who gets what is left. Gives a `HarvestShortfall` for each such
+    /// resource.
+    fn execute_harvesting(game: &mut GameState, player: PlayerId, num: DiceVal) -> Vec<GameEvent> {
         log::trace!(
             "Executing harvesting for dice roll {}",
             Into::<u8>::into(num)
@@ -938,7 +1489,8 @@ impl GameController {
         };
         log::trace!("Harvesting order: {:?}", player_ids);
 
-        for pid in player_ids {
+        let mut owed = vec![ResourceCollection::ZERO; game.players.count()];
+        for &pid in &player_ids {
             for est in game.builds[pid].establishments.clone() {
                 let coinc = est.pos.as_set();
 
@@ -952,13 +1504,13 @@ impl GameController {
                         Tile::Resource { resource, .. } => {
                             let amount = est.stage.harvest_amount() as u16;
                             log::trace!(
-                                "Player {} gets {} of {:?} from hex {:?}",
+                                "Player {} is owed {} of {:?} from hex {:?}",
                                 pid,
                                 amount,
                                 resource,
                                 hex
                             );
-                            let _ = game.transfer_from_bank((resource, amount).into(), pid);
+                            owed[pid][resource] += amount;
                         }
                         Tile::Desert => {
                             log::trace!("Hex {:?} is desert, no resource", hex);
@@ -970,13 +1522,54 @@ impl GameController {
                 }
             }
         }
+
+        let mut shortfalls = Vec::new();
+        for resource in Resource::iter() {
+            let total_owed = owed.iter().map(|owed| owed[resource]).sum::<u16>();
+            let in_bank = game.bank.resources[resource];
+            if total_owed <= in_bank {
+                continue;
+            }
+
+            let claimants = player_ids
+                .iter()
+                .copied()
+                .filter(|pid| owed[*pid][resource] > 0)
+                .collect::<Vec<_>>();
+            log::info!(
+                "Bank is short of {:?}: {} owed to {:?}, {} left",
+                resource,
+                total_owed,
+                claimants,
+                in_bank
+            );
+            match claimants.as_slice() {
+                [only] => owed[*only][resource] = in_bank,
+                _ => owed.iter_mut().for_each(|owed| owed[resource] = 0),
+            }
+            shortfalls.push(GameEvent::HarvestShortfall {
+                resource,
+                owed: total_owed,
+                in_bank,
+                claimants,
+            });
+        }
+
+        for pid in player_ids {
+            game.transfer_from_bank(owed[pid], pid)
+                .expect("harvest was cut down to what the bank holds");
+        }
+        shortfalls
     }
 
     fn execute_seven(&mut self, player: PlayerId) -> TurnFlow {
         log::trace!("Executing seven handling for player {}", player);
-        if let TurnFlow::Interrupted { reason } = self.execute_seven_discards(player) {
-            return TurnFlow::Interrupted { reason };
+        self.game.set_phase(TurnPhase::Discarding);
+        let flow = self.execute_seven_discards(player);
+        if flow.is_game_over() {
+            return flow;
         }
+        self.game.set_phase(TurnPhase::MovingRobber);
         self.execute_seven_robber(player)
     }
 
@@ -1002,7 +1595,10 @@ impl GameController {
             );
 
             loop {
-                let DropHalfAction(dropped) = self.request_drop_half(pid);
+                let DropHalfAction(dropped) = match self.request_drop_half(pid) {
+                    Ok(action) => action,
+                    Err(flow) => return flow,
+                };
 
                 /* validations */
 
@@ -1013,7 +1609,14 @@ impl GameController {
                         dropped.total(),
                         required_drop
                     );
-                    if let Some(flow) = self.record_invalid_action("discard") {
+                    if let Some(flow) = self.reject(
+                        pid,
+                        RejectedAction::Discard(dropped),
+                        RejectionReason::WrongDiscardCount {
+                            required: required_drop,
+                        },
+                        "discard",
+                    ) {
                         return flow;
                     }
                     continue;
@@ -1034,7 +1637,12 @@ impl GameController {
                             pid,
                             dropped
                         );
-                        if let Some(flow) = self.record_invalid_action("discard") {
+                        if let Some(flow) = self.reject(
+                            pid,
+                            RejectedAction::Discard(dropped),
+                            RejectionReason::AccountIsShort,
+                            "discard",
+                        ) {
                             return flow;
                         }
                     }
@@ -1050,7 +1658,10 @@ impl GameController {
     fn execute_seven_robber(&mut self, player: PlayerId) -> TurnFlow {
         log::trace!("Executing seven robber movement for player {}", player);
         loop {
-            let MoveRobbersAction(target_hex) = self.request_move_robbers(player);
+            let MoveRobbersAction(target_hex) = match self.request_move_robbers(player) {
+                Ok(action) => action,
+                Err(flow) => return flow,
+            };
 
             /* validations */
 
@@ -1059,7 +1670,12 @@ impl GameController {
                     "Player#{} attempted to keep the robber on the same hex",
                     player
                 );
-                if let Some(flow) = self.record_invalid_action("robber_move") {
+                if let Some(flow) = self.reject(
+                    player,
+                    RejectedAction::MoveRobber(target_hex),
+                    RejectionReason::RobberMustMove,
+                    "robber_move",
+                ) {
                     return flow;
                 }
                 continue;
@@ -1090,7 +1706,10 @@ impl GameController {
                 }
                 _ => loop {
                     let ChoosePlayerToRobAction(chosen) =
-                        self.request_choose_player_to_rob(player, target_hex);
+                        match self.request_choose_player_to_rob(player, target_hex) {
+                            Ok(action) => action,
+                            Err(flow) => return flow,
+                        };
                     if candidates.contains(&chosen) {
                         log::trace!("Player {} chose to rob player {}", player, chosen);
                         break Some(chosen);
@@ -1102,7 +1721,12 @@ impl GameController {
                         chosen,
                         target_hex
                     );
-                    if let Some(flow) = self.record_invalid_action("robber_target") {
+                    if let Some(flow) = self.reject(
+                        player,
+                        RejectedAction::Rob(chosen),
+                        RejectionReason::InvalidRobbery,
+                        "robber_target",
+                    ) {
                         return flow;
                     }
                 },
@@ -1124,7 +1748,12 @@ impl GameController {
                 }
                 Err(err) => {
                     log::error!("Invalid robber move by Player#{}: {:?}", player, err);
-                    if let Some(flow) = self.record_invalid_action("robber_move") {
+                    if let Some(flow) = self.reject(
+                        player,
+                        RejectedAction::MoveRobber(target_hex),
+                        err.into(),
+                        "robber_move",
+                    ) {
                         return flow;
                     }
                 }
@@ -1164,6 +1793,26 @@ impl GameController {
     }
 }
 
+/// Whether observers of `kind` see the hand of `player_id`.
+fn observer_sees_hand(kind: ObserverKind, player_id: PlayerId) -> bool {
+    match kind {
+        ObserverKind::Spectator => false,
+        ObserverKind::Player(id) => id == player_id,
+        ObserverKind::Omniscient => true,
+    }
+}
+
+/// `public` showing the time left on every clock of a timed run.
+fn with_clocks<'a>(
+    mut public: PublicGameView<'a>,
+    clocks: Option<&[Duration]>,
+) -> PublicGameView<'a> {
+    if let Some(clocks) = clocks {
+        public.show_clocks(clocks);
+    }
+    public
+}
+
 fn observer_state_summary(game: &GameState) -> String {
     let settlements: usize = (0..game.players.count())
         .map(|player_id| game.builds.by_player(player_id).settlements_count())
@@ -1186,19 +1835,22 @@ fn observer_state_summary(game: &GameState) -> String {
 
 #[cfg(test)]
 mod tests {
-    use std::{cell::RefCell, rc::Rc};
+    use std::{cell::RefCell, rc::Rc, time::Duration};
 
-    use super::{GameController, GameResult, RunOptions, TurnFlow};
+    use super::{
+        AgentErrorPolicy, GameController, GameResult, RunOptions, StandIn, TimeControl,
+        TimeoutRule, TurnFlow,
+    };
     use crate::agent::action::{
         ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction, MoveRobbersAction,
         PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
     };
-    use crate::gameplay::agent::agent::PlayerRuntime;
+    use crate::gameplay::agent::agent::{AgentError, PlayerRuntime};
     use crate::gameplay::{
         game::{
             event::{
                 GameEvent, GameObserver, ObserverKind, ObserverNotificationContext,
-                PlayerNotification,
+                PlayerNotification, RejectedAction, RejectionReason,
             },
             init::GameInitializationState,
             legal,
@@ -1210,39 +1862,50 @@ mod tests {
             build::{Build, Establishment, EstablishmentType},
             dev_card::DevCardKind,
             player::PlayerId,
-            resource::ResourceCollection,
+            resource::{Resource, ResourceCollection},
         },
     };
     use crate::math::dice::{DiceRoller, DiceVal};
     use crate::topology::Hex;
 
     fn game_with_settlement_on_numbered_hex() -> (GameState, Hex, crate::math::dice::DiceVal) {
+        let (game, hex, num, _) = game_with_settlements_on_numbered_hex(&[0]);
+        (game, hex, num)
+    }
+
+    /// A settlement of every owner, in order, on the first numbered resource
+    /// hex.
+    fn game_with_settlements_on_numbered_hex(
+        owners: &[PlayerId],
+    ) -> (GameState, Hex, crate::math::dice::DiceVal, Resource) {
         let mut init = GameInitializationState::default();
-        let (target_hex, target_num) = init
+        let (target_hex, target_num, target_resource) = init
             .board
             .arrangement
             .hex_enum_iter()
             .find_map(|(hex, tile)| match tile {
-                Tile::Resource { number, .. } => Some((hex, number)),
+                Tile::Resource { resource, number } => Some((hex, number, resource)),
                 Tile::Desert => None,
                 Tile::River { .. } => None,
             })
             .expect("default board should include resource tiles");
 
-        let (settlement, road) = init
-            .builds
-            .query()
-            .possible_initial_placements(&init.board, 0)
-            .into_iter()
-            .find(|(settlement, _)| settlement.pos.as_set().contains(&target_hex))
-            .expect("target resource hex should have a legal adjacent settlement");
+        for &owner in owners {
+            let (settlement, road) = init
+                .builds
+                .query()
+                .possible_initial_placements(&init.board, owner)
+                .into_iter()
+                .find(|(settlement, _)| settlement.pos.as_set().contains(&target_hex))
+                .expect("target resource hex should have a legal adjacent settlement");
 
-        assert_eq!(settlement.stage, EstablishmentType::Settlement);
-        init.builds
-            .try_init_place(0, road, settlement)
-            .expect("generated initial placement should be valid");
+            assert_eq!(settlement.stage, EstablishmentType::Settlement);
+            init.builds
+                .try_init_place(owner, road, settlement)
+                .expect("generated initial placement should be valid");
+        }
 
-        (init.finish(), target_hex, target_num)
+        (init.finish(), target_hex, target_num, target_resource)
     }
 
     #[test]
@@ -1264,6 +1927,39 @@ mod tests {
         assert_eq!(game.players.get(0).resources().total(), 0);
     }
 
+    #[test]
+    fn bank_shortage_pays_nobody_if_several_players_are_owed() {
+        let (mut game, _, target_num, resource) = game_with_settlements_on_numbered_hex(&[0, 1]);
+        game.bank.resources[resource] = 1;
+
+        let shortfalls = GameController::execute_harvesting(&mut game, 0, target_num);
+
+        assert_eq!(game.players.get(0).resources().total(), 0);
+        assert_eq!(game.players.get(1).resources().total(), 0);
+        assert_eq!(game.bank.resources[resource], 1);
+        assert!(matches!(
+            shortfalls.as_slice(),
+            [GameEvent::HarvestShortfall {
+                owed: 2,
+                in_bank: 1,
+                claimants,
+                ..
+            }] if *claimants == [0, 1]
+        ));
+    }
+
+    #[test]
+    fn bank_shortage_pays_what_is_left_to_a_single_player() {
+        let (mut game, _, target_num, resource) = game_with_settlements_on_numbered_hex(&[0, 0]);
+        game.bank.resources[resource] = 1;
+
+        let shortfalls = GameController::execute_harvesting(&mut game, 0, target_num);
+
+        assert_eq!(game.players.get(0).resources()[resource], 1);
+        assert_eq!(game.bank.resources[resource], 0);
+        assert_eq!(shortfalls.len(), 1);
+    }
+
     #[test]
     fn second_initial_settlement_grants_adjacent_resources() {
         let mut init = GameInitializationState::default();
@@ -1360,37 +2056,52 @@ mod tests {
             self.id
         }
 
-        fn init_stage_action(&mut self, _context: PlayerDecisionContext<'_>) -> InitStageAction {
+        fn init_stage_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<InitStageAction, AgentError> {
             unreachable!("initial stage is not used in this controller test")
         }
 
-        fn init_action(&mut self, _context: PlayerDecisionContext<'_>) -> InitAction {
-            InitAction::RollDice
+        fn init_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<InitAction, AgentError> {
+            Ok(InitAction::RollDice)
         }
 
-        fn after_dice_action(&mut self, _context: PlayerDecisionContext<'_>) -> PostDiceAction {
-            match self.invalid_actions_before_end {
+        fn after_dice_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDiceAction, AgentError> {
+            Ok(match self.invalid_actions_before_end {
                 Some(0) => PostDiceAction::RegularAction(RegularAction::EndMove),
                 Some(ref mut remaining) => {
                     *remaining -= 1;
                     PostDiceAction::RegularAction(RegularAction::BuyDevCard)
                 }
                 None => PostDiceAction::RegularAction(RegularAction::BuyDevCard),
-            }
+            })
         }
 
         fn after_dev_card_action(
             &mut self,
             _context: PlayerDecisionContext<'_>,
-        ) -> PostDevCardAction {
-            PostDevCardAction::RollDice
+        ) -> Result<PostDevCardAction, AgentError> {
+            Ok(PostDevCardAction::RollDice)
         }
 
-        fn regular_action(&mut self, _context: PlayerDecisionContext<'_>) -> RegularAction {
-            RegularAction::EndMove
+        fn regular_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<RegularAction, AgentError> {
+            Ok(RegularAction::EndMove)
         }
 
-        fn move_robbers(&mut self, _context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
+        fn move_robbers(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<MoveRobbersAction, AgentError> {
             unreachable!("fixed dice never rolls seven")
         }
 
@@ -1398,15 +2109,21 @@ mod tests {
             &mut self,
             _context: PlayerDecisionContext<'_>,
             _robber_pos: Hex,
-        ) -> ChoosePlayerToRobAction {
+        ) -> Result<ChoosePlayerToRobAction, AgentError> {
             unreachable!("fixed dice never rolls seven")
         }
 
-        fn answer_trade(&mut self, _context: PlayerDecisionContext<'_>) -> TradeAnswer {
-            TradeAnswer::Decline
+        fn answer_trade(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<TradeAnswer, AgentError> {
+            Ok(TradeAnswer::Decline)
         }
 
-        fn drop_half(&mut self, _context: PlayerDecisionContext<'_>) -> DropHalfAction {
+        fn drop_half(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<DropHalfAction, AgentError> {
             unreachable!("fixed dice never rolls seven")
         }
     }
@@ -1425,11 +2142,27 @@ mod tests {
         }
     }
 
-    #[derive(Debug, Clone)]
-    struct OmniscientRecord {
-        event: GameEvent,
-        settlements: usize,
-        roads: usize,
+    /// Records what the observers seeing the hand of `player_id` are told.
+    struct SeatObserver {
+        player_id: PlayerId,
+        events: Rc<RefCell<Vec<GameEvent>>>,
+    }
+
+    impl GameObserver for SeatObserver {
+        fn kind(&self) -> ObserverKind {
+            ObserverKind::Player(self.player_id)
+        }
+
+        fn on_event(&mut self, event: &GameEvent, _context: ObserverNotificationContext<'_>) {
+            self.events.borrow_mut().push(event.clone());
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    struct OmniscientRecord {
+        event: GameEvent,
+        settlements: usize,
+        roads: usize,
         player_resource_totals: Vec<u16>,
     }
 
@@ -1468,8 +2201,11 @@ mod tests {
         }
     }
 
+    /// Places its first `misplacements` roads away from their settlement,
+    /// then the first legal initial placement.
     struct LegalInitAgent {
         id: PlayerId,
+        misplacements: u64,
     }
 
     impl PlayerNotification for LegalInitAgent {}
@@ -1479,37 +2215,61 @@ mod tests {
             self.id
         }
 
-        fn init_stage_action(&mut self, context: PlayerDecisionContext<'_>) -> InitStageAction {
-            let (establishment, road) = legal::legal_initial_placements(&context)
-                .into_iter()
-                .next()
+        fn init_stage_action(
+            &mut self,
+            context: PlayerDecisionContext<'_>,
+        ) -> Result<InitStageAction, AgentError> {
+            let placements = legal::legal_initial_placements(&context);
+            let (establishment, road) = placements
+                .first()
+                .copied()
                 .expect("default board should have legal initial placements");
-            InitStageAction {
+            if self.misplacements > 0 {
+                self.misplacements -= 1;
+                let (_, far_road) = placements.last().copied().unwrap();
+                return Ok(InitStageAction {
+                    establishment_position: establishment.pos,
+                    road: far_road,
+                });
+            }
+            Ok(InitStageAction {
                 establishment_position: establishment.pos,
                 road,
-            }
+            })
         }
 
-        fn init_action(&mut self, _context: PlayerDecisionContext<'_>) -> InitAction {
+        fn init_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<InitAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
-        fn after_dice_action(&mut self, _context: PlayerDecisionContext<'_>) -> PostDiceAction {
+        fn after_dice_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDiceAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
         fn after_dev_card_action(
             &mut self,
             _context: PlayerDecisionContext<'_>,
-        ) -> PostDevCardAction {
+        ) -> Result<PostDevCardAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
-        fn regular_action(&mut self, _context: PlayerDecisionContext<'_>) -> RegularAction {
+        fn regular_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<RegularAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
-        fn move_robbers(&mut self, _context: PlayerDecisionContext<'_>) -> MoveRobbersAction {
+        fn move_robbers(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<MoveRobbersAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
@@ -1517,19 +2277,228 @@ mod tests {
             &mut self,
             _context: PlayerDecisionContext<'_>,
             _robber_pos: Hex,
-        ) -> ChoosePlayerToRobAction {
+        ) -> Result<ChoosePlayerToRobAction, AgentError> {
             unreachable!("not used by initialization test")
         }
 
-        fn answer_trade(&mut self, _context: PlayerDecisionContext<'_>) -> TradeAnswer {
+        fn answer_trade(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<TradeAnswer, AgentError> {
             unreachable!("not used by initialization test")
         }
 
-        fn drop_half(&mut self, _context: PlayerDecisionContext<'_>) -> DropHalfAction {
+        fn drop_half(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<DropHalfAction, AgentError> {
             unreachable!("not used by initialization test")
         }
     }
 
+    /// Fails to decide on its first `failures` turns, then rolls and ends
+    /// them.
+    struct FlakyAgent {
+        id: PlayerId,
+        failures: u32,
+    }
+
+    impl PlayerNotification for FlakyAgent {}
+
+    impl PlayerRuntime for FlakyAgent {
+        fn player_id(&self) -> PlayerId {
+            self.id
+        }
+
+        fn init_stage_action(
+            &mut self,
+            context: PlayerDecisionContext<'_>,
+        ) -> Result<InitStageAction, AgentError> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(AgentError::Disconnected("terminal closed".to_owned()));
+            }
+            let (establishment, road) = legal::legal_initial_placements(&context)[0];
+            Ok(InitStageAction {
+                establishment_position: establishment.pos,
+                road,
+            })
+        }
+
+        fn init_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<InitAction, AgentError> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err(AgentError::Disconnected("terminal closed".to_owned()));
+            }
+            Ok(InitAction::RollDice)
+        }
+
+        fn after_dice_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDiceAction, AgentError> {
+            Ok(PostDiceAction::RegularAction(RegularAction::EndMove))
+        }
+
+        fn after_dev_card_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDevCardAction, AgentError> {
+            Ok(PostDevCardAction::RollDice)
+        }
+
+        fn regular_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<RegularAction, AgentError> {
+            Ok(RegularAction::EndMove)
+        }
+
+        fn move_robbers(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<MoveRobbersAction, AgentError> {
+            unreachable!("fixed dice never rolls seven")
+        }
+
+        fn choose_player_to_rob(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+            _robber_pos: Hex,
+        ) -> Result<ChoosePlayerToRobAction, AgentError> {
+            unreachable!("fixed dice never rolls seven")
+        }
+
+        fn answer_trade(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<TradeAnswer, AgentError> {
+            Ok(TradeAnswer::Decline)
+        }
+
+        fn drop_half(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<DropHalfAction, AgentError> {
+            unreachable!("fixed dice never rolls seven")
+        }
+    }
+
+    /// Walks away from its first decision and gives up once its clock ran
+    /// out, like remote agents do.
+    struct AwayAgent {
+        id: PlayerId,
+    }
+
+    impl PlayerNotification for AwayAgent {}
+
+    impl PlayerRuntime for AwayAgent {
+        fn player_id(&self) -> PlayerId {
+            self.id
+        }
+
+        fn init_stage_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<InitStageAction, AgentError> {
+            unreachable!("the game starts after setup")
+        }
+
+        fn init_action(
+            &mut self,
+            context: PlayerDecisionContext<'_>,
+        ) -> Result<InitAction, AgentError> {
+            std::thread::sleep(context.clock().expect("the game is timed"));
+            Err(AgentError::TimedOut)
+        }
+
+        fn after_dice_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDiceAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn after_dev_card_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<PostDevCardAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn regular_action(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<RegularAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn move_robbers(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<MoveRobbersAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn choose_player_to_rob(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+            _robber_pos: Hex,
+        ) -> Result<ChoosePlayerToRobAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn answer_trade(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<TradeAnswer, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+
+        fn drop_half(
+            &mut self,
+            _context: PlayerDecisionContext<'_>,
+        ) -> Result<DropHalfAction, AgentError> {
+            unreachable!("the autopilot plays on")
+        }
+    }
+
+    fn flaky_agents(first_failures: u32) -> Vec<Box<dyn crate::agent::Agent>> {
+        (0..4)
+            .map(|id| {
+                Box::new(FlakyAgent {
+                    id,
+                    failures: if id == 0 { first_failures } else { 0 },
+                }) as Box<dyn crate::agent::Agent>
+            })
+            .collect()
+    }
+
+    fn run_flaky(controller: &mut GameController, agent_errors: AgentErrorPolicy) -> GameResult {
+        controller.run_with_options(
+            &mut FixedDice(DiceVal::try_from(8).unwrap()),
+            RunOptions {
+                max_turns: Some(4),
+                agent_errors,
+                ..RunOptions::default()
+            },
+        )
+    }
+
+    fn run_timed(controller: &mut GameController, time_control: TimeControl) -> GameResult {
+        controller.run_with_options(
+            &mut FixedDice(DiceVal::try_from(8).unwrap()),
+            RunOptions {
+                max_turns: Some(4),
+                time_control: Some(time_control),
+                ..RunOptions::default()
+            },
+        )
+    }
+
     fn invalid_agents(
         first_invalid_actions_before_end: Option<u64>,
     ) -> Vec<Box<dyn crate::agent::Agent>> {
@@ -1547,10 +2516,42 @@ mod tests {
             .collect()
     }
 
+    fn misplacing_agents(first_misplacements: u64) -> Vec<Box<dyn crate::agent::Agent>> {
+        (0..4)
+            .map(|id| {
+                Box::new(LegalInitAgent {
+                    id,
+                    misplacements: if id == 0 { first_misplacements } else { 0 },
+                }) as Box<dyn crate::agent::Agent>
+            })
+            .collect()
+    }
+
+    fn init_misplacing(
+        first_misplacements: u64,
+        observers: &mut [Box<dyn GameObserver>],
+    ) -> Result<GameState, GameResult> {
+        GameController::init_with_options(
+            GameInitializationState::default(),
+            &mut misplacing_agents(first_misplacements),
+            observers,
+            RunOptions {
+                max_invalid_actions: Some(3),
+                ..RunOptions::default()
+            },
+            None,
+        )
+    }
+
     #[test]
     fn init_with_observers_reports_initial_state_and_placements() {
         let mut agents = (0..4)
-            .map(|id| Box::new(LegalInitAgent { id }) as Box<dyn crate::agent::Agent>)
+            .map(|id| {
+                Box::new(LegalInitAgent {
+                    id,
+                    misplacements: 0,
+                }) as Box<dyn crate::agent::Agent>
+            })
             .collect::<Vec<_>>();
         let events = Rc::new(RefCell::new(Vec::new()));
         let mut observers = vec![Box::new(RecordingObserver {
@@ -1561,7 +2562,8 @@ mod tests {
             GameInitializationState::default(),
             &mut agents,
             &mut observers,
-        );
+        )
+        .unwrap();
         let events = events.borrow();
 
         assert!(matches!(events.first(), Some(GameEvent::GameStarted)));
@@ -1578,7 +2580,12 @@ mod tests {
     #[test]
     fn omniscient_observer_receives_full_state_updates_during_initialization() {
         let mut agents = (0..4)
-            .map(|id| Box::new(LegalInitAgent { id }) as Box<dyn crate::agent::Agent>)
+            .map(|id| {
+                Box::new(LegalInitAgent {
+                    id,
+                    misplacements: 0,
+                }) as Box<dyn crate::agent::Agent>
+            })
             .collect::<Vec<_>>();
         let records = Rc::new(RefCell::new(Vec::new()));
         let mut observers = vec![Box::new(RecordingOmniscientObserver {
@@ -1589,7 +2596,8 @@ mod tests {
             GameInitializationState::default(),
             &mut agents,
             &mut observers,
-        );
+        )
+        .unwrap();
         let records = records.borrow();
 
         assert!(matches!(
@@ -1629,6 +2637,7 @@ mod tests {
             RunOptions {
                 max_turns: Some(1),
                 max_invalid_actions: Some(10),
+                ..RunOptions::default()
             },
         );
         let records = records.borrow();
@@ -1648,7 +2657,7 @@ mod tests {
     }
 
     #[test]
-    fn invalid_action_limit_interrupts_game() {
+    fn invalid_action_limit_forfeits_the_seat() {
         let state = GameInitializationState::default().finish();
         let mut controller = GameController::new(state, invalid_agents(None));
         let events = Rc::new(RefCell::new(Vec::new()));
@@ -1662,22 +2671,125 @@ mod tests {
             RunOptions {
                 max_turns: Some(10),
                 max_invalid_actions: Some(10),
+                ..RunOptions::default()
             },
         );
 
+        // nobody has any points, so the next player leads
         assert_eq!(
             result,
-            GameResult::Interrupted {
-                reason: "invalid action limit reached: 10".to_owned()
+            GameResult::Forfeited {
+                player_id: 0,
+                winner: 1
             }
         );
-        assert!(events.borrow().iter().any(|event| {
-            matches!(
-                event,
-                GameEvent::GameInterrupted { reason }
-                    if reason == "invalid action limit reached: 10"
-            )
+        let events = events.borrow();
+        assert!(
+            events
+                .iter()
+                .any(|event| matches!(event, GameEvent::PlayerForfeited { player_id: 0 }))
+        );
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::GameEnded { winner_id: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn stand_ins_take_over_the_seats_of_players_who_left() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, invalid_agents(None));
+        controller.set_stand_in(|id| {
+            Box::new(InvalidBuyDevCardAgent {
+                id,
+                invalid_actions_before_end: Some(0),
+            })
+        });
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+        let mut dice = FixedDice(DiceVal::try_from(8).unwrap());
+
+        let result = controller.run_with_options(
+            &mut dice,
+            RunOptions {
+                max_turns: Some(10),
+                max_invalid_actions: Some(10),
+                ..RunOptions::default()
+            },
+        );
+
+        assert_eq!(result, GameResult::LimitReached { turns: 10 });
+        let events = events.borrow();
+        let forfeited = events
+            .iter()
+            .position(|event| matches!(event, GameEvent::PlayerForfeited { player_id: 0 }))
+            .expect("player 0 should forfeit");
+        assert!(matches!(
+            events[forfeited + 1],
+            GameEvent::AgentReplaced { player_id: 0 }
+        ));
+    }
+
+    #[test]
+    fn resigning_ends_the_game_without_a_stand_in() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, invalid_agents(Some(0)));
+
+        let flow = controller.execute_regular_action(RegularAction::Resign);
+
+        assert!(matches!(
+            flow,
+            TurnFlow::Left {
+                player_id: 0,
+                forfeit: false
+            }
+        ));
+        assert_eq!(controller.leader_besides(0), 1);
+    }
+
+    #[test]
+    fn resigned_seats_are_played_on_by_the_stand_in() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, invalid_agents(Some(0)));
+        // the stand-in wants to buy a card it can't pay for before ending
+        controller.set_stand_in(|id| {
+            Box::new(InvalidBuyDevCardAgent {
+                id,
+                invalid_actions_before_end: Some(1),
+            })
+        });
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(SeatObserver {
+            player_id: 0,
+            events: events.clone(),
         }));
+
+        let flow = controller.execute_regular_action(RegularAction::Resign);
+        assert!(matches!(flow, TurnFlow::Continue));
+        let result = controller.run_with_options(
+            &mut FixedDice(DiceVal::try_from(8).unwrap()),
+            RunOptions {
+                max_turns: Some(1),
+                ..RunOptions::default()
+            },
+        );
+
+        assert_eq!(result, GameResult::LimitReached { turns: 1 });
+        let events = events.borrow();
+        let replaced = events
+            .iter()
+            .position(|event| matches!(event, GameEvent::AgentReplaced { player_id: 0 }))
+            .expect("the stand-in should take over seat 0");
+        assert!(events[replaced..].iter().any(|event| matches!(
+            event,
+            GameEvent::ActionRejected {
+                player_id: 0,
+                action: RejectedAction::Regular(RegularAction::BuyDevCard),
+                ..
+            }
+        )));
     }
 
     #[test]
@@ -1691,9 +2803,355 @@ mod tests {
             RunOptions {
                 max_turns: Some(1),
                 max_invalid_actions: Some(10),
+                ..RunOptions::default()
             },
         );
 
         assert_eq!(result, GameResult::LimitReached { turns: 1 });
     }
+
+    #[test]
+    fn rejections_are_told_to_the_offender_only() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, invalid_agents(Some(2)));
+        let seat_events = Rc::new(RefCell::new(Vec::new()));
+        let other_seat_events = Rc::new(RefCell::new(Vec::new()));
+        let spectator_events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(SeatObserver {
+            player_id: 0,
+            events: seat_events.clone(),
+        }));
+        controller.add_observer(Box::new(SeatObserver {
+            player_id: 1,
+            events: other_seat_events.clone(),
+        }));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: spectator_events.clone(),
+        }));
+
+        controller.run_with_options(
+            &mut FixedDice(DiceVal::try_from(8).unwrap()),
+            RunOptions {
+                max_turns: Some(1),
+                ..RunOptions::default()
+            },
+        );
+
+        let rejected = |event: &&GameEvent| matches!(event, GameEvent::ActionRejected { .. });
+        let seat_events = seat_events.borrow();
+        let rejections = seat_events.iter().filter(rejected).collect::<Vec<_>>();
+        assert_eq!(rejections.len(), 2);
+        assert!(rejections.iter().all(|event| matches!(
+            event,
+            GameEvent::ActionRejected {
+                player_id: 0,
+                action: RejectedAction::Regular(RegularAction::BuyDevCard),
+                reason: RejectionReason::AccountIsShort,
+            }
+        )));
+        assert_eq!(
+            other_seat_events.borrow().iter().filter(rejected).count(),
+            0
+        );
+        assert_eq!(spectator_events.borrow().iter().filter(rejected).count(), 0);
+    }
+
+    #[test]
+    fn agent_errors_interrupt_the_game_by_default() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, flaky_agents(u32::MAX));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+
+        let result = run_flaky(&mut controller, AgentErrorPolicy::default());
+
+        assert_eq!(
+            result,
+            GameResult::Interrupted {
+                reason: "Player#0 failed to decide: disconnected: terminal closed".to_owned()
+            }
+        );
+        assert!(matches!(
+            events.borrow().last(),
+            Some(GameEvent::GameInterrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn agent_errors_are_retried_up_to_the_attempts() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state.clone(), flaky_agents(2));
+        let result = run_flaky(&mut controller, AgentErrorPolicy::Retry { attempts: 2 });
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+
+        let mut controller = GameController::new(state, flaky_agents(2));
+        let result = run_flaky(&mut controller, AgentErrorPolicy::Retry { attempts: 1 });
+        assert!(matches!(result, GameResult::Interrupted { .. }));
+    }
+
+    #[test]
+    fn agent_errors_fall_back_to_the_stand_in() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state.clone(), flaky_agents(u32::MAX));
+        controller.set_stand_in(|id| Box::new(FlakyAgent { id, failures: 0 }));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+
+        let result = run_flaky(&mut controller, AgentErrorPolicy::Fallback);
+
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+        assert!(
+            events
+                .borrow()
+                .iter()
+                .any(|event| matches!(event, GameEvent::AgentReplaced { player_id: 0 }))
+        );
+
+        // without a stand-in there is nothing to fall back to
+        let mut controller = GameController::new(state, flaky_agents(u32::MAX));
+        let result = run_flaky(&mut controller, AgentErrorPolicy::Fallback);
+        assert!(matches!(result, GameResult::Interrupted { .. }));
+    }
+
+    fn init_flaky(
+        agents: &mut Vec<Box<dyn crate::agent::Agent>>,
+        agent_errors: AgentErrorPolicy,
+        stand_in: Option<&mut StandIn>,
+    ) -> Result<GameState, GameResult> {
+        GameController::init_with_options(
+            GameInitializationState::default(),
+            agents,
+            &mut [],
+            RunOptions {
+                agent_errors,
+                ..RunOptions::default()
+            },
+            stand_in,
+        )
+    }
+
+    #[test]
+    fn agent_errors_during_setup_are_retried() {
+        let mut agents = flaky_agents(2);
+
+        let state = init_flaky(&mut agents, AgentErrorPolicy::Retry { attempts: 2 }, None)
+            .expect("setup should succeed on the third attempt");
+
+        assert_eq!(state.builds[0].establishments.len(), 2);
+        assert!(matches!(
+            init_flaky(
+                &mut flaky_agents(3),
+                AgentErrorPolicy::Retry { attempts: 2 },
+                None
+            ),
+            Err(GameResult::Interrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn agent_errors_during_setup_interrupt_it_by_policy() {
+        let result = init_flaky(&mut flaky_agents(1), AgentErrorPolicy::Interrupt, None);
+
+        assert!(matches!(
+            result,
+            Err(GameResult::Interrupted { reason }) if reason.starts_with("Player#0 failed to decide")
+        ));
+    }
+
+    #[test]
+    fn agent_errors_during_setup_fall_back_to_the_stand_in() {
+        let mut agents = flaky_agents(u32::MAX);
+        let mut stand_in: StandIn = Box::new(|id| Box::new(FlakyAgent { id, failures: 0 }));
+
+        let state = init_flaky(&mut agents, AgentErrorPolicy::Fallback, Some(&mut stand_in))
+            .expect("the stand-in should place for seat 0");
+
+        assert_eq!(state.builds[0].establishments.len(), 2);
+        assert!(matches!(
+            init_flaky(
+                &mut flaky_agents(u32::MAX),
+                AgentErrorPolicy::Fallback,
+                None
+            ),
+            Err(GameResult::Interrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn running_out_of_time_forfeits_the_seat() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, flaky_agents(0));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+
+        // an empty bank runs out with the first decision
+        let result = run_timed(
+            &mut controller,
+            TimeControl {
+                bank: Duration::ZERO,
+                increment: Duration::ZERO,
+                on_timeout: TimeoutRule::Forfeit,
+            },
+        );
+
+        assert_eq!(
+            result,
+            GameResult::Forfeited {
+                player_id: 0,
+                winner: 1
+            }
+        );
+        assert!(
+            events
+                .borrow()
+                .iter()
+                .any(|event| matches!(event, GameEvent::PlayerOutOfTime { player_id: 0 }))
+        );
+    }
+
+    #[test]
+    fn stand_ins_end_the_turns_of_players_out_of_time() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state.clone(), flaky_agents(0));
+        controller.set_stand_in(|id| Box::new(FlakyAgent { id, failures: 0 }));
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+        let time_control = TimeControl {
+            bank: Duration::ZERO,
+            increment: Duration::ZERO,
+            on_timeout: TimeoutRule::EndTurn,
+        };
+
+        let result = run_timed(&mut controller, time_control);
+
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+        // without increments the stand-ins keep the seats
+        let out_of_time = events
+            .borrow()
+            .iter()
+            .filter(|event| matches!(event, GameEvent::PlayerOutOfTime { .. }))
+            .count();
+        assert_eq!(out_of_time, 4);
+
+        // without a stand-in a lazy autopilot ends the turns
+        let mut controller = GameController::new(state, flaky_agents(0));
+        let result = run_timed(&mut controller, time_control);
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+    }
+
+    #[test]
+    fn agents_giving_up_on_their_clock_run_out_of_time() {
+        let state = GameInitializationState::default().finish();
+        let mut agents = flaky_agents(0);
+        agents[0] = Box::new(AwayAgent { id: 0 });
+        let mut controller = GameController::new(state, agents);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        controller.add_observer(Box::new(RecordingObserver {
+            events: events.clone(),
+        }));
+
+        let result = run_timed(
+            &mut controller,
+            TimeControl {
+                bank: Duration::from_millis(20),
+                increment: Duration::ZERO,
+                on_timeout: TimeoutRule::EndTurn,
+            },
+        );
+
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+        assert!(controller.clocks[0].is_zero());
+        assert!(
+            events
+                .borrow()
+                .iter()
+                .any(|event| matches!(event, GameEvent::PlayerOutOfTime { player_id: 0 }))
+        );
+    }
+
+    #[test]
+    fn clocks_get_the_increment_after_every_turn() {
+        let state = GameInitializationState::default().finish();
+        let mut controller = GameController::new(state, flaky_agents(0));
+        let bank = Duration::from_secs(3600);
+
+        let result = run_timed(
+            &mut controller,
+            TimeControl {
+                bank,
+                increment: Duration::from_secs(1),
+                on_timeout: TimeoutRule::Forfeit,
+            },
+        );
+
+        assert_eq!(result, GameResult::LimitReached { turns: 4 });
+        assert!(controller.clocks.iter().all(|clock| *clock > bank));
+    }
+
+    #[test]
+    fn invalid_initial_placements_are_rejected_below_the_limit() {
+        let seat_events = Rc::new(RefCell::new(Vec::new()));
+        let spectator_events = Rc::new(RefCell::new(Vec::new()));
+        let mut observers = vec![
+            Box::new(SeatObserver {
+                player_id: 0,
+                events: seat_events.clone(),
+            }) as Box<dyn GameObserver>,
+            Box::new(RecordingObserver {
+                events: spectator_events.clone(),
+            }),
+        ];
+
+        let result = init_misplacing(2, &mut observers);
+
+        assert!(result.is_ok());
+        let rejections = seat_events
+            .borrow()
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameEvent::ActionRejected {
+                        player_id: 0,
+                        action: RejectedAction::InitialPlacement(_),
+                        reason: RejectionReason::InvalidPlacement,
+                    }
+                )
+            })
+            .count();
+        assert_eq!(rejections, 2);
+        assert!(
+            !spectator_events
+                .borrow()
+                .iter()
+                .any(|event| matches!(event, GameEvent::ActionRejected { .. }))
+        );
+    }
+
+    #[test]
+    fn invalid_initial_placements_interrupt_setup_at_the_limit() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut observers = vec![Box::new(RecordingObserver {
+            events: events.clone(),
+        }) as Box<dyn GameObserver>];
+
+        let result = init_misplacing(u64::MAX, &mut observers);
+
+        assert!(matches!(
+            result,
+            Err(GameResult::Interrupted { reason }) if reason.contains("invalid action limit (3)")
+        ));
+        assert!(matches!(
+            events.borrow().last(),
+            Some(GameEvent::GameInterrupted { .. })
+        ));
+    }
 }
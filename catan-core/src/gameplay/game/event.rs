@@ -1,16 +1,21 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    agent::action::RegularAction,
+    agent::action::{InitStageAction, RegularAction},
     gameplay::{
-        game::view::{
-            OmniscientGameView, PlayerNotificationContext, PrivatePlayerView, PublicGameView,
+        game::{
+            state::{BuildActionError, BuyDevCardError, DevCardUsageError},
+            view::{
+                OmniscientGameView, PlayerNotificationContext, PrivatePlayerView, PublicGameView,
+            },
         },
         primitives::{
-            build::{Build, Road},
+            PortKind,
+            bank::BankResourceExchangeError,
+            build::{Build, BuildingError, Road},
             dev_card::DevCardUsage,
             player::PlayerId,
-            resource::ResourceCollection,
+            resource::{Resource, ResourceCollection},
         },
     },
     math::dice::DiceVal,
@@ -32,6 +37,111 @@ pub struct GameEndPlayerStats {
     pub has_largest_army: bool,
 }
 
+/// An action the controller turned down.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum RejectedAction {
+    InitialPlacement(InitStageAction),
+    Regular(RegularAction),
+    DevCard(DevCardUsage),
+    Discard(ResourceCollection),
+    MoveRobber(Hex),
+    Rob(PlayerId),
+}
+
+/// Why the controller turned down an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectionReason {
+    /// The player lacks the resources for it.
+    AccountIsShort,
+    /// The bank lacks the resources or development cards for it.
+    BankIsShort,
+    /// The player has no pieces of that kind left.
+    OutOfPieces,
+    /// The piece can't be placed there.
+    InvalidPlacement,
+    /// The player has no such development card it may play.
+    CardNotInHand,
+    InvalidHex,
+    InvalidEdge,
+    /// That player can't be robbed there.
+    InvalidRobbery,
+    /// The trade needs a port the player doesn't have.
+    MissingPort(PortKind),
+    /// Trades between players aren't supported yet.
+    PlayerTradesUnsupported,
+    WrongDiscardCount {
+        required: u16,
+    },
+    /// The robber has to leave its hex.
+    RobberMustMove,
+}
+
+impl std::fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AccountIsShort => write!(f, "not enough resources"),
+            Self::BankIsShort => write!(f, "the bank ran short"),
+            Self::OutOfPieces => write!(f, "no pieces of that kind left"),
+            Self::InvalidPlacement => write!(f, "can't build there"),
+            Self::CardNotInHand => write!(f, "no such development card to play"),
+            Self::InvalidHex => write!(f, "invalid hex"),
+            Self::InvalidEdge => write!(f, "invalid road edge"),
+            Self::InvalidRobbery => write!(f, "that player can't be robbed there"),
+            Self::MissingPort(port) => write!(f, "missing port {port:?}"),
+            Self::PlayerTradesUnsupported => write!(f, "trades between players aren't supported"),
+            Self::WrongDiscardCount { required } => {
+                write!(f, "must discard exactly {required} cards")
+            }
+            Self::RobberMustMove => write!(f, "the robber has to move to another hex"),
+        }
+    }
+}
+
+impl From<&BuildActionError> for RejectionReason {
+    fn from(err: &BuildActionError) -> Self {
+        match err {
+            BuildActionError::AccountIsShort { .. } => Self::AccountIsShort,
+            BuildActionError::OutOfPieces
+            | BuildActionError::InvalidPlacement(
+                BuildingError::RoadLimit()
+                | BuildingError::SettlementLimit()
+                | BuildingError::CityLimit(),
+            ) => Self::OutOfPieces,
+            BuildActionError::InvalidPlacement(_) => Self::InvalidPlacement,
+        }
+    }
+}
+
+impl From<&BuyDevCardError> for RejectionReason {
+    fn from(err: &BuyDevCardError) -> Self {
+        match err {
+            BuyDevCardError::AccountIsShort { .. } => Self::AccountIsShort,
+            BuyDevCardError::BankIsShort => Self::BankIsShort,
+        }
+    }
+}
+
+impl From<DevCardUsageError> for RejectionReason {
+    fn from(err: DevCardUsageError) -> Self {
+        match err {
+            DevCardUsageError::CardNotFoundInInventory => Self::CardNotInHand,
+            DevCardUsageError::InvalidHex => Self::InvalidHex,
+            DevCardUsageError::InvalidEdge => Self::InvalidEdge,
+            DevCardUsageError::InvalidRobbery => Self::InvalidRobbery,
+            DevCardUsageError::BankIsShort => Self::BankIsShort,
+        }
+    }
+}
+
+impl From<&BankResourceExchangeError> for RejectionReason {
+    fn from(err: &BankResourceExchangeError) -> Self {
+        match err {
+            BankResourceExchangeError::BankIsShort => Self::BankIsShort,
+            BankResourceExchangeError::AccountIsShort { .. } => Self::AccountIsShort,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ObserverKind {
     Spectator,
@@ -85,6 +195,15 @@ pub enum GameEvent {
         value: DiceVal,
     },
     ResourcesDistributed,
+    /// The bank couldn't pay `owed` of `resource` to the `claimants` of a
+    /// harvest with only `in_bank` left. A single claimant got what was
+    /// left, several got nothing.
+    HarvestShortfall {
+        resource: Resource,
+        owed: u16,
+        in_bank: u16,
+        claimants: Vec<PlayerId>,
+    },
     DevCardBought {
         player_id: PlayerId,
     },
@@ -108,10 +227,28 @@ pub enum GameEvent {
         hex: Hex,
         robbed_id: Option<PlayerId>,
     },
+    /// The player conceded.
+    PlayerResigned {
+        player_id: PlayerId,
+    },
+    /// The player ran out of invalid actions and lost its seat.
+    PlayerForfeited {
+        player_id: PlayerId,
+    },
+    /// The player's clock ran out.
+    PlayerOutOfTime {
+        player_id: PlayerId,
+    },
+    /// Someone else plays the seat from now on.
+    AgentReplaced {
+        player_id: PlayerId,
+    },
+    /// The controller turned down an action of the player, who is asked
+    /// again. Only the player and those seeing its hand are told.
     ActionRejected {
         player_id: PlayerId,
-        action: RegularAction,
-        reason: String,
+        action: RejectedAction,
+        reason: RejectionReason,
     },
     GameEnded {
         winner_id: PlayerId,
@@ -31,6 +31,23 @@ impl GameIndex {
         }
     }
 
+    /// Brings the entries that depend on `player_id`'s builds and cards back
+    /// in step with `state` after that player alone changed it, without
+    /// rebuilding the others.
+    pub fn refresh_player(&mut self, state: &GameState, player_id: PlayerId) {
+        let builds = &state.builds[player_id];
+
+        self.all_builds[player_id] = BuildCollection {
+            establishments: builds.establishments.iter().copied().collect(),
+            roads: builds.roads.iter().collect(),
+        };
+        self.longest_road_lengths[player_id] = builds.roads.find_longest_trail_length() as u16;
+        self.longest_road_owner = state.builds.longest_road();
+        self.largest_army_owner = state.players.best_army();
+        self.ports_aquired[player_id] =
+            algorithm::get_player_ports_aquired(&state.board.index().ports_intersection, builds);
+    }
+
     fn get_ports_aquired(state: &GameState) -> Vec<BTreeSet<PortKind>> {
         algorithm::get_ports_aquired(state.board.index().ports_intersection, &state.builds)
     }
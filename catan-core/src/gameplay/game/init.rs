@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use rand::{SeedableRng, rngs::SmallRng};
+
 use crate::gameplay::{
     field::state::{BoardLayout, BoardState, FieldBuildParam},
     game::state::GameState,
@@ -19,6 +21,9 @@ pub struct GameInitializationState {
     pub bank: Bank,
     pub players: PlayerDataContainer,
     pub builds: BoardBuildData,
+    /// Source of every random outcome of the game: the development card
+    /// deck order here, stolen cards once it is [`finish`](Self::finish)ed.
+    pub rng: SmallRng,
 }
 
 impl Default for GameInitializationState {
@@ -29,9 +34,19 @@ impl Default for GameInitializationState {
 
 impl GameInitializationState {
     pub fn new(field_build_param: FieldBuildParam) -> Self {
+        Self::with_rng(field_build_param, SmallRng::from_rng(&mut rand::rng()))
+    }
+
+    /// Same as [`new`](Self::new), but every random outcome of the game
+    /// is drawn from an RNG seeded with `seed`.
+    pub fn with_seed(field_build_param: FieldBuildParam, seed: u64) -> Self {
+        Self::with_rng(field_build_param, SmallRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(field_build_param: FieldBuildParam, mut rng: SmallRng) -> Self {
         let board = Arc::new(BoardLayout::new(field_build_param));
         let mut bank = Bank::default();
-        bank.shuffle_dev_cards();
+        bank.shuffle_dev_cards(&mut rng);
         Self {
             turn: GameTurn::new(board.n_players as u8),
             players: PlayerDataContainer::new(board.n_players),
@@ -39,17 +54,20 @@ impl GameInitializationState {
             board_state: BoardState::new(&board),
             board,
             bank,
+            rng,
         }
     }
 
     pub fn finish(self) -> GameState {
-        GameState {
-            board: self.board,
-            board_state: self.board_state,
-            turn: self.turn.into_regular(),
-            bank: self.bank,
-            players: self.players,
-            builds: self.builds,
-        }
+        let mut state = GameState::new(
+            self.board,
+            self.board_state,
+            self.turn.into_regular(),
+            self.bank,
+            self.players,
+            self.builds,
+        );
+        state.rng = self.rng;
+        state
     }
 }
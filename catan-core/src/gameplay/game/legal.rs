@@ -5,9 +5,10 @@ use serde::{Deserialize, Serialize};
 use crate::{
     agent::action::RegularAction,
     gameplay::{
-        game::view::{PlayerDecisionContext, PublicPlayerResources},
+        game::view::{PlayerDecisionContext, PublicBankResources, PublicPlayerResources},
         primitives::{
             PortKind,
+            bank::DeckFullnessLevel,
             build::{BoardBuildData, Build, Establishment, EstablishmentType, Road},
             dev_card::{DevCardUsage, UsableDevCard},
             player::PlayerId,
@@ -317,12 +318,20 @@ fn resource_trades_at_rate(
         .flat_map(|give| {
             Resource::iter()
                 .into_iter()
-                .filter(move |take| *take != give)
+                .filter(move |take| *take != give && bank_has_any(context, *take))
                 .map(move |take| BankTrade { give, take, kind })
         })
         .collect()
 }
 
+/// Even the approximate view of the bank tells an empty deck apart.
+fn bank_has_any(context: &PlayerDecisionContext<'_>, resource: Resource) -> bool {
+    match &context.public.bank.resources {
+        PublicBankResources::Exact(resources) => resources[resource] > 0,
+        PublicBankResources::Approx(levels) => levels[resource] != DeckFullnessLevel::Empty,
+    }
+}
+
 pub fn legal_trades(context: &PlayerDecisionContext<'_>) -> impl IntoIterator<Item = BankTrade> {
     legal_bank_trades(context)
 }
@@ -740,6 +749,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn bank_trades_exclude_resources_the_bank_ran_out_of() {
+        let mut state = GameInitializationState::default().finish();
+        state
+            .transfer_from_bank(
+                ResourceCollection {
+                    brick: 4,
+                    ..ResourceCollection::ZERO
+                },
+                0,
+            )
+            .expect("bank should fund test resources");
+        let ore = state.bank.resources.ore;
+        state
+            .transfer_from_bank((Resource::Ore, ore).into(), 1)
+            .expect("bank should hand out all its ore");
+
+        let options = context_bank_trades(&state, 0);
+
+        assert!(!options.is_empty());
+        assert!(!options.iter().any(|trade| trade.take == Resource::Ore));
+    }
+
     #[test]
     fn bank_trades_include_universal_and_specific_ports() {
         let universal = state_with_port_and_resources(
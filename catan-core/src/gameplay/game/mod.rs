@@ -5,4 +5,6 @@ pub mod init;
 pub mod legal;
 pub mod query;
 pub mod state;
+pub mod undo;
 pub mod view;
+pub mod zobrist;
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
+use rand::{SeedableRng, rngs::SmallRng};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     gameplay::{
         field::state::{BoardLayout, BoardState},
+        game::zobrist,
         primitives::{
             bank::{Bank, BankResourceExchangeError, PlayerResourceExchangeError},
             build::{BoardBuildData, Build, BuildingError, EstablishmentType, Road},
@@ -12,7 +14,7 @@ use crate::{
             player::{PlayerDataContainer, PlayerId},
             resource::{HasCost, Resource, ResourceCollection, ResourceCollectionError},
             trade::BankTrade,
-            turn::GameTurn,
+            turn::{GameTurn, TurnPhase},
         },
     },
     topology::Hex,
@@ -20,15 +22,59 @@ use crate::{
 
 use crate::topology::Path;
 
+/// Full game position.
+///
+/// The state keeps a Zobrist hash of its position (see [`GameState::zobrist_hash`])
+/// which the mutation methods below update incrementally. Mutating the public
+/// fields directly bypasses it; call [`GameState::rehash`] afterwards.
+///
+/// Chance inside the rules (which card a robber steals) is drawn from the
+/// state's own RNG, so a state built from a seeded
+/// [`GameInitializationState::with_seed`](super::init::GameInitializationState::with_seed)
+/// replays the same way.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawGameState")]
 pub struct GameState {
     #[serde(with = "arc_board_layout")]
     pub board: Arc<BoardLayout>,
     pub board_state: BoardState,
     pub turn: GameTurn,
+    pub phase: TurnPhase,
     pub bank: Bank,
     pub players: PlayerDataContainer,
     pub builds: BoardBuildData,
+    #[serde(skip)]
+    pub(super) zobrist: u64,
+    #[serde(skip)]
+    pub(super) rng: SmallRng,
+}
+
+#[derive(Deserialize)]
+struct RawGameState {
+    #[serde(with = "arc_board_layout")]
+    board: Arc<BoardLayout>,
+    board_state: BoardState,
+    turn: GameTurn,
+    #[serde(default)]
+    phase: TurnPhase,
+    bank: Bank,
+    players: PlayerDataContainer,
+    builds: BoardBuildData,
+}
+
+impl From<RawGameState> for GameState {
+    fn from(raw: RawGameState) -> Self {
+        let mut state = Self::new(
+            raw.board,
+            raw.board_state,
+            raw.turn,
+            raw.bank,
+            raw.players,
+            raw.builds,
+        );
+        state.set_phase(raw.phase);
+        state
+    }
 }
 
 mod arc_board_layout {
@@ -76,6 +122,74 @@ pub enum BuyDevCardError {
 }
 
 impl GameState {
+    pub fn new(
+        board: Arc<BoardLayout>,
+        board_state: BoardState,
+        turn: GameTurn,
+        bank: Bank,
+        players: PlayerDataContainer,
+        builds: BoardBuildData,
+    ) -> Self {
+        let mut state = Self {
+            board,
+            board_state,
+            turn,
+            phase: TurnPhase::default(),
+            bank,
+            players,
+            builds,
+            zobrist: 0,
+            rng: SmallRng::from_rng(&mut rand::rng()),
+        };
+        state.rehash();
+        state
+    }
+
+    /// 64-bit Zobrist hash of the position: builds, robber position, players'
+    /// hands and development cards, longest road and largest army holders,
+    /// turn index, round and phase.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Recomputes the hash from scratch.
+    pub fn rehash(&mut self) {
+        self.zobrist = zobrist::full_hash(self);
+    }
+
+    /// Runs `mutate`, updating the hash of the `ids` players' cards.
+    fn update_players<R>(&mut self, ids: &[PlayerId], mutate: impl FnOnce(&mut Self) -> R) -> R {
+        let before = zobrist::players(&self.players, ids);
+        let result = mutate(self);
+        self.zobrist ^= before ^ zobrist::players(&self.players, ids);
+        result
+    }
+
+    /// Replaces build data changed by `player_id` builds.
+    fn set_builds(&mut self, player_id: PlayerId, builds: BoardBuildData) {
+        self.zobrist ^=
+            zobrist::builds(&self.builds, player_id) ^ zobrist::builds(&builds, player_id);
+        self.builds = builds;
+    }
+
+    pub fn next_turn(&mut self) {
+        self.zobrist ^= zobrist::turn(&self.turn);
+        self.turn.next();
+        self.zobrist ^= zobrist::turn(&self.turn);
+        self.set_phase(TurnPhase::BeforeRoll);
+    }
+
+    pub fn set_phase(&mut self, phase: TurnPhase) {
+        self.zobrist ^= zobrist::phase(self.phase) ^ zobrist::phase(phase);
+        self.phase = phase;
+    }
+
+    pub fn reset_dev_card_queue(&mut self, player_id: PlayerId) {
+        self.update_players(&[player_id], |state| {
+            state.players.get_mut(player_id).dev_cards_reset_queue()
+        });
+    }
+
     pub fn bank_resource_exchange(
         &mut self,
         player_id: PlayerId,
@@ -93,23 +207,26 @@ impl GameState {
             return Err(BankResourceExchangeError::BankIsShort);
         }
 
-        self.players
-            .get_mut(player_id)
-            .resources()
-            .subtract_in_place(&to_bank)
-            .map_err(|_| BankResourceExchangeError::AccountIsShort {
-                account: player_id,
-                short: self
-                    .players
-                    .get_mut(player_id)
-                    .resources()
-                    .missing(&to_bank),
-            })?;
-        self.bank.deposit(to_bank);
-        self.bank.withdraw(from_bank)?;
-        *self.players.get_mut(player_id).resources() += &from_bank;
-
-        Ok(())
+        self.update_players(&[player_id], |state| {
+            state
+                .players
+                .get_mut(player_id)
+                .resources()
+                .subtract_in_place(&to_bank)
+                .map_err(|_| BankResourceExchangeError::AccountIsShort {
+                    account: player_id,
+                    short: state
+                        .players
+                        .get_mut(player_id)
+                        .resources()
+                        .missing(&to_bank),
+                })?;
+            state.bank.deposit(to_bank);
+            state.bank.withdraw(from_bank)?;
+            *state.players.get_mut(player_id).resources() += &from_bank;
+
+            Ok(())
+        })
     }
 
     pub fn trade_with_bank(
@@ -141,10 +258,12 @@ impl GameState {
             return Err(BuildActionError::AccountIsShort { id: player_id });
         }
 
-        let mut builds = self.builds.clone();
-        builds
+        // `try_build` leaves the builds untouched on failure
+        let builds_hash = zobrist::builds(&self.builds, player_id);
+        self.builds
             .try_build(player_id, build)
             .map_err(BuildActionError::InvalidPlacement)?;
+        self.zobrist ^= builds_hash ^ zobrist::builds(&self.builds, player_id);
 
         self.transfer_to_bank(cost, player_id)
             .map_err(|err| match err {
@@ -154,7 +273,6 @@ impl GameState {
                     short: _,
                 } => BuildActionError::AccountIsShort { id },
             })?;
-        self.builds = builds;
 
         Ok(())
     }
@@ -187,7 +305,9 @@ impl GameState {
             .bank
             .draw_dev_card()
             .ok_or(BuyDevCardError::BankIsShort)?;
-        self.players.get_mut(player_id).dev_cards_add(card);
+        self.update_players(&[player_id], |state| {
+            state.players.get_mut(player_id).dev_cards_add(card)
+        });
 
         Ok(())
     }
@@ -197,11 +317,13 @@ impl GameState {
         resources: ResourceCollection,
         player_id: PlayerId,
     ) -> Result<(), BankResourceExchangeError> {
-        ResourceCollection::transfer(
-            self.players.get_mut(player_id).resources(),
-            &mut self.bank.resources,
-            resources,
-        )
+        self.update_players(&[player_id], |state| {
+            ResourceCollection::transfer(
+                state.players.get_mut(player_id).resources(),
+                &mut state.bank.resources,
+                resources,
+            )
+        })
         .map_err(|err| BankResourceExchangeError::AccountIsShort {
             account: player_id,
             short: match err {
@@ -219,11 +341,13 @@ impl GameState {
         resources: ResourceCollection,
         player_id: PlayerId,
     ) -> Result<(), BankResourceExchangeError> {
-        ResourceCollection::transfer(
-            &mut self.bank.resources,
-            self.players.get_mut(player_id).resources(),
-            resources,
-        )
+        self.update_players(&[player_id], |state| {
+            ResourceCollection::transfer(
+                &mut state.bank.resources,
+                state.players.get_mut(player_id).resources(),
+                resources,
+            )
+        })
         .map_err(|_| BankResourceExchangeError::BankIsShort)
     }
 
@@ -244,10 +368,11 @@ impl GameState {
                 .ok_or(PlayerResourceExchangeError::AccountIsShort { id: from_id });
         }
 
-        let (from, to) = self.players.get_mut_both_raw((from_id, to_id));
-
-        ResourceCollection::transfer(&mut from.resources, &mut to.resources, resources)
-            .map_err(|_| PlayerResourceExchangeError::AccountIsShort { id: from_id })
+        self.update_players(&[from_id, to_id], |state| {
+            let (from, to) = state.players.get_mut_both_raw((from_id, to_id));
+            ResourceCollection::transfer(&mut from.resources, &mut to.resources, resources)
+        })
+        .map_err(|_| PlayerResourceExchangeError::AccountIsShort { id: from_id })
     }
 
     pub fn players_resource_exchange(
@@ -300,6 +425,7 @@ impl GameState {
             return Err(DevCardUsageError::InvalidRobbery);
         }
 
+        self.zobrist ^= zobrist::robber(self.board_state.robber_pos) ^ zobrist::robber(rob_hex);
         self.board_state.robber_pos = rob_hex;
         if let Some(robbed_id) = robbed_id {
             self.steal(robbed_id, robber_id);
@@ -349,9 +475,12 @@ impl GameState {
         }
 
         if self
-            .players
-            .get_mut(user)
-            .dev_cards_move_to_used(usage.card_kind())
+            .update_players(&[user], |state| {
+                state
+                    .players
+                    .get_mut(user)
+                    .dev_cards_move_to_used(usage.card_kind())
+            })
             .is_err()
         {
             return Err(DevCardUsageError::CardNotFoundInInventory);
@@ -394,7 +523,7 @@ impl GameState {
     fn steal(&mut self, robbed_id: PlayerId, robber_id: PlayerId) {
         log::trace!("steal");
         let robbed_account = self.players.get(robbed_id).resources();
-        let stolen = robbed_account.peek_random();
+        let stolen = robbed_account.peek_random(&mut self.rng);
         log::trace!("peek random success");
         if let Some(card) = stolen {
             if let Err(e) = self.players_resource_transfer(robbed_id, robber_id, card.into()) {
@@ -453,7 +582,8 @@ impl GameState {
         poses: [Path; 2],
         user: PlayerId,
     ) -> Result<(), DevCardUsageError> {
-        self.builds = self.validated_roadbuild_state(poses, user)?;
+        let builds = self.validated_roadbuild_state(poses, user)?;
+        self.set_builds(user, builds);
 
         Ok(())
     }
@@ -0,0 +1,320 @@
+//! Make/unmake support for search.
+//!
+//! [`GameState::apply_reversible`] applies a regular action in place and
+//! returns an [`Undo`] record; [`GameState::undo`] takes the action back. Undo
+//! records are small and hold no heap data, so search can walk deep trees on a
+//! single state instead of cloning it per candidate action.
+
+use crate::{
+    agent::action::RegularAction,
+    constants::costs,
+    gameplay::{
+        game::state::{BuildActionError, BuyDevCardError, GameState},
+        primitives::{
+            bank::BankResourceExchangeError,
+            build::Build,
+            dev_card::{DevCardData, DevCardKind},
+            player::PlayerId,
+            resource::{HasCost, ResourceCollection},
+            trade::BankTrade,
+            turn::{GameTurn, TurnPhase},
+        },
+    },
+};
+
+#[derive(Debug)]
+pub enum ReversibleActionError {
+    Build(BuildActionError),
+    BankTrade(BankResourceExchangeError),
+    BuyDevCard(BuyDevCardError),
+    /// Trade offers need answers from other players and can't be applied to
+    /// the state alone.
+    NotReversible,
+}
+
+/// Record of an action applied with [`GameState::apply_reversible`].
+///
+/// Undo records must be taken back in the reverse order of application.
+#[must_use = "an applied action can only be taken back with its undo record"]
+#[derive(Debug, Clone)]
+pub struct Undo {
+    player_id: PlayerId,
+    zobrist: u64,
+    kind: UndoKind,
+}
+
+#[derive(Debug, Clone)]
+enum UndoKind {
+    Build {
+        build: Build,
+        longest_road: Option<PlayerId>,
+    },
+    TradeWithBank(BankTrade),
+    BuyDevCard(DevCardKind),
+    EndMove {
+        turn: GameTurn,
+        phase: TurnPhase,
+        next_dev_cards: DevCardData,
+    },
+}
+
+impl GameState {
+    /// Applies `action` of `player_id` the same way the controller does and
+    /// returns the record needed to take it back. On error the state is left
+    /// untouched.
+    pub fn apply_reversible(
+        &mut self,
+        player_id: PlayerId,
+        action: RegularAction,
+    ) -> Result<Undo, ReversibleActionError> {
+        let zobrist = self.zobrist_hash();
+        let kind = match action {
+            RegularAction::Build(build) => {
+                let longest_road = self.builds.longest_road();
+                self.build(player_id, build)
+                    .map_err(ReversibleActionError::Build)?;
+                UndoKind::Build {
+                    build,
+                    longest_road,
+                }
+            }
+            RegularAction::TradeWithBank(trade) => {
+                self.trade_with_bank(player_id, trade)
+                    .map_err(ReversibleActionError::BankTrade)?;
+                UndoKind::TradeWithBank(trade)
+            }
+            RegularAction::BuyDevCard => {
+                let card = self.bank.dev_cards.last().copied();
+                self.buy_dev_card(player_id)
+                    .map_err(ReversibleActionError::BuyDevCard)?;
+                UndoKind::BuyDevCard(card.expect("bought card was on top of the deck"))
+            }
+            RegularAction::EndMove => {
+                let turn = self.turn.clone();
+                let phase = self.phase;
+                self.next_turn();
+                let next = self.turn.get_turn_index();
+                let next_dev_cards = self.players.get(next).dev_cards().clone();
+                self.reset_dev_card_queue(next);
+                UndoKind::EndMove {
+                    turn,
+                    phase,
+                    next_dev_cards,
+                }
+            }
+            RegularAction::OfferPublicTrade(_)
+            | RegularAction::OfferPersonalTrade(_)
+            | RegularAction::Resign => {
+                return Err(ReversibleActionError::NotReversible);
+            }
+        };
+
+        Ok(Undo {
+            player_id,
+            zobrist,
+            kind,
+        })
+    }
+
+    /// Takes back the action recorded in `undo`.
+    pub fn undo(&mut self, undo: Undo) {
+        let Undo {
+            player_id,
+            zobrist,
+            kind,
+        } = undo;
+
+        match kind {
+            UndoKind::Build {
+                build,
+                longest_road,
+            } => {
+                self.builds.undo_build(player_id, build, longest_road);
+                self.refund(player_id, build.cost());
+            }
+            UndoKind::TradeWithBank(trade) => {
+                let mut player = self.players.get_mut(player_id);
+                let resources = player.resources();
+                *resources += &trade.to_bank();
+                resources
+                    .subtract_in_place(&trade.from_bank())
+                    .expect("player owns the resources bought from the bank");
+                self.bank.deposit(trade.from_bank());
+                self.bank
+                    .withdraw(trade.to_bank())
+                    .expect("bank owns the resources sold to it");
+            }
+            UndoKind::BuyDevCard(card) => {
+                self.players.get_mut(player_id).dev_cards_mut().remove(card);
+                self.bank.dev_cards.push(card);
+                self.refund(player_id, costs::DEV_CARD);
+            }
+            UndoKind::EndMove {
+                turn,
+                phase,
+                next_dev_cards,
+            } => {
+                let next = self.turn.get_turn_index();
+                *self.players.get_mut(next).dev_cards_mut() = next_dev_cards;
+                self.turn = turn;
+                self.phase = phase;
+            }
+        }
+
+        self.zobrist = zobrist;
+    }
+
+    fn refund(&mut self, player_id: PlayerId, cost: ResourceCollection) {
+        self.bank
+            .withdraw(cost)
+            .expect("bank owns the resources paid to it");
+        *self.players.get_mut(player_id).resources() += &cost;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::{prelude::*, sample::Index};
+    use rand::{SeedableRng, rngs::SmallRng, seq::IndexedRandom};
+
+    use super::*;
+    use crate::gameplay::{
+        game::{
+            index::GameIndex,
+            init::GameInitializationState,
+            legal,
+            view::{ContextFactory, SearchFactory, VisibilityConfig},
+            zobrist,
+        },
+        primitives::{resource::Resource, trade::PublicTradeOffer},
+    };
+
+    fn random_state(seed: u64, grants: &[(usize, usize)]) -> GameState {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut init = GameInitializationState::default();
+        for player_id in (0..4).chain((0..4).rev()) {
+            let (establishment, road) = *init
+                .builds
+                .query()
+                .possible_initial_placements(&init.board, player_id)
+                .choose(&mut rng)
+                .expect("default board should have initial placements");
+            init.builds
+                .try_init_place(player_id, road, establishment)
+                .expect("generated initial placement should be valid");
+        }
+
+        let mut state = init.finish();
+        for (player_id, resource) in grants {
+            let resource = Resource::LIST[resource % Resource::LIST.len()];
+            let _ = state.transfer_from_bank((resource, 2).into(), player_id % 4);
+        }
+        state
+    }
+
+    fn legal_actions(state: &GameState) -> Vec<RegularAction> {
+        let player_id = state.turn.get_turn_index();
+        let index = GameIndex::rebuild(state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state,
+            index: &index,
+            visibility: &visibility,
+        };
+        let search = SearchFactory::new(state, visibility.player_policy(player_id), player_id);
+        legal::legal_regular_actions(&factory.player_decision_context(player_id, Some(search)))
+    }
+
+    fn assert_same_state(lhs: &GameState, rhs: &GameState) -> Result<(), TestCaseError> {
+        prop_assert_eq!(
+            serde_json::to_value(lhs).unwrap(),
+            serde_json::to_value(rhs).unwrap()
+        );
+        prop_assert_eq!(lhs.zobrist_hash(), rhs.zobrist_hash());
+        for (lhs, rhs) in lhs.builds.players().iter().zip(rhs.builds.players()) {
+            prop_assert_eq!(&lhs.roads, &rhs.roads);
+        }
+        Ok(())
+    }
+
+    fn assert_index_in_step(index: &GameIndex, state: &GameState) -> Result<(), TestCaseError> {
+        prop_assert_eq!(
+            format!("{index:?}"),
+            format!("{:?}", GameIndex::rebuild(state))
+        );
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn undo_restores_state_after_each_action(
+            seed in any::<u64>(),
+            grants in prop::collection::vec((0..4usize, 0..5usize), 0..24),
+            choices in prop::collection::vec(any::<Index>(), 1..10),
+        ) {
+            let mut state = random_state(seed, &grants);
+            let mut index = GameIndex::rebuild(&state);
+
+            for choice in choices {
+                let before = state.clone();
+                let actions = legal_actions(&state);
+                let action = *choice.get(&actions);
+                let player_id = state.turn.get_turn_index();
+
+                let undo = state.apply_reversible(player_id, action).unwrap();
+                prop_assert_eq!(state.zobrist_hash(), zobrist::full_hash(&state));
+                index.refresh_player(&state, player_id);
+                assert_index_in_step(&index, &state)?;
+                state.undo(undo);
+                index.refresh_player(&state, player_id);
+                assert_index_in_step(&index, &state)?;
+                assert_same_state(&state, &before)?;
+
+                let _ = state.apply_reversible(player_id, action).unwrap();
+                index.refresh_player(&state, player_id);
+            }
+        }
+
+        #[test]
+        fn undo_in_reverse_order_restores_initial_state(
+            seed in any::<u64>(),
+            grants in prop::collection::vec((0..4usize, 0..5usize), 0..24),
+            choices in prop::collection::vec(any::<Index>(), 1..10),
+        ) {
+            let mut state = random_state(seed, &grants);
+            let initial = state.clone();
+            let mut undos = Vec::new();
+
+            for choice in choices {
+                let actions = legal_actions(&state);
+                let action = *choice.get(&actions);
+                let player_id = state.turn.get_turn_index();
+                undos.push(state.apply_reversible(player_id, action).unwrap());
+            }
+            while let Some(undo) = undos.pop() {
+                state.undo(undo);
+            }
+
+            assert_same_state(&state, &initial)?;
+        }
+    }
+
+    #[test]
+    fn trade_offers_are_not_reversible() {
+        let mut state = random_state(0, &[]);
+
+        let err = state
+            .apply_reversible(
+                0,
+                RegularAction::OfferPublicTrade(PublicTradeOffer {
+                    give: Resource::Brick.into(),
+                    take: Resource::Ore.into(),
+                }),
+            )
+            .unwrap_err();
+
+        assert!(matches!(err, ReversibleActionError::NotReversible));
+    }
+}
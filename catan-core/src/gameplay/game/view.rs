@@ -1,4 +1,5 @@
 use std::collections::BTreeSet;
+use std::time::Duration;
 
 use crate::{
     algorithm,
@@ -109,6 +110,8 @@ pub struct PublicPlayerView {
     pub player_id: PlayerId,
     pub resources: PublicPlayerResources,
     pub dev_cards: PublicPlayerDevCards,
+    /// Time left on the player's clock, in timed games only.
+    pub clock: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -123,6 +126,15 @@ pub struct PublicGameView<'a> {
     pub largest_army_owner: Option<PlayerId>,
 }
 
+impl PublicGameView<'_> {
+    /// Shows the time left on every player's clock.
+    pub(crate) fn show_clocks(&mut self, clocks: &[Duration]) {
+        for player in &mut self.players {
+            player.clock = clocks.get(player.player_id).copied();
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PrivatePlayerView<'a> {
     pub player_id: PlayerId,
@@ -159,6 +171,15 @@ pub struct PlayerDecisionContext<'a> {
 }
 
 impl PlayerDecisionContext<'_> {
+    /// Time left to decide, in timed games only.
+    pub fn clock(&self) -> Option<Duration> {
+        self.public
+            .players
+            .iter()
+            .find(|player| player.player_id == self.actor)
+            .and_then(|player| player.clock)
+    }
+
     pub fn counting(&self) -> CountingMode {
         match &self.public.players[0].resources {
             PublicPlayerResources::Exact(_) => CountingMode::Counting,
@@ -347,5 +368,6 @@ fn project_player(
             played: dev_cards.used,
             victory_points,
         },
+        clock: None,
     }
 }
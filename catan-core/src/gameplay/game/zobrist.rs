@@ -0,0 +1,346 @@
+//! Zobrist keys for [`GameState`] hashing.
+//!
+//! Every hashed feature of a position (a build, the robber hex, one resource
+//! count of a player, ...) is packed into an integer and mixed into a
+//! pseudo-random 64-bit key, so no board-sized key tables are needed. The hash
+//! of a state is the XOR of the keys of all its features: mutations update it
+//! by XOR-ing out the keys of the features they change and XOR-ing in the new
+//! ones.
+
+use crate::{
+    gameplay::{
+        game::state::GameState,
+        primitives::{
+            build::{BoardBuildData, Establishment, EstablishmentType, PlayerBuildData},
+            dev_card::{DevCardData, UsableDevCard},
+            player::{PlayerDataContainer, PlayerId},
+            resource::{Resource, ResourceCollection},
+            turn::{GameTurn, TurnPhase},
+        },
+    },
+    topology::{Hex, Path},
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Feature {
+    Establishment = 1,
+    Road,
+    Robber,
+    Resource,
+    DevCard,
+    VictoryPoints,
+    Turn,
+    LongestRoad,
+    BestArmy,
+    Phase,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DevCardPile {
+    Queued,
+    Active,
+    Used,
+}
+
+/// SplitMix64 finalizer.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// `data` must fit into 56 bits.
+fn key(feature: Feature, data: u64) -> u64 {
+    mix(((feature as u64) << 56) | data)
+}
+
+fn hex_code(hex: Hex) -> u64 {
+    ((hex.q as u8 as u64) << 8) | hex.r as u8 as u64
+}
+
+fn establishment(player_id: PlayerId, establishment: &Establishment) -> u64 {
+    let stage = match establishment.stage {
+        EstablishmentType::Settlement => 0,
+        EstablishmentType::City => 1,
+    };
+    let hexes = establishment
+        .pos
+        .as_set()
+        .into_iter()
+        .fold(0, |acc, hex| (acc << 16) | hex_code(hex));
+    key(
+        Feature::Establishment,
+        ((player_id as u64) << 50) | (stage << 48) | hexes,
+    )
+}
+
+fn road(player_id: PlayerId, path: Path) -> u64 {
+    let (h1, h2) = path.as_pair();
+    key(
+        Feature::Road,
+        ((player_id as u64) << 32) | (hex_code(h1) << 16) | hex_code(h2),
+    )
+}
+
+pub(super) fn robber(hex: Hex) -> u64 {
+    key(Feature::Robber, hex_code(hex))
+}
+
+fn resources(player_id: PlayerId, resources: &ResourceCollection) -> u64 {
+    Resource::iter()
+        .enumerate()
+        .fold(0, |acc, (index, resource)| {
+            acc ^ key(
+                Feature::Resource,
+                ((player_id as u64) << 24) | ((index as u64) << 16) | resources[resource] as u64,
+            )
+        })
+}
+
+fn dev_cards(player_id: PlayerId, dev_cards: &DevCardData) -> u64 {
+    let piles = [
+        (DevCardPile::Queued, &dev_cards.queued),
+        (DevCardPile::Active, &dev_cards.active),
+        (DevCardPile::Used, &dev_cards.used),
+    ];
+    let cards = piles.into_iter().fold(0, |acc, (pile, collection)| {
+        UsableDevCard::LIST
+            .into_iter()
+            .enumerate()
+            .fold(acc, |acc, (index, card)| {
+                acc ^ key(
+                    Feature::DevCard,
+                    ((player_id as u64) << 24)
+                        | ((pile as u64) << 20)
+                        | ((index as u64) << 16)
+                        | collection[card] as u64,
+                )
+            })
+    });
+
+    cards
+        ^ key(
+            Feature::VictoryPoints,
+            ((player_id as u64) << 16) | dev_cards.victory_pts as u64,
+        )
+}
+
+/// Hand and development cards of one player plus the largest army holder.
+pub(super) fn players(players: &PlayerDataContainer, ids: &[PlayerId]) -> u64 {
+    ids.iter()
+        .map(|id| {
+            let player = players.get(*id);
+            resources(*id, player.resources()) ^ dev_cards(*id, player.dev_cards())
+        })
+        .fold(best_army(players.best_army()), |acc, key| acc ^ key)
+}
+
+pub(super) fn turn(turn: &GameTurn) -> u64 {
+    key(
+        Feature::Turn,
+        ((turn.get_rounds_played() as u64) << 8) | turn.get_turn_index() as u64,
+    )
+}
+
+pub(super) fn phase(phase: TurnPhase) -> u64 {
+    key(Feature::Phase, phase as u64)
+}
+
+fn longest_road(holder: Option<PlayerId>) -> u64 {
+    key(Feature::LongestRoad, holder.map_or(0, |id| id as u64 + 1))
+}
+
+fn best_army(holder: Option<PlayerId>) -> u64 {
+    key(Feature::BestArmy, holder.map_or(0, |id| id as u64 + 1))
+}
+
+/// Builds of one player plus the longest road holder.
+pub(super) fn builds(builds: &BoardBuildData, player_id: PlayerId) -> u64 {
+    player_builds(player_id, &builds[player_id]) ^ longest_road(builds.longest_road())
+}
+
+fn player_builds(player_id: PlayerId, builds: &PlayerBuildData) -> u64 {
+    builds
+        .establishments
+        .iter()
+        .map(|est| establishment(player_id, est))
+        .chain(
+            builds
+                .roads
+                .edges()
+                .iter()
+                .map(|path| road(player_id, *path)),
+        )
+        .fold(0, |acc, key| acc ^ key)
+}
+
+/// Hash of `state` computed from scratch.
+pub fn full_hash(state: &GameState) -> u64 {
+    let ids = (0..state.players.count()).collect::<Vec<_>>();
+    let builds = state
+        .builds
+        .players_indexed()
+        .fold(0, |acc, (id, builds)| acc ^ player_builds(id, builds));
+
+    builds
+        ^ longest_road(state.builds.longest_road())
+        ^ robber(state.board_state.robber_pos)
+        ^ players(&state.players, &ids)
+        ^ turn(&state.turn)
+        ^ phase(state.phase)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngExt, SeedableRng, rngs::SmallRng, seq::IndexedRandom};
+
+    use super::*;
+    use crate::{
+        agent::action::RegularAction,
+        gameplay::game::{
+            index::GameIndex,
+            init::GameInitializationState,
+            legal,
+            view::{ContextFactory, SearchFactory, VisibilityConfig},
+        },
+        topology::HexIndex,
+    };
+
+    fn random_initial_state(rng: &mut SmallRng) -> GameState {
+        let mut init = GameInitializationState::default();
+        for player_id in (0..4).chain((0..4).rev()) {
+            let (establishment, road) = *init
+                .builds
+                .query()
+                .possible_initial_placements(&init.board, player_id)
+                .choose(rng)
+                .expect("default board should have initial placements");
+            init.builds
+                .try_init_place(player_id, road, establishment)
+                .expect("generated initial placement should be valid");
+        }
+        init.finish()
+    }
+
+    fn random_step(state: &mut GameState, rng: &mut SmallRng) {
+        let player = state.turn.get_turn_index();
+        let other = rng.random_range(0..state.players.count());
+
+        match rng.random_range(0..9) {
+            0 | 1 => {
+                let resource = *Resource::LIST.choose(rng).unwrap();
+                let _ = state.transfer_from_bank((resource, 2).into(), other);
+            }
+            2 => {
+                let hex =
+                    HexIndex::spiral_to_hex(rng.random_range(0..state.board.arrangement.len()));
+                let robbed = rng.random_bool(0.5).then_some(other);
+                let _ = state.use_robbers(hex, player, robbed);
+            }
+            3 => {
+                state.next_turn();
+                state.reset_dev_card_queue(state.turn.get_turn_index());
+            }
+            4 => {
+                let phase = *[
+                    TurnPhase::BeforeRoll,
+                    TurnPhase::Discarding,
+                    TurnPhase::MovingRobber,
+                    TurnPhase::AfterRoll,
+                ]
+                .choose(rng)
+                .unwrap();
+                state.set_phase(phase);
+            }
+            5 => {
+                let resource = *Resource::LIST.choose(rng).unwrap();
+                let _ = state.players_resource_exchange(
+                    (player, resource.into()),
+                    (other, ResourceCollection::default()),
+                );
+            }
+            _ => {
+                let (actions, usages) = {
+                    let index = GameIndex::rebuild(state);
+                    let visibility = VisibilityConfig::default();
+                    let factory = ContextFactory {
+                        state,
+                        index: &index,
+                        visibility: &visibility,
+                    };
+                    let search =
+                        SearchFactory::new(state, visibility.player_policy(player), player);
+                    let context = factory.player_decision_context(player, Some(search));
+                    (
+                        legal::legal_regular_actions(&context),
+                        legal::legal_dev_card_usages(&context),
+                    )
+                };
+
+                if let Some(usage) = usages.choose(rng)
+                    && rng.random_bool(0.5)
+                {
+                    state
+                        .use_dev_card(*usage, player)
+                        .expect("legal dev card usage should apply");
+                    return;
+                }
+
+                match actions.choose(rng).unwrap() {
+                    RegularAction::Build(build) => state
+                        .build(player, *build)
+                        .expect("legal build should apply"),
+                    RegularAction::BuyDevCard => state
+                        .buy_dev_card(player)
+                        .expect("legal purchase should apply"),
+                    RegularAction::TradeWithBank(trade) => state
+                        .trade_with_bank(player, *trade)
+                        .expect("legal trade should apply"),
+                    _ => state.next_turn(),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn incremental_hash_matches_full_hash_after_random_playouts() {
+        for seed in 0..4 {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let mut state = random_initial_state(&mut rng);
+            assert_eq!(state.zobrist_hash(), full_hash(&state));
+
+            for step in 0..200 {
+                random_step(&mut state, &mut rng);
+                assert_eq!(
+                    state.zobrist_hash(),
+                    full_hash(&state),
+                    "hash diverged at step {step} of playout {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn hash_distinguishes_positions_and_survives_serialization() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut state = random_initial_state(&mut rng);
+        let initial = state.zobrist_hash();
+
+        state.transfer_from_bank(Resource::Ore.into(), 0).unwrap();
+        let funded = state.zobrist_hash();
+        assert_ne!(funded, initial);
+        state.transfer_to_bank(Resource::Ore.into(), 0).unwrap();
+        assert_eq!(state.zobrist_hash(), initial);
+
+        state.next_turn();
+        let next_turn = state.zobrist_hash();
+        assert_ne!(next_turn, initial);
+        state.set_phase(TurnPhase::AfterRoll);
+        assert_ne!(state.zobrist_hash(), next_turn);
+
+        let raw = serde_json::to_string(&state).unwrap();
+        let restored: GameState = serde_json::from_str(&raw).unwrap();
+        assert_eq!(restored.zobrist_hash(), state.zobrist_hash());
+    }
+}
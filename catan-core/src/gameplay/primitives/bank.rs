@@ -1,4 +1,4 @@
-use rand::seq::SliceRandom;
+use rand::{Rng, seq::SliceRandom};
 use serde::{Deserialize, Serialize};
 
 use crate::gameplay::primitives::{
@@ -35,9 +35,8 @@ impl Bank {
         self.dev_cards.pop()
     }
 
-    pub fn shuffle_dev_cards(&mut self) {
-        let mut rng = rand::rng();
-        self.dev_cards.shuffle(&mut rng);
+    pub fn shuffle_dev_cards<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        self.dev_cards.shuffle(rng);
     }
 
     pub fn public_view(&self) -> BankViewOwned {
@@ -23,7 +23,7 @@ use crate::{
         },
     },
     topology::{
-        HasPos, Hex, Intersection, Path,
+        HasPos, Hex, HexSymmetry, Intersection, Path, Symmetric,
         collision::CollisionChecker,
         graph::{self, EdgeInsertationError},
     },
@@ -195,6 +195,36 @@ pub mod builds {
             }
         }
     }
+
+    /* Symmetric impls */
+
+    impl Symmetric for Establishment {
+        fn transform(&self, symmetry: HexSymmetry) -> Self {
+            Self {
+                pos: self.pos.transform(symmetry),
+                stage: self.stage,
+            }
+        }
+    }
+
+    impl Symmetric for Road {
+        fn transform(&self, symmetry: HexSymmetry) -> Self {
+            Self {
+                pos: self.pos.transform(symmetry),
+            }
+        }
+    }
+
+    impl Symmetric for Build {
+        fn transform(&self, symmetry: HexSymmetry) -> Self {
+            match self {
+                Build::Establishment(establishment) => {
+                    Build::Establishment(establishment.transform(symmetry))
+                }
+                Build::Road(road) => Build::Road(road.transform(symmetry)),
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -502,10 +532,9 @@ pub mod data {
                             Err(BuildingError::SettlementLimit())
                         }
                         true => Ok({
-                            debug_assert!(
-                                self.players[player_id].establishments.insert(establishment),
-                                "checker malfunction"
-                            );
+                            let inserted =
+                                self.players[player_id].establishments.insert(establishment);
+                            debug_assert!(inserted, "checker malfunction");
                         }),
                         false => Err(BuildingError::Settlement()), // invalid placement for a settlement
                     },
@@ -521,15 +550,14 @@ pub mod data {
                             Err(BuildingError::CityLimit())
                         }
                         true => Ok({
-                            debug_assert!(
+                            let removed =
                                 self.players[player_id]
                                     .establishments
                                     .remove(&Establishment {
                                         pos: establishment.pos,
                                         stage: EstablishmentType::Settlement,
-                                    }),
-                                "set handling logic error"
-                            );
+                                    });
+                            debug_assert!(removed, "set handling logic error");
 
                             assert!(
                                 self.players[player_id].establishments.insert(establishment),
@@ -542,6 +570,30 @@ pub mod data {
             }
         }
 
+        /// Takes back a successful [`BoardBuildData::try_build`] of `build`,
+        /// restoring the longest road holder from before it.
+        pub fn undo_build(
+            &mut self,
+            player_id: PlayerId,
+            build: Build,
+            longest_road: Option<PlayerId>,
+        ) {
+            let player = &mut self.players[player_id];
+            match build {
+                Build::Road(road) => player.roads.remove_edge(&road.pos),
+                Build::Establishment(establishment) => {
+                    player.establishments.remove(&establishment);
+                    if establishment.stage == EstablishmentType::City {
+                        player.establishments.insert(Establishment {
+                            pos: establishment.pos,
+                            stage: EstablishmentType::Settlement,
+                        });
+                    }
+                }
+            }
+            self.longest_road = longest_road;
+        }
+
         fn update_longest_road(&mut self, candidate: PlayerId) {
             let candidate_len = self.players[candidate].roads.find_longest_trail_length();
             if candidate_len < 5 {
@@ -639,6 +691,36 @@ pub mod data {
             &mut self.players[index]
         }
     }
+    impl Symmetric for PlayerBuildData {
+        fn transform(&self, symmetry: HexSymmetry) -> Self {
+            Self {
+                establishments: self
+                    .establishments
+                    .iter()
+                    .map(|establishment| establishment.transform(symmetry))
+                    .collect(),
+                roads: graph::RoadGraph::from_roads(
+                    self.roads
+                        .edges()
+                        .iter()
+                        .map(|path| path.transform(symmetry)),
+                ),
+            }
+        }
+    }
+
+    impl Symmetric for BoardBuildData {
+        fn transform(&self, symmetry: HexSymmetry) -> Self {
+            Self {
+                players: self
+                    .players
+                    .iter()
+                    .map(|player| player.transform(symmetry))
+                    .collect(),
+                longest_road: self.longest_road,
+            }
+        }
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////////////////////////
@@ -934,4 +1016,42 @@ mod tests {
 
         assert_eq!(builds.longest_road(), Some(0));
     }
+
+    #[test]
+    fn symmetries_map_builds_and_keep_longest_road() {
+        let center = h(0, 0);
+        let vertex = center.vertices_arr()[0];
+        let builds = BoardBuildData::from_build_collections(vec![
+            BuildCollection {
+                establishments: vec![city(vertex)],
+                roads: center
+                    .paths_arr()
+                    .into_iter()
+                    .take(5)
+                    .map(|pos| Road { pos })
+                    .collect(),
+            },
+            BuildCollection {
+                establishments: vec![settlement(h(2, -1).vertices_arr()[3])],
+                roads: vec![],
+            },
+        ]);
+
+        for symmetry in HexSymmetry::all() {
+            let image = builds.transform(symmetry);
+
+            assert_eq!(image.longest_road(), builds.longest_road());
+            assert!(
+                image[0]
+                    .establishments
+                    .contains(&city(vertex.transform(symmetry)))
+            );
+            assert_eq!(image[0].roads_count(), 5);
+            let restored = image.transform(symmetry.inverse());
+            for (lhs, rhs) in restored.players().iter().zip(builds.players()) {
+                assert_eq!(lhs.establishments, rhs.establishments);
+                assert_eq!(lhs.roads.edges(), rhs.roads.edges());
+            }
+        }
+    }
 }
@@ -14,7 +14,7 @@ pub enum UsableDevCard {
 }
 
 impl UsableDevCard {
-    const LIST: [UsableDevCard; 4] = [
+    pub const LIST: [UsableDevCard; 4] = [
         Self::Knight,
         Self::YearOfPlenty,
         Self::RoadBuild,
@@ -139,6 +139,18 @@ impl DevCardData {
         }
     }
 
+    /// Inverse of [`DevCardData::add`].
+    pub fn remove(&mut self, card: DevCardKind) {
+        match card {
+            DevCardKind::Usable(usable_dev_card_kind) => {
+                self.queued[usable_dev_card_kind].dec();
+            }
+            DevCardKind::VictoryPoint => {
+                self.victory_pts.dec();
+            }
+        }
+    }
+
     pub fn move_to_used(&mut self, card: UsableDevCard) -> Result<(), DevCardDataPlayingError> {
         match self.active.contains(card) {
             true => Ok({
@@ -151,6 +151,10 @@ impl<'a> PlayerDataProxyMut<'a> {
         &self.container.players[self.player_id].dev_cards
     }
 
+    pub fn dev_cards_mut(&mut self) -> &mut DevCardData {
+        &mut self.container.players[self.player_id].dev_cards
+    }
+
     pub fn dev_cards_reset_queue(&mut self) {
         self.container.players[self.player_id]
             .dev_cards
@@ -3,7 +3,7 @@ use std::{
     ops::{Add, AddAssign, Index, IndexMut},
 };
 
-use rand::RngExt;
+use rand::{Rng, RngExt};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -196,7 +196,7 @@ impl ResourceCollection {
     }
 
     // None if empty, weighted random otherwise
-    pub fn peek_random(&self) -> Option<Resource> {
+    pub fn peek_random<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Resource> {
         // Calculate total and return None if empty
         if self.is_empty() {
             return None;
@@ -205,7 +205,6 @@ impl ResourceCollection {
         log::debug!("self.total={}", self.total());
 
         // Generate random number
-        let mut rng = rand::rng();
         let rand_val: u16 = rng.random_range(0..self.total());
         let mut cum_total: u16 = 0;
 
@@ -221,8 +220,8 @@ impl ResourceCollection {
         unreachable!("peek random: total == 0?")
     }
 
-    pub fn pop_random(&mut self) -> Option<Resource> {
-        match self.peek_random() {
+    pub fn pop_random<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<Resource> {
+        match self.peek_random(rng) {
             Some(resource) => {
                 self.subtract_in_place(&resource.into()).ok()?;
                 Some(resource)
@@ -8,6 +8,18 @@ pub struct RegularCycle;
 #[derive(Debug, Clone)]
 pub struct BackAndForthCycle;
 
+/// Stage of the current player's turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TurnPhase {
+    #[default]
+    BeforeRoll,
+    /// A seven was rolled and players over the hand limit discard.
+    Discarding,
+    /// A seven was rolled and the robber is about to move.
+    MovingRobber,
+    AfterRoll,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameTurn<CycleType = RegularCycle> {
     n_players: u8, // in [0..=4]
@@ -37,10 +37,12 @@ impl GameInitializer {
         Self { state, agents }
     }
 
-    pub fn init(self) -> GameRunner {
+    /// Plays the initial placements; `Err` holds the result of a game that
+    /// ended during them.
+    pub fn init(self) -> Result<GameRunner, GameResult> {
         let mut agents = self.agents;
-        let state = GameController::init(self.state, &mut agents);
-        GameRunner { state, agents }
+        let state = GameController::init(self.state, &mut agents)?;
+        Ok(GameRunner { state, agents })
     }
 }
 
@@ -10,7 +10,7 @@ use crate::{
 // (better than v -> {v}, cause edge's invariant enforces correctness of a graph)
 
 /// Not oriented graph
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct RoadGraph {
     edges: BTreeSet<Path>,
     out: BTreeMap<Intersection, BTreeSet<Path>>,
@@ -74,6 +74,21 @@ impl RoadGraph {
         self.edges.insert(edge.clone());
     }
 
+    /// remove an edge, no questions asked
+    /// ---
+    /// inverse of `add_edge`, used to take back moves
+    pub fn remove_edge(&mut self, edge: &Path) {
+        for vertex in edge.intersections() {
+            if let Some(edges) = self.out.get_mut(&vertex) {
+                edges.remove(edge);
+                if edges.is_empty() {
+                    self.out.remove(&vertex);
+                }
+            }
+        }
+        self.edges.remove(edge);
+    }
+
     /// add new road connected to existing
     pub fn extend(
         &mut self,
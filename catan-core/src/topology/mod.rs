@@ -3,10 +3,12 @@ pub mod graph;
 pub mod hex;
 pub mod intersection;
 pub mod path;
+pub mod symmetry;
 
 pub use hex::*;
 pub use intersection::*;
 pub use path::*;
+pub use symmetry::*;
 
 pub trait HasPos {
     type Pos;
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+
+use crate::topology::{Hex, Intersection, Path, SignedAxis};
+
+/// One of the 12 symmetries of the hex grid that keep the origin hex in place.
+///
+/// A symmetry is an optional reflection followed by a counter-clockwise
+/// rotation by `rotation * 60°`. One rotation step maps each direction of
+/// [`Hex::neighbors`] to the next one (East to Northeast, ...); the reflection
+/// keeps the East-West axis and swaps Northeast with Southeast.
+///
+/// # Examples
+///
+/// ```
+/// use catan_core::topology::{Hex, HexSymmetry, Symmetric};
+///
+/// let east = Hex::new(1, 0);
+/// let quarter = HexSymmetry::rotation(1);
+///
+/// // East rotates to Northeast
+/// assert_eq!(east.transform(quarter), Hex::new(1, -1));
+/// // every symmetry can be undone
+/// for symmetry in HexSymmetry::all() {
+///     assert_eq!(east.transform(symmetry).transform(symmetry.inverse()), east);
+/// }
+/// ```
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+pub struct HexSymmetry {
+    rotation: u8,
+    reflected: bool,
+}
+
+impl HexSymmetry {
+    pub const IDENTITY: Self = Self {
+        rotation: 0,
+        reflected: false,
+    };
+
+    pub const fn new(rotation: u8, reflected: bool) -> Self {
+        Self {
+            rotation: rotation % 6,
+            reflected,
+        }
+    }
+
+    pub const fn rotation(steps: u8) -> Self {
+        Self::new(steps, false)
+    }
+
+    pub const fn reflection() -> Self {
+        Self::new(0, true)
+    }
+
+    /// All 12 symmetries: 6 rotations followed by 6 reflections.
+    pub fn all() -> [Self; 12] {
+        std::array::from_fn(|i| Self::new((i % 6) as u8, i >= 6))
+    }
+
+    pub const fn rotation_steps(&self) -> u8 {
+        self.rotation
+    }
+
+    pub const fn is_reflected(&self) -> bool {
+        self.reflected
+    }
+
+    pub const fn inverse(&self) -> Self {
+        if self.reflected {
+            *self
+        } else {
+            Self::new(6 - self.rotation, false)
+        }
+    }
+
+    /// Symmetry equivalent to applying `self` and then `next`.
+    pub const fn then(&self, next: Self) -> Self {
+        if next.reflected {
+            Self::new(next.rotation + 6 - self.rotation, !self.reflected)
+        } else {
+            Self::new(self.rotation + next.rotation, self.reflected)
+        }
+    }
+
+    pub const fn apply(&self, hex: Hex) -> Hex {
+        let (mut q, mut r, mut s) = (hex.q, hex.r, hex.get_s());
+        if self.reflected {
+            (q, r, s) = (-s, -r, -q);
+        }
+        let mut step = 0;
+        while step < self.rotation {
+            (q, r, s) = (-s, -q, -r);
+            step += 1;
+        }
+        Hex::new(q, r)
+    }
+
+    /// Maps a direction index of [`Hex::neighbors`].
+    pub const fn apply_dir(&self, dir_index: usize) -> usize {
+        let dir_index = if self.reflected {
+            (6 - dir_index) % 6
+        } else {
+            dir_index
+        };
+        (dir_index + self.rotation as usize) % 6
+    }
+}
+
+/// Types that can be mapped by a [`HexSymmetry`].
+pub trait Symmetric {
+    fn transform(&self, symmetry: HexSymmetry) -> Self;
+}
+
+impl Symmetric for Hex {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        symmetry.apply(*self)
+    }
+}
+
+impl Symmetric for SignedAxis {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        SignedAxis::from_dir(symmetry.apply_dir(self.dir_index()))
+    }
+}
+
+impl Symmetric for Intersection {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        let hexes = self
+            .as_set()
+            .into_iter()
+            .map(|hex| symmetry.apply(hex))
+            .collect::<Vec<_>>();
+        let hexes: [Hex; 3] = hexes
+            .try_into()
+            .expect("intersection consists of three hexes");
+        Intersection::try_from(hexes).expect("symmetries preserve adjacency")
+    }
+}
+
+impl Symmetric for Path {
+    fn transform(&self, symmetry: HexSymmetry) -> Self {
+        let (h1, h2) = self.as_pair();
+        Path::try_from((symmetry.apply(h1), symmetry.apply(h2)))
+            .expect("symmetries preserve adjacency")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::topology::HexIndex;
+
+    fn hexes() -> impl Iterator<Item = Hex> {
+        (0..HexIndex::spiral_start_of_ring(4)).map(HexIndex::spiral_to_hex)
+    }
+
+    #[test]
+    fn twelve_distinct_symmetries() {
+        let images = HexSymmetry::all()
+            .into_iter()
+            .map(|symmetry| [Hex::new(1, 0), Hex::new(2, -1)].map(|hex| hex.transform(symmetry)))
+            .collect::<BTreeSet<_>>();
+
+        assert_eq!(images.len(), 12);
+    }
+
+    #[test]
+    fn symmetries_preserve_distance_and_fix_origin() {
+        for symmetry in HexSymmetry::all() {
+            assert_eq!(Hex::new(0, 0).transform(symmetry), Hex::new(0, 0));
+            for a in hexes() {
+                for b in hexes().take(7) {
+                    assert_eq!(
+                        a.transform(symmetry).distance(&b.transform(symmetry)),
+                        a.distance(&b)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn composition_and_inverse_agree_with_application() {
+        for first in HexSymmetry::all() {
+            assert_eq!(first.then(first.inverse()), HexSymmetry::IDENTITY);
+            for second in HexSymmetry::all() {
+                let composed = first.then(second);
+                for hex in hexes() {
+                    assert_eq!(
+                        hex.transform(composed),
+                        hex.transform(first).transform(second)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn directions_follow_hexes() {
+        for symmetry in HexSymmetry::all() {
+            for (dir_index, dir) in Hex::directions().into_iter().enumerate() {
+                assert_eq!(
+                    Hex::direction(symmetry.apply_dir(dir_index)),
+                    dir.transform(symmetry)
+                );
+                let axis = SignedAxis::from_dir(dir_index);
+                assert_eq!(axis.transform(symmetry).dir(), dir.transform(symmetry));
+            }
+        }
+    }
+
+    #[test]
+    fn vertices_and_paths_map_onto_image_hex() {
+        let hex = Hex::new(1, -2);
+        for symmetry in HexSymmetry::all() {
+            let image = hex.transform(symmetry);
+            let vertices = hex
+                .vertices_arr()
+                .map(|vertex| vertex.transform(symmetry))
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+            let paths = hex
+                .paths_arr()
+                .map(|path| path.transform(symmetry))
+                .into_iter()
+                .collect::<BTreeSet<_>>();
+
+            assert_eq!(vertices, image.vertices_arr().into_iter().collect());
+            assert_eq!(paths, image.paths_arr().into_iter().collect());
+        }
+    }
+}
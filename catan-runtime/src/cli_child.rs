@@ -1,10 +1,12 @@
 //! Remote terminal UI child process.
 //!
-//! This module is intentionally split into small files: session/socket orchestration,
-//! terminal rendering, input parsing, selectors, panels, labels, render adapters, and logging.
+//! This module is intentionally split into small files: session orchestration, host links
+//! (sockets and websockets), terminal rendering, input parsing, selectors, panels, labels,
+//! render adapters, and logging.
 
 mod input;
 mod labels;
+mod link;
 mod logging;
 mod panels;
 mod render;
@@ -13,4 +15,4 @@ mod session;
 mod snapshot;
 mod tui;
 
-pub use session::run;
+pub use session::{run, run_remote, run_server};
@@ -452,6 +452,10 @@ pub(crate) fn parse_regular_action(line: &str) -> Option<RegularAction> {
     if matches!(line, "buy dev" | "buy-dev" | "bd") {
         return Some(RegularAction::BuyDevCard);
     }
+    // no shortcut, so nobody resigns by accident
+    if line == "resign" {
+        return Some(RegularAction::Resign);
+    }
     if let Some(build) = parse_build(line) {
         return Some(RegularAction::Build(build));
     }
@@ -700,6 +704,10 @@ mod tests {
             parse_regular_action("e"),
             Some(RegularAction::EndMove)
         ));
+        assert!(matches!(
+            parse_regular_action("resign"),
+            Some(RegularAction::Resign)
+        ));
         assert_eq!(
             partial_dev_card_command("kn"),
             Some(PartialDevCardMode::Knight)
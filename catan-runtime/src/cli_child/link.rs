@@ -0,0 +1,176 @@
+//! Connection from the CLI child to its host.
+//!
+//! A `catan-runtime` host speaks length-prefixed frames over a Unix socket or
+//! TCP, a `catan-server` room a JSON text message per frame over a websocket.
+//! The session reads and writes frames the same way over both.
+
+use std::{io, net::TcpStream};
+
+use catan_agents::remote_agent::{
+    CliStream, CliToHost, HostToCli, NonblockingFrameReader, read_frame, write_frame,
+};
+use tungstenite::{Message, WebSocket, stream::MaybeTlsStream};
+
+pub(super) enum HostLink {
+    Socket {
+        stream: CliStream,
+        reader: NonblockingFrameReader<HostToCli>,
+    },
+    WebSocket(Box<WebSocket<MaybeTlsStream<TcpStream>>>),
+}
+
+impl HostLink {
+    pub(super) fn socket(stream: CliStream) -> Self {
+        Self::Socket {
+            stream,
+            reader: NonblockingFrameReader::default(),
+        }
+    }
+
+    /// Dials a `catan-server` seat or spectator URL.
+    pub(super) fn websocket(url: &str) -> Result<Self, String> {
+        let (socket, _) = tungstenite::connect(url)
+            .map_err(|err| format!("failed to connect to {url}: {err}"))?;
+        Ok(Self::WebSocket(Box::new(socket)))
+    }
+
+    pub(super) fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        match self {
+            Self::Socket { stream, .. } => stream.set_nonblocking(nonblocking),
+            Self::WebSocket(socket) => match socket.get_ref() {
+                MaybeTlsStream::Plain(stream) => stream.set_nonblocking(nonblocking),
+                _ => Err(io::Error::other("only plain websockets are supported")),
+            },
+        }
+    }
+
+    /// Waits for the next host frame; a closed connection is reported as
+    /// [`io::ErrorKind::UnexpectedEof`].
+    pub(super) fn recv(&mut self) -> io::Result<HostToCli> {
+        match self {
+            Self::Socket { stream, .. } => read_frame(stream),
+            Self::WebSocket(socket) => loop {
+                if let Some(msg) = read_message(socket)? {
+                    return Ok(msg);
+                }
+            },
+        }
+    }
+
+    /// The next host frame of a nonblocking link, if one is complete.
+    pub(super) fn poll(&mut self) -> io::Result<Option<HostToCli>> {
+        match self {
+            Self::Socket { stream, reader } => reader.poll(stream),
+            Self::WebSocket(socket) => match read_message(socket) {
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(None),
+                other => other,
+            },
+        }
+    }
+
+    pub(super) fn send(&mut self, msg: &CliToHost) -> io::Result<()> {
+        match self {
+            Self::Socket { stream, .. } => write_frame(stream, msg),
+            Self::WebSocket(socket) => {
+                let text = serde_json::to_string(msg).map_err(io::Error::other)?;
+                socket
+                    .send(Message::Text(text.into()))
+                    .map_err(websocket_error)
+            }
+        }
+    }
+}
+
+/// The frame of the next websocket message, `None` for pings and other
+/// messages without one.
+fn read_message(
+    socket: &mut WebSocket<MaybeTlsStream<TcpStream>>,
+) -> io::Result<Option<HostToCli>> {
+    match socket.read().map_err(websocket_error)? {
+        Message::Text(text) => serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err)),
+        Message::Close(_) => Err(io::ErrorKind::UnexpectedEof.into()),
+        _ => Ok(None),
+    }
+}
+
+fn websocket_error(err: tungstenite::Error) -> io::Error {
+    match err {
+        tungstenite::Error::Io(err) => err,
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            io::ErrorKind::UnexpectedEof.into()
+        }
+        other => io::Error::other(other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread, time::Duration};
+
+    use catan_agents::remote_agent::{Capability, CliRole, PROTOCOL_VERSION};
+
+    use super::*;
+
+    #[test]
+    fn websocket_links_carry_frames_as_text_messages() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/spectate/0", listener.local_addr().unwrap());
+        let host = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let text = |msg: &HostToCli| Message::Text(serde_json::to_string(msg).unwrap().into());
+            socket
+                .send(text(&HostToCli::hello(CliRole::Spectator, None)))
+                .unwrap();
+            let reply = socket.read().unwrap();
+            socket.send(Message::Ping(Vec::new().into())).unwrap();
+            thread::sleep(Duration::from_millis(50));
+            socket
+                .send(text(&HostToCli::Shutdown {
+                    reason: "game over".to_owned(),
+                }))
+                .unwrap();
+            socket.close(None).unwrap();
+            while socket.read().is_ok() {}
+            serde_json::from_str::<CliToHost>(reply.to_text().unwrap()).unwrap()
+        });
+
+        let mut link = HostLink::websocket(&url).unwrap();
+        assert!(matches!(
+            link.recv().unwrap(),
+            HostToCli::Hello {
+                role: CliRole::Spectator,
+                ..
+            }
+        ));
+        link.send(&CliToHost::answer_hello(
+            PROTOCOL_VERSION,
+            vec![Capability::Diffs],
+        ))
+        .unwrap();
+
+        // the ping is no frame, and the shutdown isn't there yet
+        link.set_nonblocking(true).unwrap();
+        assert!(link.poll().unwrap().is_none());
+        let shutdown = loop {
+            if let Some(msg) = link.poll().unwrap() {
+                break msg;
+            }
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert!(matches!(shutdown, HostToCli::Shutdown { reason } if reason == "game over"));
+        link.set_nonblocking(false).unwrap();
+        assert_eq!(
+            link.recv().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        drop(link);
+
+        assert!(matches!(
+            host.join().unwrap(),
+            CliToHost::Hello { capabilities, .. } if capabilities == [Capability::Diffs]
+        ));
+    }
+}
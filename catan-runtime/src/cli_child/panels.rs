@@ -311,6 +311,15 @@ fn public_player_line(player: &catan_agents::remote_agent::UiPublicPlayer) -> Li
         "  dev {}/{}",
         player.active_dev_cards, player.queued_dev_cards
     )));
+    if let Some(clock_ms) = player.clock_ms {
+        spans.push(Span::raw("  clock "));
+        let style = if clock_ms == 0 {
+            Style::default().fg(Color::Red)
+        } else {
+            style
+        };
+        spans.push(Span::styled(clock_label(clock_ms), style));
+    }
     Line::from(spans)
 }
 
@@ -333,6 +342,12 @@ fn victory_points_label(victory_points: Option<u16>) -> String {
         .unwrap_or_else(|| "?".to_owned())
 }
 
+/// Time left on a clock as minutes and seconds, rounded down.
+fn clock_label(clock_ms: u64) -> String {
+    let secs = clock_ms / 1000;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
 fn snapshot_box_width(width: u16) -> usize {
     usize::from(width.max(12))
 }
@@ -900,8 +915,8 @@ mod tests {
     };
 
     use super::{
-        adjust_drop_selection, bank_trade_menu_lines, dev_card_lines, drop_personal_lines,
-        resource_card_lines, snapshot_state_lines,
+        adjust_drop_selection, bank_trade_menu_lines, clock_label, dev_card_lines,
+        drop_personal_lines, resource_card_lines, snapshot_state_lines,
     };
 
     #[test]
@@ -934,6 +949,13 @@ mod tests {
         assert!(rendered_dev[1].contains("│VP│"));
     }
 
+    #[test]
+    fn clock_labels_show_minutes_and_seconds() {
+        assert_eq!(clock_label(0), "0:00");
+        assert_eq!(clock_label(59_999), "0:59");
+        assert_eq!(clock_label(754_000), "12:34");
+    }
+
     #[test]
     fn drop_selection_is_bounded_by_available_resources() {
         let available = ResourceCollection {
@@ -1,17 +1,22 @@
 //! Host session orchestration for the CLI child.
 //!
-//! Owns the Unix socket lifecycle, handshake, host-frame loop, decision dispatch,
-//! shutdown handling, and request/response logging.
+//! Owns the connection lifecycle (a Unix socket to a local host, TCP to a
+//! listening one or a websocket to a `catan-server` room), handshake,
+//! host-frame loop, decision dispatch, shutdown handling, and
+//! request/response logging.
 
-use std::{io, os::unix::net::UnixStream, path::Path as FsPath, time::Duration};
+use std::{io, net::TcpStream, os::unix::net::UnixStream, path::Path as FsPath, time::Duration};
 
 use catan_agents::remote_agent::{
-    CliRole, CliToHost, DecisionRequestFrame, DecisionResponseFrame, HostToCli,
-    NonblockingFrameReader, UiModel, read_frame, ui_model_summary, write_frame,
+    Capability, CliRole, CliStream, CliToHost, DecisionRequestFrame, DecisionResponseFrame,
+    HostToCli, UiModel, ViewDecoder, ui_model_summary, write_frame,
 };
-use catan_core::agent::action::{
-    ChoosePlayerToRobAction, DropHalfAction, InitStageAction, MoveRobbersAction, PostDevCardAction,
-    TradeAnswer,
+use catan_core::{
+    agent::action::{
+        ChoosePlayerToRobAction, DropHalfAction, InitStageAction, MoveRobbersAction,
+        PostDevCardAction, TradeAnswer,
+    },
+    gameplay::game::event::GameEvent,
 };
 
 use super::{
@@ -19,13 +24,14 @@ use super::{
         read_hex, read_init_action, read_initial_road, read_initial_settlement,
         read_post_dice_action, read_regular_action, read_resource_collection, read_robbed_player,
     },
+    link::HostLink,
     logging::init_socket_logger,
     snapshot::SnapshotWriter,
     tui::{CliUi, CliViewMode, ControlInput},
 };
 
 pub fn run(socket: &FsPath, log_socket: &FsPath, _role: &str) -> Result<(), String> {
-    let mut stream = UnixStream::connect(socket)
+    let stream = UnixStream::connect(socket)
         .map_err(|err| format!("failed to connect to {}: {err}", socket.display()))?;
     let log_stream = UnixStream::connect(log_socket).map_err(|err| {
         format!(
@@ -35,13 +41,49 @@ pub fn run(socket: &FsPath, log_socket: &FsPath, _role: &str) -> Result<(), Stri
     })?;
     init_socket_logger(log_stream);
     log::trace!("Starting CLI with socket: {}", socket.display());
-    let role = match read_frame::<HostToCli>(&mut stream)
+    serve(HostLink::socket(stream.into()))
+}
+
+/// Takes the seat of a host listening on `addr` that `token` was handed out
+/// for. There is no log socket across machines, so the child's logs are
+/// dropped.
+pub fn run_remote(addr: &str, token: &str) -> Result<(), String> {
+    let stream =
+        TcpStream::connect(addr).map_err(|err| format!("failed to connect to {addr}: {err}"))?;
+    stream
+        .set_nodelay(true)
+        .map_err(|err| format!("failed to configure connection to {addr}: {err}"))?;
+    let mut stream = CliStream::from(stream);
+    write_frame(
+        &mut stream,
+        &CliToHost::Join {
+            token: token.to_owned(),
+        },
+    )
+    .map_err(|err| format!("failed to join {addr}: {err}"))?;
+    serve(HostLink::socket(stream))
+}
+
+/// Plays a seat of a `catan-server` room, or watches one, at `url`: a
+/// `ws://<host>/rooms/<id>/seats/<seat>` or `ws://<host>/spectate/<id>` URL.
+/// Its logs are dropped like the ones of [`run_remote`].
+pub fn run_server(url: &str) -> Result<(), String> {
+    serve(HostLink::websocket(url)?)
+}
+
+fn serve(mut link: HostLink) -> Result<(), String> {
+    let (role, host_version) = match link
+        .recv()
         .map_err(|err| format!("failed to read hello: {err}"))?
     {
-        HostToCli::Hello { role } => {
-            log::trace!("Connected as role: {:?}", role);
-            role
+        HostToCli::Hello { role, version, .. } => {
+            log::trace!(
+                "Connected as role: {:?} over protocol version {version}",
+                role
+            );
+            (role, version)
         }
+        HostToCli::Shutdown { reason } => return Err(format!("host turned us away: {reason}")),
         other => return Err(format!("expected hello, got {other:?}")),
     };
     let view_mode = view_mode_for_role(&role);
@@ -55,15 +97,18 @@ pub fn run(socket: &FsPath, log_socket: &FsPath, _role: &str) -> Result<(), Stri
     let mut ui = CliUi::new(view_mode).map_err(|err| format!("failed to initialize TUI: {err}"))?;
     ui.set_message(format!("connected as {role:?}"))
         .map_err(|err| format!("failed to draw TUI: {err}"))?;
-    write_frame(&mut stream, &CliToHost::Ready)
-        .map_err(|err| format!("failed to send ready: {err}"))?;
-    log::trace!("Sent ready message to host");
+    link.send(&CliToHost::answer_hello(
+        host_version,
+        vec![Capability::Trades, Capability::Diffs],
+    ))
+    .map_err(|err| format!("failed to answer hello: {err}"))?;
+    log::trace!("Answered hello from host");
 
     if role.is_observer() {
-        return run_observer_session(stream, ui, snapshot_writer);
+        return run_observer_session(link, ui, snapshot_writer);
     }
 
-    run_player_session(stream, ui, view_mode)
+    run_player_session(link, ui, view_mode)
 }
 
 fn view_mode_for_role(role: &CliRole) -> CliViewMode {
@@ -75,12 +120,13 @@ fn view_mode_for_role(role: &CliRole) -> CliViewMode {
 }
 
 fn run_player_session(
-    mut stream: UnixStream,
+    mut link: HostLink,
     mut ui: CliUi,
     view_mode: CliViewMode,
 ) -> Result<(), String> {
+    let mut views = ViewDecoder::default();
     loop {
-        let msg = match read_frame::<HostToCli>(&mut stream) {
+        let msg = match link.recv() {
             Ok(msg) => msg,
             Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
                 log::error!("Host closed socket unexpectedly");
@@ -96,13 +142,21 @@ fn run_player_session(
             HostToCli::Hello { .. } => {
                 log::trace!("Ignoring duplicate hello message");
             }
+            HostToCli::Error { message } => {
+                log::warn!("host refused our last frame: {message}");
+                ui.set_message(format!("refused: {message}"))
+                    .map_err(|err| format!("failed to draw TUI: {err}"))?;
+            }
             HostToCli::Shutdown { reason } => {
                 log::trace!("Shutdown received with reason: {}", reason);
                 ui.set_message(format!("shutdown: {reason}"))
                     .map_err(|err| format!("failed to draw TUI: {err}"))?;
                 return Ok(());
             }
-            HostToCli::Event { event, view } => {
+            msg @ (HostToCli::Event { .. } | HostToCli::EventDelta { .. }) => {
+                let Some((event, view)) = decode_event(&mut views, msg) else {
+                    continue;
+                };
                 if process_host_event(&mut ui, view_mode, &event, &view, None)? {
                     return Ok(());
                 }
@@ -113,7 +167,7 @@ fn run_player_session(
                         "snapshot observer received unexpected decision request: {request:?}"
                     );
                     log::warn!("{message}");
-                    write_frame(&mut stream, &CliToHost::Error { message })
+                    link.send(&CliToHost::Error { message })
                         .map_err(|err| format!("failed to send observer error: {err}"))?;
                     continue;
                 }
@@ -126,7 +180,7 @@ fn run_player_session(
                 let response = handle_decision(&mut ui, request)
                     .map_err(|err| format!("failed to handle decision: {err}"))?;
                 log::trace!("Sending decision response: {:?}", response);
-                write_frame(&mut stream, &CliToHost::DecisionResponse(response))
+                link.send(&CliToHost::DecisionResponse(response))
                     .map_err(|err| format!("failed to send decision response: {err}"))?;
             }
         }
@@ -134,14 +188,13 @@ fn run_player_session(
 }
 
 fn run_observer_session(
-    mut stream: UnixStream,
+    mut link: HostLink,
     mut ui: CliUi,
     snapshot_writer: Option<SnapshotWriter>,
 ) -> Result<(), String> {
-    stream
-        .set_nonblocking(true)
+    link.set_nonblocking(true)
         .map_err(|err| format!("failed to set snapshot observer socket nonblocking: {err}"))?;
-    let mut reader = NonblockingFrameReader::<HostToCli>::default();
+    let mut views = ViewDecoder::default();
     let mut state = ObserverSessionState {
         latest: SessionViewState::message("connected as observer"),
         event_count: 0,
@@ -149,8 +202,8 @@ fn run_observer_session(
     };
     loop {
         let mut received_message = false;
-        while let Some(msg) = reader
-            .poll(&mut stream)
+        while let Some(msg) = link
+            .poll()
             .map_err(|err| format!("failed to read host frame: {err}"))?
         {
             received_message = true;
@@ -158,10 +211,16 @@ fn run_observer_session(
                 HostToCli::Hello { .. } => {
                     log::trace!("Ignoring duplicate hello message");
                 }
+                HostToCli::Error { message } => {
+                    log::warn!("host refused our last frame: {message}");
+                }
                 HostToCli::Shutdown { reason } => {
                     return handle_shutdown(&mut ui, reason);
                 }
-                HostToCli::Event { event, view } => {
+                msg @ (HostToCli::Event { .. } | HostToCli::EventDelta { .. }) => {
+                    let Some((event, view)) = decode_event(&mut views, msg) else {
+                        continue;
+                    };
                     state.event_count += 1;
                     log::trace!(
                         target: "catan_runtime::cli_child::observer_flow",
@@ -185,7 +244,7 @@ fn run_observer_session(
                 HostToCli::DecisionRequest(request) => {
                     let message =
                         format!("observer received unexpected decision request: {request:?}");
-                    send_child_error(&mut stream, message.clone())?;
+                    send_child_error(&mut link, message.clone())?;
                     state.latest.message = message;
                     draw_latest_or_message(&mut ui, &state.latest, state.event_count)?;
                 }
@@ -207,6 +266,22 @@ fn run_observer_session(
     }
 }
 
+/// Event and full view of an event frame. A delta that doesn't fit the view
+/// is skipped, and so are the ones after it until the next full view.
+fn decode_event(views: &mut ViewDecoder, msg: HostToCli) -> Option<(GameEvent, UiModel)> {
+    match msg {
+        HostToCli::Event { event, view } => Some((event, views.keyframe(view).clone())),
+        HostToCli::EventDelta { event, delta } => match views.apply(&delta) {
+            Ok(view) => Some((event, view.clone())),
+            Err(err) => {
+                log::warn!("skipped {event:?} until the next full view: {err}");
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
 struct ObserverSessionState {
     latest: SessionViewState,
     event_count: u64,
@@ -260,7 +335,12 @@ fn process_host_event(
         return Ok(true);
     }
 
-    let message = format!("event: {event:?}");
+    let message = match event {
+        catan_core::gameplay::game::event::GameEvent::ActionRejected { reason, .. } => {
+            format!("rejected: {reason}")
+        }
+        event => format!("event: {event:?}"),
+    };
     match observer_event_count {
         Some(event_count) => {
             log::trace!(
@@ -324,9 +404,9 @@ fn handle_shutdown(ui: &mut CliUi, reason: String) -> Result<(), String> {
     Ok(())
 }
 
-fn send_child_error(stream: &mut UnixStream, message: String) -> Result<(), String> {
+fn send_child_error(link: &mut HostLink, message: String) -> Result<(), String> {
     log::warn!("{message}");
-    write_frame(stream, &CliToHost::Error { message })
+    link.send(&CliToHost::Error { message })
         .map_err(|err| format!("failed to send observer error: {err}"))
 }
 
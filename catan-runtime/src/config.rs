@@ -1,5 +1,11 @@
-use std::path::PathBuf;
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
+use catan_agents::greedy::GreedyWeights;
+use catan_core::gameplay::game::controller::{AgentErrorPolicy, TimeControl, TimeoutRule};
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -15,6 +21,57 @@ pub struct MatchConfig {
     pub limits: LimitsConfig,
     #[serde(default)]
     pub logging: LoggingConfig,
+    /// Address CLI players and observers join over TCP with
+    /// `cli-child --connect`; without it each gets a terminal on this
+    /// machine.
+    #[serde(default)]
+    pub listen: Option<SocketAddr>,
+    /// Lets a greedy bot take over the seats of players who resign or
+    /// forfeit, instead of ending the game.
+    #[serde(default)]
+    pub stand_in: bool,
+    /// What happens when a player's agent fails to decide, e.g. because its
+    /// terminal was closed.
+    #[serde(default)]
+    pub agent_errors: AgentErrorPolicy,
+    /// A chess clock per player; games are untimed without one.
+    #[serde(default)]
+    pub time_control: Option<TimeControlConfig>,
+}
+
+impl MatchConfig {
+    /// Makes the weight files given relative to the config file's directory
+    /// `config_dir` readable from anywhere.
+    pub fn resolve_paths(&mut self, config_dir: &Path) {
+        for player in &mut self.players {
+            if let PlayerConfig::Greedy {
+                weights: Some(WeightsConfig::File(path)),
+            } = player
+                && path.is_relative()
+            {
+                *path = config_dir.join(&*path);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct TimeControlConfig {
+    pub bank_secs: u64,
+    #[serde(default)]
+    pub increment_secs: u64,
+    #[serde(default)]
+    pub on_timeout: TimeoutRule,
+}
+
+impl From<TimeControlConfig> for TimeControl {
+    fn from(config: TimeControlConfig) -> Self {
+        Self {
+            bank: Duration::from_secs(config.bank_secs),
+            increment: Duration::from_secs(config.increment_secs),
+            on_timeout: config.on_timeout,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -22,10 +79,22 @@ pub struct MatchConfig {
 pub enum PlayerConfig {
     Cli,
     Lazy,
-    Greedy,
+    Greedy {
+        #[serde(default)]
+        weights: Option<WeightsConfig>,
+    },
     Random,
 }
 
+/// Greedy weights given inline or as a path to a JSON file, such as a genome
+/// saved by `catan-train tune`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum WeightsConfig {
+    File(PathBuf),
+    Inline(GreedyWeights),
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ObserverConfig {
@@ -108,7 +177,12 @@ fn default_logging_file_prefix() -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::{MatchConfig, ObserverConfig};
+    use std::{fs, time::Duration};
+
+    use catan_core::gameplay::game::controller::{TimeControl, TimeoutRule};
+
+    use super::{AgentErrorPolicy, MatchConfig, ObserverConfig, PlayerConfig, WeightsConfig};
+    use crate::host::{load_config, load_weights};
 
     #[test]
     fn parses_snapshot_observer_config() {
@@ -125,4 +199,116 @@ mod tests {
             [ObserverConfig::SnapshotObserver]
         ));
     }
+
+    #[test]
+    fn parses_greedy_weights_inline_and_from_file() {
+        let config: MatchConfig = serde_json::from_str(
+            r#"{
+              "players": [
+                { "kind": "greedy" },
+                { "kind": "greedy", "weights": { "river_robber_bonus": 1.5 } },
+                { "kind": "greedy", "weights": "genomes/best.json" }
+              ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.players.as_slice(),
+            [
+                PlayerConfig::Greedy { weights: None },
+                PlayerConfig::Greedy {
+                    weights: Some(WeightsConfig::Inline(weights))
+                },
+                PlayerConfig::Greedy {
+                    weights: Some(WeightsConfig::File(_))
+                },
+            ] if weights.river_robber_bonus == 1.5
+        ));
+        assert!(config.listen.is_none());
+    }
+
+    #[test]
+    fn parses_listen_address() {
+        let config: MatchConfig = serde_json::from_str(
+            r#"{
+              "players": [{ "kind": "cli" }, { "kind": "cli" }],
+              "listen": "0.0.0.0:7878"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.listen, Some("0.0.0.0:7878".parse().unwrap()));
+        assert!(!config.stand_in);
+    }
+
+    #[test]
+    fn parses_agent_error_policy() {
+        let config: MatchConfig = serde_json::from_str(
+            r#"{
+              "players": [{ "kind": "cli" }, { "kind": "greedy" }],
+              "stand_in": true,
+              "agent_errors": { "kind": "retry", "attempts": 3 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.agent_errors, AgentErrorPolicy::Retry { attempts: 3 });
+
+        let config: MatchConfig = serde_json::from_str(r#"{ "players": [] }"#).unwrap();
+        assert_eq!(config.agent_errors, AgentErrorPolicy::Interrupt);
+    }
+
+    #[test]
+    fn parses_time_control() {
+        let config: MatchConfig = serde_json::from_str(
+            r#"{
+              "players": [{ "kind": "cli" }, { "kind": "greedy" }],
+              "time_control": { "bank_secs": 600, "increment_secs": 10, "on_timeout": "forfeit" }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.time_control.map(TimeControl::from),
+            Some(TimeControl {
+                bank: Duration::from_secs(600),
+                increment: Duration::from_secs(10),
+                on_timeout: TimeoutRule::Forfeit,
+            })
+        );
+
+        let config: MatchConfig = serde_json::from_str(r#"{ "players": [] }"#).unwrap();
+        assert!(config.time_control.is_none());
+    }
+
+    #[test]
+    fn weight_files_are_relative_to_the_config_file() {
+        let dir = std::env::temp_dir().join(format!("rc-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("genomes")).unwrap();
+        fs::write(
+            dir.join("genomes/best.json"),
+            r#"{ "river_robber_bonus": 2.5 }"#,
+        )
+        .unwrap();
+        let config_path = dir.join("match.json");
+        fs::write(
+            &config_path,
+            r#"{ "players": [{ "kind": "greedy", "weights": "genomes/best.json" }] }"#,
+        )
+        .unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        let [
+            PlayerConfig::Greedy {
+                weights: Some(WeightsConfig::File(path)),
+            },
+        ] = config.players.as_slice()
+        else {
+            panic!("expected a greedy player with a weights file");
+        };
+        assert_eq!(path, &dir.join("genomes/best.json"));
+        assert_eq!(load_weights(path).unwrap().river_robber_bonus, 2.5);
+        fs::remove_dir_all(dir).unwrap();
+    }
 }
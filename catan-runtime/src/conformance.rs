@@ -0,0 +1,566 @@
+//! Protocol conformance suite for remote agents.
+//!
+//! Acts as a scripted host on a Unix socket. The agent under test connects
+//! like a CLI child would and gets the handshake, one decision request of
+//! every kind with crafted legal options, an oversized frame and finally a
+//! shutdown. Answers have to match the request kind, stay within the offered
+//! options and arrive within the time limit. An oversized frame breaks the
+//! stream, so the agent has to drop the connection. Dialing again is an
+//! optional check, agents that do are shut down on the second connection.
+
+use std::{
+    fs, io,
+    io::Write,
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+use catan_agents::remote_agent::{
+    CliRole, CliToHost, DecisionRequestEnvelope, DecisionRequestFrame, DecisionResponseFrame,
+    HOST_CAPABILITIES, HostToCli, LegalDecisionOptions, MAX_FRAME_LEN, UiModel, read_frame,
+    write_frame,
+};
+use catan_core::{
+    agent::action::{InitAction, PostDiceAction},
+    gameplay::{
+        game::{
+            event::GameEvent,
+            index::GameIndex,
+            init::GameInitializationState,
+            view::{ContextFactory, VisibilityConfig},
+        },
+        primitives::resource::{Resource, ResourceCollection},
+    },
+};
+use serde::Serialize;
+
+pub struct Check {
+    pub name: &'static str,
+    pub outcome: Result<(), String>,
+    /// Reported, but not needed to be conformant.
+    pub optional: bool,
+}
+
+enum Failure {
+    /// The agent answered, but wrongly.
+    Answer(String),
+    /// The stream can't be trusted anymore.
+    Connection(String),
+    Closed,
+}
+
+impl Failure {
+    fn message(self) -> String {
+        match self {
+            Self::Answer(message) | Self::Connection(message) => message,
+            Self::Closed => "agent closed the connection".to_owned(),
+        }
+    }
+}
+
+/// Waits for an agent on `socket` and runs the suite against it.
+pub fn run(socket: &Path, timeout: Duration) -> Result<(), String> {
+    if socket.exists() {
+        fs::remove_file(socket)
+            .map_err(|err| format!("failed to remove stale socket {}: {err}", socket.display()))?;
+    }
+    let listener = UnixListener::bind(socket)
+        .map_err(|err| format!("failed to bind socket {}: {err}", socket.display()))?;
+    println!("waiting for an agent on {}", socket.display());
+    // the agent may take its time to start, answers may not
+    let checks = run_suite(&listener, Duration::from_secs(60), timeout);
+    let _ = fs::remove_file(socket);
+
+    for check in &checks {
+        match &check.outcome {
+            Ok(()) => println!("ok   {}", check.name),
+            Err(reason) if check.optional => println!("skip {}: {reason}", check.name),
+            Err(reason) => println!("FAIL {}: {reason}", check.name),
+        }
+    }
+    let required = checks.iter().filter(|check| !check.optional);
+    let (passed, total) = required.fold((0, 0), |(passed, total), check| {
+        (passed + usize::from(check.outcome.is_ok()), total + 1)
+    });
+    println!("{passed}/{total} required checks passed");
+    if passed == total {
+        Ok(())
+    } else {
+        Err("agent is not conformant".to_owned())
+    }
+}
+
+pub(crate) fn run_suite(
+    listener: &UnixListener,
+    connect_timeout: Duration,
+    timeout: Duration,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut stream = match accept(listener, connect_timeout, timeout) {
+        Ok(stream) => stream,
+        Err(reason) => {
+            checks.push(Check {
+                name: "connect",
+                outcome: Err(reason),
+                optional: false,
+            });
+            return checks;
+        }
+    };
+
+    let handshake = handshake(&mut stream, timeout);
+    let connected = handshake.is_ok();
+    checks.push(Check {
+        name: "handshake",
+        outcome: handshake,
+        optional: false,
+    });
+    if !connected {
+        return checks;
+    }
+
+    let mut broken = None;
+    for request in requests() {
+        let name = request.kind();
+        let outcome = match &broken {
+            Some(reason) => Err(format!("skipped, {reason}")),
+            None => exchange(&mut stream, &request, timeout).map_err(|failure| match failure {
+                Failure::Answer(reason) => reason,
+                failure => {
+                    let reason = failure.message();
+                    broken = Some(reason.clone());
+                    reason
+                }
+            }),
+        };
+        checks.push(Check {
+            name,
+            outcome,
+            optional: false,
+        });
+    }
+    checks.push(Check {
+        name: "oversized_frame",
+        outcome: oversized_frame(&mut stream, timeout),
+        optional: false,
+    });
+    drop(stream);
+
+    let (reconnect, shutdown) = match reconnect(listener, timeout) {
+        Ok(mut stream) => (Ok(()), shutdown(&mut stream, timeout)),
+        Err(reason) => (
+            Err(reason),
+            Err("skipped, the agent did not reconnect".to_owned()),
+        ),
+    };
+    // the shutdown can only be checked on a second connection
+    let reconnected = reconnect.is_ok();
+    checks.push(Check {
+        name: "reconnect",
+        outcome: reconnect,
+        optional: true,
+    });
+    checks.push(Check {
+        name: "shutdown",
+        outcome: shutdown,
+        optional: !reconnected,
+    });
+    checks
+}
+
+fn accept(
+    listener: &UnixListener,
+    connect_timeout: Duration,
+    timeout: Duration,
+) -> Result<UnixStream, String> {
+    let deadline = Instant::now() + connect_timeout;
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| format!("failed to poll the socket: {err}"))?;
+    let stream = loop {
+        match listener.accept() {
+            Ok((stream, _)) => break stream,
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                if Instant::now() >= deadline {
+                    return Err(format!(
+                        "no agent connected within {} ms",
+                        connect_timeout.as_millis()
+                    ));
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(err) => return Err(format!("failed to accept the agent: {err}")),
+        }
+    };
+    stream
+        .set_nonblocking(false)
+        .and_then(|()| stream.set_read_timeout(Some(timeout)))
+        .map_err(|err| format!("failed to configure the connection: {err}"))?;
+    Ok(stream)
+}
+
+fn handshake(stream: &mut UnixStream, timeout: Duration) -> Result<(), String> {
+    send(
+        stream,
+        &HostToCli::hello(CliRole::Player { player_id: 0 }, None),
+    )
+    .map_err(Failure::message)?;
+    let reply = recv(stream, timeout).map_err(Failure::message)?;
+    match reply.negotiate(HOST_CAPABILITIES) {
+        Some(Ok(_)) => Ok(()),
+        Some(Err(reason)) => Err(reason),
+        None => Err(format!("expected hello or ready, got {reply:?}")),
+    }
+}
+
+fn send(stream: &mut UnixStream, msg: &HostToCli) -> Result<(), Failure> {
+    write_frame(stream, msg)
+        .map_err(|err| Failure::Connection(format!("failed to send a frame: {err}")))
+}
+
+/// Next frame that isn't a log line.
+fn recv(stream: &mut UnixStream, timeout: Duration) -> Result<CliToHost, Failure> {
+    loop {
+        match read_frame::<CliToHost>(stream) {
+            Ok(CliToHost::Log { .. }) => {}
+            Ok(msg) => return Ok(msg),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Err(Failure::Connection(format!(
+                    "no answer within {} ms",
+                    timeout.as_millis()
+                )));
+            }
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(Failure::Closed);
+            }
+            Err(err) => return Err(Failure::Connection(format!("unreadable answer: {err}"))),
+        }
+    }
+}
+
+/// Requests of every kind, on a fresh board seen by player 0.
+fn requests() -> Vec<DecisionRequestFrame> {
+    let state = GameInitializationState::default().finish();
+    let index = GameIndex::rebuild(&state);
+    let visibility = VisibilityConfig::default();
+    let factory = ContextFactory {
+        state: &state,
+        index: &index,
+        visibility: &visibility,
+    };
+    let context = factory.player_decision_context(0, None);
+    let view = UiModel::from_decision(&context);
+    let full = LegalDecisionOptions::from_context(&context, None);
+
+    // only part of what the rules allow is offered, an agent has to pick
+    // from the options it gets
+    let crafted = |request_id: u64, legal: LegalDecisionOptions| DecisionRequestEnvelope {
+        request_id,
+        view: view.clone(),
+        legal,
+    };
+    let robber_hexes = full.robber_hexes.iter().rev().take(2).copied().collect();
+    let mut drop_half = crafted(8, LegalDecisionOptions::default());
+    if let Some(private) = &mut drop_half.view.private {
+        private.resources = ResourceCollection::from((Resource::Brick, 4))
+            + ResourceCollection::from((Resource::Wood, 3))
+            + ResourceCollection::from((Resource::Ore, 2));
+    }
+
+    vec![
+        DecisionRequestFrame::InitStage(crafted(
+            0,
+            LegalDecisionOptions {
+                initial_placements: full.initial_placements.iter().step_by(7).copied().collect(),
+                ..LegalDecisionOptions::default()
+            },
+        )),
+        DecisionRequestFrame::InitAction(crafted(1, LegalDecisionOptions::default())),
+        DecisionRequestFrame::PostDice(crafted(2, full.clone())),
+        DecisionRequestFrame::PostDevCard(crafted(3, LegalDecisionOptions::default())),
+        DecisionRequestFrame::Regular(crafted(4, full.clone())),
+        DecisionRequestFrame::MoveRobbers(crafted(
+            5,
+            LegalDecisionOptions {
+                robber_hexes,
+                ..LegalDecisionOptions::default()
+            },
+        )),
+        DecisionRequestFrame::ChoosePlayerToRob(crafted(
+            6,
+            LegalDecisionOptions {
+                robber_pos: full.robber_hexes.first().copied(),
+                rob_targets: vec![2],
+                ..LegalDecisionOptions::default()
+            },
+        )),
+        DecisionRequestFrame::AnswerTrade(crafted(7, LegalDecisionOptions::default())),
+        DecisionRequestFrame::DropHalf(drop_half),
+    ]
+}
+
+fn exchange(
+    stream: &mut UnixStream,
+    request: &DecisionRequestFrame,
+    timeout: Duration,
+) -> Result<(), Failure> {
+    // events come and go without an answer
+    send(
+        stream,
+        &HostToCli::Event {
+            event: GameEvent::GameStarted,
+            view: request.envelope().view.clone(),
+        },
+    )?;
+    send(stream, &HostToCli::DecisionRequest(request.clone()))?;
+    match recv(stream, timeout)? {
+        CliToHost::DecisionResponse(response) => check_response(request, &response),
+        CliToHost::Error { message } => Err(Failure::Answer(format!(
+            "agent gave up on the request: {message}"
+        ))),
+        other => Err(Failure::Answer(format!(
+            "expected a decision response, got {other:?}"
+        ))),
+    }
+}
+
+/// Actions don't implement `PartialEq`, they are compared by their wire form.
+fn offered<T: Serialize>(choice: &T, options: &[T]) -> bool {
+    let choice = serde_json::to_value(choice).ok();
+    options
+        .iter()
+        .any(|option| serde_json::to_value(option).ok() == choice)
+}
+
+fn check_response(
+    request: &DecisionRequestFrame,
+    response: &DecisionResponseFrame,
+) -> Result<(), Failure> {
+    let legal = &request.envelope().legal;
+    let is_legal = match (request, response) {
+        (DecisionRequestFrame::InitStage(_), DecisionResponseFrame::InitStage(action)) => {
+            offered(action, &legal.initial_placements)
+        }
+        (DecisionRequestFrame::InitAction(_), DecisionResponseFrame::InitAction(action)) => {
+            match action {
+                InitAction::RollDice => true,
+                InitAction::UseDevCard(usage) => offered(usage, &legal.dev_card_usages),
+            }
+        }
+        (DecisionRequestFrame::PostDice(_), DecisionResponseFrame::PostDice(action)) => {
+            match action {
+                PostDiceAction::UseDevCard(usage) => offered(usage, &legal.dev_card_usages),
+                PostDiceAction::RegularAction(action) => offered(action, &legal.regular_actions),
+            }
+        }
+        (DecisionRequestFrame::PostDevCard(_), DecisionResponseFrame::PostDevCard(_))
+        | (DecisionRequestFrame::AnswerTrade(_), DecisionResponseFrame::AnswerTrade(_)) => true,
+        (DecisionRequestFrame::Regular(_), DecisionResponseFrame::Regular(action)) => {
+            offered(action, &legal.regular_actions)
+        }
+        (DecisionRequestFrame::MoveRobbers(_), DecisionResponseFrame::MoveRobbers(action)) => {
+            offered(&action.0, &legal.robber_hexes)
+        }
+        (
+            DecisionRequestFrame::ChoosePlayerToRob(_),
+            DecisionResponseFrame::ChoosePlayerToRob(action),
+        ) => legal.rob_targets.contains(&action.0),
+        (DecisionRequestFrame::DropHalf(envelope), DecisionResponseFrame::DropHalf(action)) => {
+            let held = envelope
+                .view
+                .private
+                .as_ref()
+                .map(|private| private.resources)
+                .unwrap_or_default();
+            let required = held.total() / 2;
+            if action.0.total() != required {
+                return Err(Failure::Answer(format!(
+                    "dropped {} cards, must drop {required}",
+                    action.0.total()
+                )));
+            }
+            held.has_enough(&action.0)
+        }
+        _ => {
+            return Err(Failure::Answer(format!(
+                "answered a {} request with {response:?}",
+                request.kind()
+            )));
+        }
+    };
+    if is_legal {
+        Ok(())
+    } else {
+        Err(Failure::Answer(format!(
+            "answer {response:?} was not among the legal options"
+        )))
+    }
+}
+
+/// Announces a frame above [`MAX_FRAME_LEN`]; the agent has to refuse it
+/// instead of waiting for the payload.
+fn oversized_frame(stream: &mut UnixStream, timeout: Duration) -> Result<(), String> {
+    let header = (MAX_FRAME_LEN as u32 + 1).to_be_bytes();
+    if let Err(err) = stream.write_all(&header) {
+        // an agent that went away on a broken earlier check can't refuse
+        return Err(format!("failed to send the frame: {err}"));
+    }
+    match recv(stream, timeout) {
+        Ok(CliToHost::Error { .. }) => Ok(()),
+        Ok(other) => Err(format!("expected the connection to drop, got {other:?}")),
+        Err(Failure::Closed) => Ok(()),
+        Err(failure) => Err(failure.message()),
+    }
+}
+
+fn reconnect(listener: &UnixListener, timeout: Duration) -> Result<UnixStream, String> {
+    // reconnecting takes a backoff, give it a few answer times
+    let mut stream = accept(listener, timeout * 5, timeout)
+        .map_err(|reason| format!("agent did not reconnect: {reason}"))?;
+    handshake(&mut stream, timeout)?;
+    Ok(stream)
+}
+
+fn shutdown(stream: &mut UnixStream, timeout: Duration) -> Result<(), String> {
+    send(
+        stream,
+        &HostToCli::Shutdown {
+            reason: "conformance suite finished".to_owned(),
+        },
+    )
+    .map_err(Failure::message)?;
+    match recv(stream, timeout) {
+        Err(Failure::Closed) => Ok(()),
+        Ok(other) => Err(format!("expected the connection to close, got {other:?}")),
+        Err(failure) => Err(format!(
+            "connection stayed open after shutdown: {}",
+            failure.message()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use catan_agents::remote_agent::DecisionRequestEnvelope;
+    use catan_bot::{Bot, ClientOptions, Endpoint};
+    use catan_core::agent::action::DropHalfAction;
+
+    use super::*;
+
+    fn unique_socket_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rc-conformance-{}-{}.sock",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    fn run_against(bot: impl Bot + Send + 'static) -> Vec<Check> {
+        let (checks, agent) = run_with_reconnects(bot, 3);
+        agent.unwrap();
+        checks
+    }
+
+    fn run_with_reconnects(
+        bot: impl Bot + Send + 'static,
+        reconnect_attempts: u32,
+    ) -> (Vec<Check>, Result<(), catan_bot::ClientError>) {
+        let path = unique_socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let endpoint = Endpoint::Unix(path.clone());
+        let agent = thread::spawn(move || {
+            let mut bot = bot;
+            let options = ClientOptions {
+                reconnect_attempts,
+                reconnect_delay: Duration::from_millis(10),
+            };
+            catan_bot::run_with_options(&endpoint, &mut bot, options)
+        });
+        let checks = run_suite(
+            &listener,
+            Duration::from_secs(5),
+            Duration::from_millis(500),
+        );
+        let agent = agent.join().unwrap();
+        fs::remove_file(path).unwrap();
+        (checks, agent)
+    }
+
+    fn failed(checks: &[Check]) -> Vec<&'static str> {
+        checks
+            .iter()
+            .filter(|check| check.outcome.is_err())
+            .map(|check| check.name)
+            .collect()
+    }
+
+    struct Defaults;
+
+    impl Bot for Defaults {}
+
+    struct Careless;
+
+    impl Bot for Careless {
+        fn drop_half(&mut self, request: &DecisionRequestEnvelope) -> DropHalfAction {
+            let held = request.view.private.as_ref().unwrap().resources;
+            DropHalfAction((Resource::Brick, held[Resource::Brick] + 1).into())
+        }
+    }
+
+    #[test]
+    fn sdk_bots_pass_the_suite() {
+        let checks = run_against(Defaults);
+        assert_eq!(failed(&checks), Vec::<&str>::new());
+        assert_eq!(checks.len(), 13);
+    }
+
+    #[test]
+    fn agents_that_do_not_reconnect_only_skip_optional_checks() {
+        let (checks, agent) = run_with_reconnects(Defaults, 0);
+        assert!(agent.is_err());
+        assert_eq!(failed(&checks), ["reconnect", "shutdown"]);
+        assert!(
+            checks
+                .iter()
+                .all(|check| check.outcome.is_ok() || check.optional)
+        );
+    }
+
+    #[test]
+    fn illegal_answers_fail_their_check() {
+        let checks = run_against(Careless);
+        assert_eq!(failed(&checks), ["drop_half"]);
+        let drop_half = checks.iter().find(|check| check.name == "drop_half");
+        assert_eq!(
+            drop_half.unwrap().outcome.as_ref().unwrap_err(),
+            "dropped 5 cards, must drop 4"
+        );
+    }
+
+    #[test]
+    fn silent_agents_time_out() {
+        let path = unique_socket_path();
+        let listener = UnixListener::bind(&path).unwrap();
+        let client = UnixStream::connect(&path).unwrap();
+        let checks = run_suite(&listener, Duration::from_secs(1), Duration::from_millis(50));
+        drop(client);
+        assert_eq!(checks[0].name, "handshake");
+        assert_eq!(
+            checks[0].outcome.as_ref().unwrap_err(),
+            "no answer within 50 ms"
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
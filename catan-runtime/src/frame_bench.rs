@@ -0,0 +1,207 @@
+//! Compares the frame encodings of the remote agent protocol on the frames of
+//! real games, with full views and with deltas: bytes and encode/decode time
+//! per frame.
+
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use catan_agents::{
+    greedy::GreedyAgent,
+    remote_agent::{CliRole, Encoding, HostToCli, UiModel, ViewEncoder},
+};
+use catan_core::{
+    agent::Agent,
+    gameplay::game::{
+        controller::{GameController, RunOptions},
+        event::{GameEvent, GameObserver, ObserverKind, ObserverNotificationContext},
+        init::GameInitializationState,
+    },
+    math::dice::RandomDiceRoller,
+};
+
+const ENCODINGS: [Encoding; 2] = [Encoding::Json, Encoding::MessagePack];
+/// Turns a recorded game is cut off after.
+const MAX_TURNS: u64 = 200;
+
+pub fn run(games: u64, seed: u64) -> Result<(), String> {
+    let mut player_frames = Vec::new();
+    let mut snapshot_frames = Vec::new();
+    for game in 0..games {
+        let (player, snapshot) = record_game(seed.wrapping_add(game), MAX_TURNS);
+        player_frames.extend(player);
+        snapshot_frames.extend(snapshot);
+    }
+
+    let player_diffs = with_diffs(&player_frames);
+    let snapshot_diffs = with_diffs(&snapshot_frames);
+
+    println!(
+        "{:<15} {:<13} {:>7} {:>12} {:>11} {:>11}",
+        "frames", "encoding", "count", "bytes/frame", "encode µs", "decode µs"
+    );
+    for (label, frames) in [
+        ("player", &player_frames),
+        ("player diffs", &player_diffs),
+        ("snapshot", &snapshot_frames),
+        ("snapshot diffs", &snapshot_diffs),
+    ] {
+        for encoding in ENCODINGS {
+            let stats = measure(frames, encoding)?;
+            println!(
+                "{label:<15} {:<13} {:>7} {:>12} {:>11.1} {:>11.1}",
+                format!("{encoding:?}"),
+                frames.len(),
+                stats.bytes / frames.len().max(1),
+                per_frame_micros(stats.encode, frames.len()),
+                per_frame_micros(stats.decode, frames.len()),
+            );
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct FrameStats {
+    pub(crate) bytes: usize,
+    pub(crate) encode: Duration,
+    pub(crate) decode: Duration,
+}
+
+/// Encodes all `frames`, then decodes them again.
+pub(crate) fn measure(frames: &[HostToCli], encoding: Encoding) -> Result<FrameStats, String> {
+    let started = Instant::now();
+    let payloads = frames
+        .iter()
+        .map(|frame| encoding.encode(frame))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("failed to encode a frame as {encoding:?}: {err}"))?;
+    let encode = started.elapsed();
+
+    let started = Instant::now();
+    for payload in &payloads {
+        encoding
+            .decode::<HostToCli>(payload)
+            .map_err(|err| format!("failed to decode a {encoding:?} frame: {err}"))?;
+    }
+    let decode = started.elapsed();
+
+    Ok(FrameStats {
+        bytes: payloads.iter().map(Vec::len).sum(),
+        encode,
+        decode,
+    })
+}
+
+/// The frames a client that negotiated diffs gets instead. The first frame of
+/// a game follows the last one of the previous game and ends up a keyframe,
+/// as the builds of that game are gone.
+fn with_diffs(frames: &[HostToCli]) -> Vec<HostToCli> {
+    let mut views = ViewEncoder::default();
+    frames
+        .iter()
+        .cloned()
+        .map(|frame| match frame {
+            HostToCli::Event { event, view } => views.encode(event, view),
+            other => other,
+        })
+        .collect()
+}
+
+fn per_frame_micros(total: Duration, frames: usize) -> f64 {
+    total.as_secs_f64() * 1e6 / frames.max(1) as f64
+}
+
+/// Event frames one game sends to a player's observer and to a snapshot
+/// observer.
+pub(crate) fn record_game(seed: u64, max_turns: u64) -> (Vec<HostToCli>, Vec<HostToCli>) {
+    let player = Rc::new(RefCell::new(Vec::new()));
+    let snapshot = Rc::new(RefCell::new(Vec::new()));
+    let mut observers = vec![
+        Box::new(RecordingObserver {
+            role: CliRole::PlayerObserver { player_id: 0 },
+            frames: player.clone(),
+        }) as Box<dyn GameObserver>,
+        Box::new(RecordingObserver {
+            role: CliRole::SnapshotObserver,
+            frames: snapshot.clone(),
+        }),
+    ];
+
+    let init = GameInitializationState::default();
+    let mut agents = (0..init.board.n_players)
+        .map(|id| Box::new(GreedyAgent::new(id)) as Box<dyn Agent>)
+        .collect::<Vec<_>>();
+    let state = GameController::init_with_observers(init, &mut agents, &mut observers)
+        .expect("greedy agents always decide");
+    let mut controller = GameController::new(state, agents);
+    for observer in observers {
+        controller.add_observer(observer);
+    }
+    controller.run_with_options(
+        &mut RandomDiceRoller::with_seed(seed),
+        RunOptions {
+            max_turns: Some(max_turns),
+            ..RunOptions::default()
+        },
+    );
+    drop(controller);
+
+    (player.take(), snapshot.take())
+}
+
+struct RecordingObserver {
+    role: CliRole,
+    frames: Rc<RefCell<Vec<HostToCli>>>,
+}
+
+impl GameObserver for RecordingObserver {
+    fn kind(&self) -> ObserverKind {
+        self.role
+            .observer_kind()
+            .expect("recording observers have observer roles")
+    }
+
+    fn on_event(&mut self, event: &GameEvent, context: ObserverNotificationContext<'_>) {
+        let view = UiModel::from_observer(context, self.role.includes_exact_snapshot_state());
+        self.frames.borrow_mut().push(HostToCli::Event {
+            event: event.clone(),
+            view,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_pack_frames_round_trip_smaller_than_json() {
+        let (player, snapshot) = record_game(7, 10);
+        assert!(!player.is_empty());
+        assert_eq!(player.len(), snapshot.len());
+
+        for frames in [&player, &snapshot] {
+            let json = measure(frames, Encoding::Json).unwrap();
+            let binary = measure(frames, Encoding::MessagePack).unwrap();
+            assert!(
+                binary.bytes < json.bytes,
+                "{} bytes of MessagePack, {} bytes of JSON",
+                binary.bytes,
+                json.bytes
+            );
+        }
+
+        // decoding doesn't lose anything JSON would have kept
+        let frame = snapshot.last().unwrap();
+        let decoded = Encoding::MessagePack
+            .decode::<HostToCli>(&Encoding::MessagePack.encode(frame).unwrap())
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::to_value(frame).unwrap()
+        );
+    }
+}
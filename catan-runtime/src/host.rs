@@ -1,34 +1,52 @@
 use std::{
-    fs,
+    collections::VecDeque,
+    fs, io,
+    net::{SocketAddr, TcpListener, TcpStream},
     os::unix::net::{UnixListener, UnixStream},
     path::{Path, PathBuf},
     process::{Command, Stdio},
-    time::{SystemTime, UNIX_EPOCH},
+    sync::mpsc,
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use catan_agents::{
-    greedy::GreedyAgent,
+    greedy::{GreedyAgent, GreedyWeights},
     lazy::LazyAgent,
     random::RandomAgent,
-    remote_agent::{CliRole, CliToHost, RemoteCliAgent, RemoteCliObserver, read_frame},
+    remote_agent::{
+        CliRole, CliStream, CliToHost, HostToCli, RemoteCliAgent, RemoteCliObserver, read_frame,
+        write_frame,
+    },
 };
 use catan_core::{
     agent::Agent,
     gameplay::game::{
-        controller::{GameController, RunOptions},
+        controller::{GameController, RunOptions, StandIn},
         event::{GameObserver, ObserverKind},
         init::GameInitializationState,
     },
     math::dice::{DiceRoller, RandomDiceRoller},
 };
 
-use crate::config::{DiceConfig, FieldConfig, MatchConfig, ObserverConfig, PlayerConfig};
+use crate::config::{
+    DiceConfig, FieldConfig, MatchConfig, ObserverConfig, PlayerConfig, WeightsConfig,
+};
 
 pub fn load_config(path: &Path) -> Result<MatchConfig, String> {
     let raw = fs::read_to_string(path)
         .map_err(|err| format!("failed to read config {}: {err}", path.display()))?;
+    let mut config: MatchConfig = serde_json::from_str(&raw)
+        .map_err(|err| format!("failed to parse config {}: {err}", path.display()))?;
+    config.resolve_paths(path.parent().unwrap_or(Path::new("")));
+    Ok(config)
+}
+
+pub(crate) fn load_weights(path: &Path) -> Result<GreedyWeights, String> {
+    let raw = fs::read_to_string(path)
+        .map_err(|err| format!("failed to read weights {}: {err}", path.display()))?;
     serde_json::from_str(&raw)
-        .map_err(|err| format!("failed to parse config {}: {err}", path.display()))
+        .map_err(|err| format!("failed to parse weights {}: {err}", path.display()))
 }
 
 pub fn run_match(config: MatchConfig) -> Result<(), String> {
@@ -36,39 +54,72 @@ pub fn run_match(config: MatchConfig) -> Result<(), String> {
         return Err("config must contain at least one player".to_owned());
     }
 
-    let exe =
-        std::env::current_exe().map_err(|err| format!("failed to find current exe: {err}"))?;
-    let agents = build_agents(&config.players, &exe)?;
-    let mut observers = build_observers(&config.observers, &exe)?;
+    let mut clients = match config.listen {
+        Some(addr) => CliClients::Joined(accept_remote_clients(addr, cli_seats(&config))?),
+        None => CliClients::Terminals {
+            exe: std::env::current_exe()
+                .map_err(|err| format!("failed to find current exe: {err}"))?,
+        },
+    };
+    let agents = build_agents(&config.players, &mut clients)?;
+    let mut observers = build_observers(&config.observers, &mut clients)?;
     let mut dice = build_dice(&config.dice);
     let init_state = build_initial_state(&config.field);
     let mut agents = agents;
-    let state = GameController::init_with_observers(init_state, &mut agents, &mut observers);
+    let options = RunOptions {
+        max_turns: config.limits.max_turns,
+        max_invalid_actions: config.limits.max_invalid_actions,
+        agent_errors: config.agent_errors,
+        time_control: config.time_control.map(Into::into),
+    };
+    let mut stand_in = config
+        .stand_in
+        .then(|| Box::new(|id| Box::new(GreedyAgent::new(id)) as Box<dyn Agent>) as StandIn);
+    let state = match GameController::init_with_options(
+        init_state,
+        &mut agents,
+        &mut observers,
+        options,
+        stand_in.as_mut(),
+    ) {
+        Ok(state) => state,
+        Err(result) => {
+            log::info!("match result: {result:?}");
+            return Ok(());
+        }
+    };
     let mut controller = GameController::new(state, agents);
     for observer in observers {
         controller.add_observer(observer);
     }
+    if let Some(stand_in) = stand_in {
+        controller.set_stand_in(stand_in);
+    }
 
-    let result = controller.run_with_options(
-        dice.as_mut(),
-        RunOptions {
-            max_turns: config.limits.max_turns,
-            max_invalid_actions: config.limits.max_invalid_actions,
-        },
-    );
+    let result = controller.run_with_options(dice.as_mut(), options);
     log::info!("match result: {result:?}");
     Ok(())
 }
 
-fn build_agents(players: &[PlayerConfig], exe: &Path) -> Result<Vec<Box<dyn Agent>>, String> {
+fn build_agents(
+    players: &[PlayerConfig],
+    clients: &mut CliClients,
+) -> Result<Vec<Box<dyn Agent>>, String> {
     players
         .iter()
         .enumerate()
         .map(|(id, player)| match player {
             PlayerConfig::Lazy => Ok(Box::new(LazyAgent::new(id)) as Box<dyn Agent>),
-            PlayerConfig::Greedy => Ok(Box::new(GreedyAgent::new(id)) as Box<dyn Agent>),
+            PlayerConfig::Greedy { weights } => {
+                let weights = match weights {
+                    None => GreedyWeights::default(),
+                    Some(WeightsConfig::Inline(weights)) => *weights,
+                    Some(WeightsConfig::File(path)) => load_weights(path)?,
+                };
+                Ok(Box::new(GreedyAgent::with_weights(id, weights)) as Box<dyn Agent>)
+            }
             PlayerConfig::Cli => {
-                let stream = spawn_cli_child(exe, &CliChildSpec::player(id))?;
+                let stream = clients.connect(&CliChildSpec::player(id))?;
                 let agent = RemoteCliAgent::new(id, stream)
                     .map_err(|err| format!("failed to initialize remote CLI player: {err}"))?;
                 Ok(Box::new(agent) as Box<dyn Agent>)
@@ -80,7 +131,7 @@ fn build_agents(players: &[PlayerConfig], exe: &Path) -> Result<Vec<Box<dyn Agen
 
 fn build_observers(
     observers: &[ObserverConfig],
-    exe: &Path,
+    clients: &mut CliClients,
 ) -> Result<Vec<Box<dyn GameObserver>>, String> {
     observers
         .iter()
@@ -89,7 +140,7 @@ fn build_observers(
             let _kind = spec
                 .observer_kind
                 .ok_or_else(|| format!("{} is not an observer role", spec.label))?;
-            let stream = spawn_cli_child(exe, &spec)?;
+            let stream = clients.connect(&spec)?;
             let observer = RemoteCliObserver::new_with_role(spec.role.clone(), stream)
                 .map_err(|err| format!("failed to initialize remote CLI observer: {err}"))?;
             Ok(Box::new(observer) as Box<dyn GameObserver>)
@@ -151,6 +202,135 @@ impl CliChildSpec {
     }
 }
 
+/// Where the CLI seats of a match get their clients from.
+enum CliClients {
+    /// A terminal spawned on this machine for every seat.
+    Terminals { exe: PathBuf },
+    /// Clients that joined over TCP, in seat order.
+    Joined(VecDeque<TcpStream>),
+}
+
+impl CliClients {
+    fn connect(&mut self, spec: &CliChildSpec) -> Result<CliStream, String> {
+        match self {
+            Self::Terminals { exe } => spawn_cli_child(exe, spec).map(CliStream::from),
+            Self::Joined(streams) => streams
+                .pop_front()
+                .map(CliStream::from)
+                .ok_or_else(|| format!("no client joined the {} seat", spec.label)),
+        }
+    }
+}
+
+/// How long a fresh connection may take to name its seat.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A CLI seat remote clients take with its join token.
+#[derive(Debug, Clone)]
+struct RemoteSeat {
+    spec: CliChildSpec,
+    token: String,
+}
+
+/// CLI seats of `config` in the order agents and observers are built.
+fn cli_seats(config: &MatchConfig) -> Vec<RemoteSeat> {
+    let players = config
+        .players
+        .iter()
+        .enumerate()
+        .filter(|(_, player)| matches!(player, PlayerConfig::Cli))
+        .map(|(id, _)| CliChildSpec::player(id));
+    let observers = config.observers.iter().map(CliChildSpec::observer);
+    players
+        .chain(observers)
+        .map(|spec| RemoteSeat {
+            spec,
+            token: format!("{:016x}", rand::random::<u64>()),
+        })
+        .collect()
+}
+
+fn accept_remote_clients(
+    addr: SocketAddr,
+    seats: Vec<RemoteSeat>,
+) -> Result<VecDeque<TcpStream>, String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|err| format!("failed to listen on {addr}: {err}"))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|err| format!("failed to read listen address: {err}"))?;
+    log::info!(
+        "waiting for {} CLI clients on {addr}, join tokens are on stdout",
+        seats.len()
+    );
+    for seat in &seats {
+        println!("{:?} joins with --token {}", seat.spec.role, seat.token);
+    }
+    accept_clients(&listener, &seats).map(VecDeque::from)
+}
+
+/// Accepts connections until a client joined every seat and returns their
+/// streams in seat order. Join frames are read on a thread per connection,
+/// so a slow client doesn't hold up the others. Clients with an unknown
+/// token or one of a seat that is already taken are told so and turned away.
+fn accept_clients(listener: &TcpListener, seats: &[RemoteSeat]) -> Result<Vec<TcpStream>, String> {
+    let mut joined = seats
+        .iter()
+        .map(|_| None)
+        .collect::<Vec<Option<TcpStream>>>();
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| format!("failed to poll for CLI clients: {err}"))?;
+    let (joins, arrived) = mpsc::channel();
+    while joined.iter().any(Option::is_none) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let joins = joins.clone();
+                thread::spawn(move || joins.send((peer, read_join(stream))));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            Err(err) => return Err(format!("failed to accept CLI client: {err}")),
+        }
+        let Ok((peer, join)) = arrived.recv_timeout(Duration::from_millis(10)) else {
+            continue;
+        };
+        let (mut stream, token) = match join {
+            Ok(join) => join,
+            Err(err) => {
+                log::warn!("dropped CLI client {peer}: {err}");
+                continue;
+            }
+        };
+        let reason = match seats.iter().position(|seat| seat.token == token) {
+            Some(index) if joined[index].is_none() => {
+                log::info!("{peer} joined as {:?}", seats[index].spec.role);
+                joined[index] = Some(stream);
+                continue;
+            }
+            Some(index) => format!("{:?} is already taken", seats[index].spec.role),
+            None => "unknown join token".to_owned(),
+        };
+        log::warn!("turned away CLI client {peer}: {reason}");
+        let _ = write_frame(&mut stream, &HostToCli::Shutdown { reason });
+    }
+    Ok(joined.into_iter().flatten().collect())
+}
+
+fn read_join(mut stream: TcpStream) -> io::Result<(TcpStream, String)> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(JOIN_TIMEOUT))?;
+    let frame = read_frame::<CliToHost>(&mut stream)?;
+    stream.set_read_timeout(None)?;
+    stream.set_nodelay(true)?;
+    match frame {
+        CliToHost::Join { token } => Ok((stream, token)),
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("expected join, got {other:?}"),
+        )),
+    }
+}
+
 fn spawn_cli_child(exe: &Path, spec: &CliChildSpec) -> Result<UnixStream, String> {
     let socket_path = unique_socket_path(spec, "game");
     let log_socket_path = unique_socket_path(spec, "log");
@@ -198,7 +378,7 @@ fn unique_socket_path(spec: &CliChildSpec, channel: &str) -> PathBuf {
 }
 
 fn spawn_child_log_reader(role: String, mut stream: UnixStream) {
-    std::thread::spawn(move || {
+    thread::spawn(move || {
         loop {
             match read_frame::<CliToHost>(&mut stream) {
                 Ok(CliToHost::Log {
@@ -345,14 +525,51 @@ fn apple_quote(value: &str) -> String {
 
 #[cfg(test)]
 mod tests {
-    use catan_agents::remote_agent::CliRole;
+    use std::{
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    use catan_agents::remote_agent::{
+        Capability, CliRole, CliToHost, HostToCli, RemoteCliAgent, RemoteCliObserver, read_frame,
+        write_frame,
+    };
     use catan_core::gameplay::game::event::ObserverKind;
 
     use crate::{
         config::ObserverConfig,
-        host::{CliChildSpec, unique_socket_path},
+        host::{CliChildSpec, RemoteSeat, accept_clients, unique_socket_path},
     };
 
+    /// Joins with `token` and returns the role of the hello, or the reason
+    /// the host turned the client away.
+    fn join(addr: std::net::SocketAddr, token: &str) -> Result<String, String> {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write_frame(
+            &mut stream,
+            &CliToHost::Join {
+                token: token.to_owned(),
+            },
+        )
+        .unwrap();
+        match read_frame::<HostToCli>(&mut stream).unwrap() {
+            HostToCli::Hello { role, version, .. } => {
+                write_frame(
+                    &mut stream,
+                    &CliToHost::answer_hello(version, vec![Capability::Trades]),
+                )
+                .unwrap();
+                let HostToCli::Shutdown { .. } = read_frame::<HostToCli>(&mut stream).unwrap()
+                else {
+                    panic!("expected the host to shut down");
+                };
+                Ok(format!("{role:?}"))
+            }
+            HostToCli::Shutdown { reason } => Err(reason),
+            other => panic!("unexpected frame {other:?}"),
+        }
+    }
+
     #[test]
     fn snapshot_observer_config_maps_to_snapshot_role_and_omniscient_kind() {
         let spec = CliChildSpec::observer(&ObserverConfig::SnapshotObserver);
@@ -377,4 +594,39 @@ mod tests {
         assert!(game_name.len() < 104);
         assert!(log_name.len() < 104);
     }
+
+    #[test]
+    fn remote_clients_take_the_seats_of_their_join_tokens() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seats = [
+            RemoteSeat {
+                spec: CliChildSpec::player(1),
+                token: "player-token".to_owned(),
+            },
+            RemoteSeat {
+                spec: CliChildSpec::observer(&ObserverConfig::CliSpectator),
+                token: "spectator-token".to_owned(),
+            },
+        ];
+
+        let host = thread::spawn(move || {
+            let streams = accept_clients(&listener, &seats).unwrap();
+            let [player, spectator] = <[_; 2]>::try_from(streams).unwrap();
+            drop(RemoteCliAgent::new(1, player).unwrap());
+            drop(RemoteCliObserver::new_with_role(CliRole::Spectator, spectator).unwrap());
+        });
+        // a client that never names its seat doesn't hold up the others
+        let silent = TcpStream::connect(addr).unwrap();
+        // a stranger and the spectator show up before the player
+        assert_eq!(join(addr, "guess"), Err("unknown join token".to_owned()));
+        let spectator = thread::spawn(move || join(addr, "spectator-token"));
+        thread::sleep(std::time::Duration::from_millis(50));
+        let player = join(addr, "player-token");
+
+        host.join().unwrap();
+        drop(silent);
+        assert_eq!(player, Ok("Player { player_id: 1 }".to_owned()));
+        assert_eq!(spectator.join().unwrap(), Ok("Spectator".to_owned()));
+    }
 }
@@ -1,12 +1,38 @@
 mod cli_child;
 mod config;
+mod conformance;
+mod frame_bench;
 mod host;
 mod logging;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 fn main() {
     let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).map(String::as_str) == Some("cli-child")
+        && let Some(url) = arg_value(&args, "--url")
+    {
+        if let Err(err) = cli_child::run_server(&url) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("cli-child")
+        && let Some(addr) = arg_value(&args, "--connect")
+    {
+        let token = arg_value(&args, "--token").unwrap_or_else(|| {
+            eprintln!("usage: catan-runtime cli-child --connect <host:port> --token <join token>");
+            std::process::exit(2);
+        });
+        if let Err(err) = cli_child::run_remote(&addr, &token) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     if args.get(1).map(String::as_str) == Some("cli-child") {
         let socket = arg_value(&args, "--socket").unwrap_or_else(|| {
             eprintln!("missing --socket");
@@ -25,6 +51,31 @@ fn main() {
         return;
     }
 
+    if args.get(1).map(String::as_str) == Some("conformance") {
+        let socket = arg_value(&args, "--socket").unwrap_or_else(|| {
+            eprintln!("usage: catan-runtime conformance --socket <path> [--timeout-ms <n>]");
+            std::process::exit(2);
+        });
+        let timeout_ms = numeric_arg(&args, "--timeout-ms", 5000);
+        if let Err(err) =
+            conformance::run(&PathBuf::from(socket), Duration::from_millis(timeout_ms))
+        {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("frame-bench") {
+        let games = numeric_arg(&args, "--games", 3);
+        let seed = numeric_arg(&args, "--seed", 0);
+        if let Err(err) = frame_bench::run(games, seed) {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let config_path = args
         .get(1)
         .cloned()
@@ -54,3 +105,14 @@ fn arg_value(args: &[String], name: &str) -> Option<String> {
     args.windows(2)
         .find_map(|window| (window[0] == name).then(|| window[1].clone()))
 }
+
+fn numeric_arg(args: &[String], name: &str, default: u64) -> u64 {
+    match arg_value(args, name).map(|raw| raw.parse::<u64>()) {
+        None => default,
+        Some(Ok(value)) => value,
+        Some(Err(err)) => {
+            eprintln!("invalid {name}: {err}");
+            std::process::exit(2);
+        }
+    }
+}
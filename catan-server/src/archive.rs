@@ -0,0 +1,413 @@
+//! Archive of finished server games.
+//!
+//! Every game is stored as one JSON file holding its room config, game seed,
+//! initial state and omniscient event log, enough to replay it. The summaries are read
+//! back when the server starts, so the history survives restarts.
+
+use std::{
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use catan_core::gameplay::{
+    game::{
+        controller::GameResult,
+        event::{GameEndPlayerStats, GameEvent},
+        state::GameState,
+    },
+    primitives::player::PlayerId,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{game::FinishedGame, lobby::RoomConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerKind {
+    Human,
+    Greedy,
+    Random,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedPlayer {
+    pub seat: PlayerId,
+    pub kind: PlayerKind,
+    /// Name the player joined with, if any.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GameOutcome {
+    Won {
+        winner_id: PlayerId,
+    },
+    Resigned {
+        player_id: PlayerId,
+        winner_id: PlayerId,
+    },
+    Forfeited {
+        player_id: PlayerId,
+        winner_id: PlayerId,
+    },
+    Interrupted {
+        reason: String,
+    },
+    LimitReached {
+        turns: u64,
+    },
+}
+
+impl From<GameResult> for GameOutcome {
+    fn from(value: GameResult) -> Self {
+        match value {
+            GameResult::Win(winner_id) => Self::Won { winner_id },
+            GameResult::Resigned { player_id, winner } => Self::Resigned {
+                player_id,
+                winner_id: winner,
+            },
+            GameResult::Forfeited { player_id, winner } => Self::Forfeited {
+                player_id,
+                winner_id: winner,
+            },
+            GameResult::Interrupted { reason } => Self::Interrupted { reason },
+            GameResult::LimitReached { turns } => Self::LimitReached { turns },
+        }
+    }
+}
+
+/// What a room knows about its game when it starts.
+pub struct GameHeader {
+    pub config: RoomConfig,
+    pub players: Vec<ArchivedPlayer>,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: String,
+    pub config: RoomConfig,
+    pub players: Vec<ArchivedPlayer>,
+    pub seed: u64,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub outcome: GameOutcome,
+    /// Final stats, missing when the game didn't end with a winner.
+    pub stats: Option<Vec<GameEndPlayerStats>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedGame {
+    #[serde(flatten)]
+    pub summary: GameSummary,
+    pub initial_state: GameState,
+    pub events: Vec<GameEvent>,
+}
+
+impl ArchivedGame {
+    pub fn new(header: GameHeader, game: FinishedGame) -> Self {
+        let finished_at = Utc::now();
+        let stats = game.events.iter().rev().find_map(|event| match event {
+            GameEvent::GameEnded { stats, .. } => Some(stats.clone()),
+            _ => None,
+        });
+        Self {
+            summary: GameSummary {
+                id: format!(
+                    "{}-{:08x}",
+                    finished_at.format("%Y%m%d-%H%M%S"),
+                    rand::random::<u32>()
+                ),
+                config: header.config,
+                players: header.players,
+                seed: game.seed,
+                started_at: header.started_at,
+                finished_at,
+                outcome: game.result.into(),
+                stats,
+            },
+            initial_state: game.initial_state,
+            events: game.events,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArchiveQuery {
+    /// Name of a player who took part.
+    pub player: Option<String>,
+    /// First day, in UTC, the game may have finished on.
+    pub from: Option<NaiveDate>,
+    /// Last day, in UTC, the game may have finished on.
+    pub to: Option<NaiveDate>,
+}
+
+impl ArchiveQuery {
+    fn matches(&self, game: &GameSummary) -> bool {
+        let day = game.finished_at.date_naive();
+        self.player.as_ref().is_none_or(|name| {
+            game.players
+                .iter()
+                .any(|player| player.name.as_ref() == Some(name))
+        }) && self.from.is_none_or(|from| from <= day)
+            && self.to.is_none_or(|to| day <= to)
+    }
+}
+
+/// Shared handle to the archive directory.
+#[derive(Clone)]
+pub struct Archive {
+    dir: PathBuf,
+    /// Summaries of the stored games, oldest first.
+    index: Arc<Mutex<Vec<GameSummary>>>,
+}
+
+impl Archive {
+    /// Opens the archive in `dir`, creating it if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut index = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let summary = fs::read_to_string(&path).and_then(|raw| {
+                serde_json::from_str::<GameSummary>(&raw).map_err(io::Error::other)
+            });
+            match summary {
+                Ok(summary) => index.push(summary),
+                Err(err) => log::warn!("skipping archived game {}: {err}", path.display()),
+            }
+        }
+        index.sort_by_key(|summary| summary.finished_at);
+
+        Ok(Self {
+            dir,
+            index: Arc::new(Mutex::new(index)),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<GameSummary>> {
+        self.index.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    pub fn store(&self, game: &ArchivedGame) -> io::Result<()> {
+        // written aside first, so a crash never leaves half a game behind
+        let path = self.path(&game.summary.id);
+        let partial = path.with_extension("json.partial");
+        let raw = serde_json::to_vec(game).map_err(io::Error::other)?;
+        fs::write(&partial, raw)?;
+        fs::rename(&partial, &path)?;
+
+        self.lock().push(game.summary.clone());
+        Ok(())
+    }
+
+    /// Matching games, newest first.
+    pub fn query(&self, query: &ArchiveQuery) -> Vec<GameSummary> {
+        self.lock()
+            .iter()
+            .rev()
+            .filter(|game| query.matches(game))
+            .cloned()
+            .collect()
+    }
+
+    /// Raw JSON of an archived game.
+    pub fn load(&self, id: &str) -> io::Result<Option<Vec<u8>>> {
+        if !self.lock().iter().any(|game| game.id == id) {
+            return Ok(None);
+        }
+        fs::read(self.path(id)).map(Some)
+    }
+}
+
+pub async fn list_games(
+    State(archive): State<Archive>,
+    Query(query): Query<ArchiveQuery>,
+) -> Json<Vec<GameSummary>> {
+    Json(archive.query(&query))
+}
+
+/// Downloads a game as a replay file.
+pub async fn download_game(State(archive): State<Archive>, Path(id): Path<String>) -> Response {
+    match archive.load(&id) {
+        Ok(Some(raw)) => (
+            [
+                (header::CONTENT_TYPE, "application/json".to_owned()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"catan-{id}.json\""),
+                ),
+            ],
+            raw,
+        )
+            .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, format!("game {id} is not archived")).into_response(),
+        Err(err) => {
+            log::error!("failed to read archived game {id}: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use catan_core::gameplay::game::init::GameInitializationState;
+
+    use super::*;
+    use crate::{
+        game::BotKind,
+        lobby::{CreatedRoom, LimitsConfig, Lobby},
+    };
+
+    fn unique_test_dir() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rusty-catan-archive-test-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    fn game(name: &str, started_at: DateTime<Utc>) -> ArchivedGame {
+        let header = GameHeader {
+            config: RoomConfig::default(),
+            players: vec![ArchivedPlayer {
+                seat: 0,
+                kind: PlayerKind::Human,
+                name: Some(name.to_owned()),
+            }],
+            started_at,
+        };
+        let mut game = ArchivedGame::new(
+            header,
+            FinishedGame {
+                result: GameResult::LimitReached { turns: 4 },
+                seed: 7,
+                initial_state: GameInitializationState::default().finish(),
+                events: vec![GameEvent::GameStarted],
+            },
+        );
+        game.summary.finished_at = started_at;
+        game
+    }
+
+    #[test]
+    fn archived_games_survive_reopening_and_are_queried_by_player_and_date() {
+        let dir = unique_test_dir();
+        let archive = Archive::open(&dir).unwrap();
+        let day = |day| {
+            NaiveDate::from_ymd_opt(2026, 10, day)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        let first = game("ada", day(1));
+        let second = game("grace", day(2));
+        archive.store(&first).unwrap();
+        archive.store(&second).unwrap();
+
+        let archive = Archive::open(&dir).unwrap();
+        let ids = |query: ArchiveQuery| {
+            archive
+                .query(&query)
+                .into_iter()
+                .map(|game| game.id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(ArchiveQuery::default()),
+            [second.summary.id.as_str(), first.summary.id.as_str()]
+        );
+        assert_eq!(
+            ids(ArchiveQuery {
+                player: Some("ada".to_owned()),
+                ..ArchiveQuery::default()
+            }),
+            [first.summary.id.as_str()]
+        );
+        assert_eq!(
+            ids(ArchiveQuery {
+                from: Some(day(2).date_naive()),
+                to: Some(day(2).date_naive()),
+                ..ArchiveQuery::default()
+            }),
+            [second.summary.id.as_str()]
+        );
+
+        let raw = archive.load(&first.summary.id).unwrap().unwrap();
+        let replay = serde_json::from_slice::<ArchivedGame>(&raw).unwrap();
+        assert_eq!(replay.summary.seed, 7);
+        assert_eq!(replay.events.len(), 1);
+        assert!(archive.load("../elsewhere").unwrap().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn lobby_archives_finished_games() {
+        let dir = unique_test_dir();
+        let archive = Archive::open(&dir).unwrap();
+        let lobby = Lobby::with_archive(archive.clone());
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(RoomConfig {
+                fill_empty_seats: Some(BotKind::Greedy),
+                limits: LimitsConfig {
+                    max_turns: Some(4),
+                    ..LimitsConfig::default()
+                },
+                reconnect_grace_secs: 0,
+                ..RoomConfig::default()
+            })
+            .unwrap();
+
+        let mut lease = lobby.join(room.id, 2, None, Some("ada")).unwrap();
+        lease.ready();
+        lobby.start(room.id, &owner_token).unwrap();
+        drop(lease);
+        while lobby.room(room.id).is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+
+        let games = archive.query(&ArchiveQuery {
+            player: Some("ada".to_owned()),
+            ..ArchiveQuery::default()
+        });
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].players[2].kind, PlayerKind::Human);
+        assert_eq!(games[0].players[0].kind, PlayerKind::Greedy);
+        let raw = archive.load(&games[0].id).unwrap().unwrap();
+        let replay = serde_json::from_slice::<ArchivedGame>(&raw).unwrap();
+        assert!(matches!(
+            replay.events.first(),
+            Some(GameEvent::GameStarted)
+        ));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,630 @@
+//! Bot arena.
+//!
+//! Teams register a bot name and get a key back. A bot connects once over a
+//! websocket and is placed into games against the other connected bots, with
+//! house bots taking any seats left over. Each game starts with a `Hello`
+//! naming the bot's seat and then carries the same frames as a player
+//! session, except for the game's `Shutdown`: the connection stays open for
+//! the next game. Won games update an Elo ladder kept on disk.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json,
+    extract::{
+        Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use catan_agents::remote_agent::CliRole;
+use catan_core::gameplay::game::{event::GameEvent, init::GameInitializationState};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    Notify,
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+
+use crate::{
+    game::FinishedGame,
+    lobby::{LimitsConfig, Lobby, RoomConfig, SeatConfig, SeatLease},
+    protocol::ServerToClient,
+    session::PlayerSession,
+};
+
+const INITIAL_RATING: f64 = 1500.0;
+const K_FACTOR: f64 = 32.0;
+/// Connected bots needed for a game; house bots fill the other seats.
+const MIN_BOTS: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArenaError {
+    InvalidName,
+    NameTaken(String),
+    Unauthorized,
+    AlreadyConnected(String),
+}
+
+impl std::fmt::Display for ArenaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidName => write!(f, "bot names must not be empty"),
+            Self::NameTaken(name) => write!(f, "bot {name} is already registered"),
+            Self::Unauthorized => write!(f, "unknown bot or wrong key"),
+            Self::AlreadyConnected(name) => write!(f, "bot {name} is already connected"),
+        }
+    }
+}
+
+impl IntoResponse for ArenaError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::InvalidName => StatusCode::BAD_REQUEST,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::NameTaken(_) | Self::AlreadyConnected(_) => StatusCode::CONFLICT,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BotRecord {
+    key: String,
+    rating: f64,
+    games: u64,
+    wins: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LadderEntry {
+    pub name: String,
+    pub rating: f64,
+    pub games: u64,
+    pub wins: u64,
+    pub connected: bool,
+}
+
+struct Connection {
+    /// Leases of the games the bot is placed into.
+    assign: UnboundedSender<SeatLease>,
+    idle: bool,
+}
+
+#[derive(Default)]
+struct ArenaState {
+    bots: BTreeMap<String, BotRecord>,
+    connections: BTreeMap<String, Connection>,
+}
+
+/// Shared handle to the arena.
+#[derive(Clone)]
+pub struct Arena {
+    lobby: Lobby,
+    /// Ladder file, rewritten after every change.
+    path: PathBuf,
+    limits: LimitsConfig,
+    state: Arc<Mutex<ArenaState>>,
+    /// Woken whenever a bot becomes idle.
+    idle: Arc<Notify>,
+}
+
+impl Arena {
+    /// Opens the ladder at `path`, starting an empty one if it doesn't exist.
+    pub fn open(lobby: Lobby, path: impl Into<PathBuf>, limits: LimitsConfig) -> io::Result<Self> {
+        let path = path.into();
+        let bots = match fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw).map_err(io::Error::other)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(err) => return Err(err),
+        };
+        Ok(Self {
+            lobby,
+            path,
+            limits,
+            state: Arc::new(Mutex::new(ArenaState {
+                bots,
+                connections: BTreeMap::new(),
+            })),
+            idle: Arc::default(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ArenaState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn save(&self, state: &ArenaState) {
+        let result = serde_json::to_vec_pretty(&state.bots)
+            .map_err(io::Error::other)
+            .and_then(|raw| {
+                let partial = self.path.with_extension("partial");
+                fs::write(&partial, raw)?;
+                fs::rename(&partial, &self.path)
+            });
+        if let Err(err) = result {
+            log::error!("failed to save ladder {}: {err}", self.path.display());
+        }
+    }
+
+    /// Registers a bot and returns the key it connects with.
+    pub fn register(&self, name: &str) -> Result<String, ArenaError> {
+        if name.trim().is_empty() {
+            return Err(ArenaError::InvalidName);
+        }
+        let mut state = self.lock();
+        if state.bots.contains_key(name) {
+            return Err(ArenaError::NameTaken(name.to_owned()));
+        }
+        let key = format!("{:032x}", rand::random::<u128>());
+        state.bots.insert(
+            name.to_owned(),
+            BotRecord {
+                key: key.clone(),
+                rating: INITIAL_RATING,
+                games: 0,
+                wins: 0,
+            },
+        );
+        self.save(&state);
+        Ok(key)
+    }
+
+    /// Bots by rating, best first.
+    pub fn ladder(&self) -> Vec<LadderEntry> {
+        let state = self.lock();
+        let mut ladder = state
+            .bots
+            .iter()
+            .map(|(name, bot)| LadderEntry {
+                name: name.clone(),
+                rating: bot.rating,
+                games: bot.games,
+                wins: bot.wins,
+                connected: state.connections.contains_key(name),
+            })
+            .collect::<Vec<_>>();
+        ladder.sort_by(|a, b| b.rating.total_cmp(&a.rating));
+        ladder
+    }
+
+    fn connect(&self, name: &str, key: &str) -> Result<BotConnection, ArenaError> {
+        let mut state = self.lock();
+        if state.bots.get(name).is_none_or(|bot| bot.key != key) {
+            return Err(ArenaError::Unauthorized);
+        }
+        if state.connections.contains_key(name) {
+            return Err(ArenaError::AlreadyConnected(name.to_owned()));
+        }
+        let (assign, assignments) = mpsc::unbounded_channel();
+        state
+            .connections
+            .insert(name.to_owned(), Connection { assign, idle: true });
+        self.idle.notify_one();
+        log::info!("bot {name} connected to the arena");
+        Ok(BotConnection {
+            arena: self.clone(),
+            name: name.to_owned(),
+            assignments,
+        })
+    }
+
+    fn set_idle(&self, name: &str) {
+        if let Some(connection) = self.lock().connections.get_mut(name) {
+            connection.idle = true;
+            self.idle.notify_one();
+        }
+    }
+
+    /// Places idle bots into games for as long as the server runs.
+    pub fn spawn_scheduler(&self) {
+        let arena = self.clone();
+        tokio::spawn(async move {
+            loop {
+                arena.idle.notified().await;
+                while arena.schedule() {}
+            }
+        });
+    }
+
+    /// Starts one game if enough bots are idle.
+    fn schedule(&self) -> bool {
+        let n_players = GameInitializationState::default().board.n_players;
+        let mut state = self.lock();
+        let mut idle = state
+            .connections
+            .iter()
+            .filter(|(_, connection)| connection.idle)
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if idle.len() < MIN_BOTS {
+            return false;
+        }
+
+        let mut rng = rand::rng();
+        idle.shuffle(&mut rng);
+        idle.truncate(n_players);
+        let mut seats = idle.into_iter().map(Some).collect::<Vec<_>>();
+        seats.resize(n_players, None);
+        seats.shuffle(&mut rng);
+
+        let config = RoomConfig {
+            name: "arena".to_owned(),
+            seats: seats
+                .iter()
+                .map(|bot| match bot {
+                    Some(_) => SeatConfig::Human,
+                    None => SeatConfig::Greedy,
+                })
+                .collect(),
+            limits: self.limits,
+            // a bot that drops out is replaced right away
+            reconnect_grace_secs: 0,
+            ..RoomConfig::default()
+        };
+        let arena = self.clone();
+        let names = seats.clone();
+        let room = self
+            .lobby
+            .create_room_with_hook(config, move |game| arena.record(&names, game))
+            .expect("arena rooms are valid")
+            .info;
+
+        let mut leases = Vec::new();
+        for (seat, name) in seats.iter().enumerate() {
+            let Some(name) = name else {
+                continue;
+            };
+            let lease = self
+                .lobby
+                .join(room.id, seat, None, Some(name))
+                .expect("arena seats are open");
+            leases.push((name, lease));
+            state
+                .connections
+                .get_mut(name)
+                .expect("idle bots are connected")
+                .idle = false;
+        }
+        log::info!("arena room {} seats {seats:?}", room.id);
+
+        for (name, mut lease) in leases {
+            lease.ready();
+            // a bot gone by now is played by its fallback
+            let _ = state.connections[name].assign.send(lease);
+        }
+        true
+    }
+
+    /// Updates the ladder with a finished game, `seats` naming the bot of
+    /// every seat.
+    fn record(&self, seats: &[Option<String>], game: &FinishedGame) {
+        let mut state = self.lock();
+        for name in seats.iter().flatten() {
+            if let Some(bot) = state.bots.get_mut(name) {
+                bot.games += 1;
+            }
+        }
+
+        let Some(winner_id) = game.result.winner() else {
+            self.save(&state);
+            return;
+        };
+        if let Some(bot) = seats[winner_id]
+            .as_ref()
+            .and_then(|name| state.bots.get_mut(name))
+        {
+            bot.wins += 1;
+        }
+
+        let Some(stats) = game.events.iter().rev().find_map(|event| match event {
+            GameEvent::GameEnded { stats, .. } => Some(stats),
+            _ => None,
+        }) else {
+            self.save(&state);
+            return;
+        };
+        let rated = stats
+            .iter()
+            .filter_map(|stats| {
+                let name = seats.get(stats.player_id)?.as_ref()?;
+                let bot = state.bots.get(name)?;
+                Some((name.clone(), bot.rating, stats.total_vp))
+            })
+            .collect::<Vec<_>>();
+        let ratings = rated
+            .iter()
+            .map(|(_, rating, _)| *rating)
+            .collect::<Vec<_>>();
+        let scores = rated.iter().map(|(_, _, vp)| *vp).collect::<Vec<_>>();
+        for ((name, _, _), delta) in rated.iter().zip(elo_deltas(&ratings, &scores)) {
+            if let Some(bot) = state.bots.get_mut(name) {
+                bot.rating += delta;
+            }
+        }
+        self.save(&state);
+    }
+}
+
+/// Rating changes of a multiplayer game, scored as a round of pairwise
+/// matches decided by victory points.
+fn elo_deltas(ratings: &[f64], scores: &[u16]) -> Vec<f64> {
+    let n = ratings.len();
+    if n < 2 {
+        return vec![0.0; n];
+    }
+    let k = K_FACTOR / (n - 1) as f64;
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .filter(|&j| j != i)
+                .map(|j| {
+                    let expected = 1.0 / (1.0 + 10f64.powf((ratings[j] - ratings[i]) / 400.0));
+                    let actual = match scores[i].cmp(&scores[j]) {
+                        std::cmp::Ordering::Greater => 1.0,
+                        std::cmp::Ordering::Equal => 0.5,
+                        std::cmp::Ordering::Less => 0.0,
+                    };
+                    k * (actual - expected)
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// A connected bot, disconnected from the arena when dropped.
+struct BotConnection {
+    arena: Arena,
+    name: String,
+    assignments: UnboundedReceiver<SeatLease>,
+}
+
+impl Drop for BotConnection {
+    fn drop(&mut self) {
+        self.arena.lock().connections.remove(&self.name);
+        log::info!("bot {} left the arena", self.name);
+    }
+}
+
+impl BotConnection {
+    async fn serve(mut self, mut socket: WebSocket) {
+        loop {
+            let lease = tokio::select! {
+                lease = self.assignments.recv() => match lease {
+                    Some(lease) => lease,
+                    None => return,
+                },
+                msg = socket.recv() => match msg {
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                },
+            };
+
+            let hello = ServerToClient::hello(
+                CliRole::Player {
+                    player_id: lease.seat(),
+                },
+                None,
+            );
+            let hello = serde_json::to_string(&hello).expect("hello frame is serializable");
+            if socket.send(Message::Text(hello.into())).await.is_err() {
+                return;
+            }
+            let Some(reusable) = PlayerSession::new(socket, lease).run_game().await else {
+                return;
+            };
+            socket = reusable;
+            self.arena.set_idle(&self.name);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Registration {
+    name: String,
+    key: String,
+}
+
+pub async fn register_bot(
+    State(arena): State<Arena>,
+    Json(request): Json<RegisterRequest>,
+) -> Result<(StatusCode, Json<Registration>), ArenaError> {
+    let key = arena.register(&request.name)?;
+    log::info!("bot {} registered", request.name);
+    Ok((
+        StatusCode::CREATED,
+        Json(Registration {
+            name: request.name,
+            key,
+        }),
+    ))
+}
+
+pub async fn get_ladder(State(arena): State<Arena>) -> Json<Vec<LadderEntry>> {
+    Json(arena.ladder())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConnectQuery {
+    name: String,
+    key: String,
+}
+
+pub async fn connect_bot(
+    State(arena): State<Arena>,
+    Query(query): Query<ConnectQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    match arena.connect(&query.name, &query.key) {
+        Ok(connection) => ws.on_upgrade(|socket| connection.serve(socket)),
+        Err(err) => err.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{Router, routing::get};
+    use catan_core::gameplay::game::{controller::GameResult, event::GameEndPlayerStats};
+
+    use super::*;
+    use crate::arena_bot;
+
+    fn unique_ladder_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "rusty-catan-ladder-test-{}-{}.json",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn elo_deltas_reward_victory_points() {
+        let deltas = elo_deltas(&[1500.0, 1500.0, 1500.0], &[10, 4, 4]);
+        assert!(deltas[0] > 0.0);
+        assert_eq!(deltas[1], deltas[2]);
+        assert!(deltas.iter().sum::<f64>().abs() < 1e-9);
+
+        // beating a stronger bot is worth more than beating a weaker one
+        let upset = elo_deltas(&[1400.0, 1600.0], &[10, 5]);
+        let expected = elo_deltas(&[1600.0, 1400.0], &[10, 5]);
+        assert!(upset[0] > expected[0]);
+        assert_eq!(elo_deltas(&[1500.0], &[10]), [0.0]);
+    }
+
+    #[test]
+    fn won_games_move_the_ladder() {
+        let path = unique_ladder_path();
+        let arena = Arena::open(Lobby::default(), &path, LimitsConfig::default()).unwrap();
+        arena.register("ada").unwrap();
+        arena.register("grace").unwrap();
+        let stats = |player_id, total_vp| GameEndPlayerStats {
+            player_id,
+            total_vp,
+            build_and_dev_card_vp: total_vp,
+            award_vp: 0,
+            settlements: 0,
+            cities: 0,
+            roads: 0,
+            longest_road_length: 0,
+            knights_used: 0,
+            has_longest_road: false,
+            has_largest_army: false,
+        };
+        let game = FinishedGame {
+            result: GameResult::Win(2),
+            seed: 7,
+            initial_state: GameInitializationState::default().finish(),
+            events: vec![GameEvent::GameEnded {
+                winner_id: 2,
+                turn_no: 40,
+                stats: vec![stats(0, 3), stats(1, 6), stats(2, 10), stats(3, 4)],
+            }],
+        };
+        let seats = [None, Some("grace".to_owned()), Some("ada".to_owned()), None];
+        arena.record(&seats, &game);
+
+        let ladder = Arena::open(Lobby::default(), &path, LimitsConfig::default())
+            .unwrap()
+            .ladder();
+        assert_eq!(ladder[0].name, "ada");
+        assert_eq!((ladder[0].games, ladder[0].wins), (1, 1));
+        assert_eq!((ladder[1].games, ladder[1].wins), (1, 0));
+        // house bots aren't rated, so the bots trade points among themselves
+        assert!(ladder[0].rating > INITIAL_RATING);
+        assert_eq!(
+            ladder[0].rating - INITIAL_RATING,
+            INITIAL_RATING - ladder[1].rating
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn bots_register_and_connect_with_their_key() {
+        let path = unique_ladder_path();
+        let arena = Arena::open(Lobby::default(), &path, LimitsConfig::default()).unwrap();
+        let key = arena.register("ada").unwrap();
+        assert_eq!(arena.register(" "), Err(ArenaError::InvalidName));
+        assert_eq!(
+            arena.register("ada"),
+            Err(ArenaError::NameTaken("ada".to_owned()))
+        );
+
+        assert_eq!(
+            arena.connect("ada", "guess").err(),
+            Some(ArenaError::Unauthorized)
+        );
+        assert_eq!(
+            arena.connect("grace", &key).err(),
+            Some(ArenaError::Unauthorized)
+        );
+        let connection = arena.connect("ada", &key).unwrap();
+        assert_eq!(
+            arena.connect("ada", &key).err(),
+            Some(ArenaError::AlreadyConnected("ada".to_owned()))
+        );
+        assert!(arena.ladder()[0].connected);
+        drop(connection);
+        assert!(!arena.ladder()[0].connected);
+
+        let reopened = Arena::open(Lobby::default(), &path, LimitsConfig::default()).unwrap();
+        assert_eq!(reopened.ladder()[0].name, "ada");
+        assert_eq!(reopened.ladder()[0].rating, INITIAL_RATING);
+        assert!(reopened.connect("ada", &key).is_ok());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn connected_bots_play_each_other() {
+        let path = unique_ladder_path();
+        let limits = LimitsConfig {
+            max_turns: Some(4),
+            ..LimitsConfig::default()
+        };
+        let arena = Arena::open(Lobby::default(), &path, limits).unwrap();
+        arena.spawn_scheduler();
+        let app = Router::new()
+            .route("/arena/connect", get(connect_bot))
+            .with_state(arena.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let clients = ["ada", "grace"].map(|name| {
+            let key = arena.register(name).unwrap();
+            let url = url.clone();
+            tokio::spawn(async move { arena_bot::run(&url, name, &key).await })
+        });
+
+        tokio::time::timeout(Duration::from_secs(60), async {
+            while arena.ladder().iter().any(|bot| bot.games < 2) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("bots are placed into consecutive games");
+        assert!(arena.ladder().iter().all(|bot| bot.connected));
+
+        for client in clients {
+            client.abort();
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
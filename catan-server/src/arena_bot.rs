@@ -0,0 +1,131 @@
+//! Stand-in arena client. It plays a random legal option of every request,
+//! which is enough to try the arena without writing a bot.
+
+use catan_agents::remote_agent::{
+    CliRole, DecisionRequestFrame, DecisionResponseFrame, LegalDecisionOptions,
+};
+use catan_core::{
+    agent::action::{
+        ChoosePlayerToRobAction, DropHalfAction, InitAction, MoveRobbersAction, PostDevCardAction,
+        PostDiceAction, RegularAction, TradeAnswer,
+    },
+    gameplay::{game::event::GameEvent, primitives::resource::ResourceCollection},
+};
+use futures_util::{SinkExt, StreamExt};
+use rand::seq::IndexedRandom;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::protocol::{ClientToServer, ServerToClient};
+
+/// Plays arena games as `name` until the server closes the connection.
+pub async fn run(url: &str, name: &str, key: &str) -> Result<(), String> {
+    let url = format!(
+        "{}/arena/connect?name={name}&key={key}",
+        url.trim_end_matches('/')
+    );
+    let (mut socket, _) = tokio_tungstenite::connect_async(&url)
+        .await
+        .map_err(|err| format!("failed to connect to {url}: {err}"))?;
+
+    while let Some(msg) = socket.next().await {
+        let text = match msg.map_err(|err| format!("connection failed: {err}"))? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let msg = serde_json::from_str::<ServerToClient>(&text)
+            .map_err(|err| format!("malformed server frame: {err}"))?;
+        let reply = match msg {
+            ServerToClient::Hello {
+                role: CliRole::Player { player_id },
+                ..
+            } => {
+                log::info!("{name} plays seat {player_id}");
+                continue;
+            }
+            ServerToClient::DecisionRequest(request) => match decide(&request) {
+                Some(response) => ClientToServer::DecisionResponse(response),
+                None => ClientToServer::Error {
+                    message: format!("no legal option for {}", request.kind()),
+                },
+            },
+            ServerToClient::Event {
+                event: GameEvent::GameEnded { winner_id, .. },
+                ..
+            } => {
+                log::info!("{name}: player {winner_id} won");
+                continue;
+            }
+            ServerToClient::Shutdown { reason } => {
+                log::info!("{name}: server shut down: {reason}");
+                break;
+            }
+            _ => continue,
+        };
+        let reply = serde_json::to_string(&reply).expect("client frames are serializable");
+        socket
+            .send(Message::Text(reply.into()))
+            .await
+            .map_err(|err| format!("connection failed: {err}"))?;
+    }
+    Ok(())
+}
+
+/// Picks a random legal answer to `request`.
+pub fn decide(request: &DecisionRequestFrame) -> Option<DecisionResponseFrame> {
+    let rng = &mut rand::rng();
+    let envelope = request.envelope();
+    let legal = &envelope.legal;
+    let response = match request {
+        DecisionRequestFrame::InitStage(_) => {
+            DecisionResponseFrame::InitStage(*legal.initial_placements.choose(rng)?)
+        }
+        DecisionRequestFrame::InitAction(_) => {
+            DecisionResponseFrame::InitAction(InitAction::RollDice)
+        }
+        DecisionRequestFrame::PostDice(_) => {
+            DecisionResponseFrame::PostDice(PostDiceAction::RegularAction(regular_action(legal)))
+        }
+        DecisionRequestFrame::PostDevCard(_) => {
+            DecisionResponseFrame::PostDevCard(PostDevCardAction::RollDice)
+        }
+        DecisionRequestFrame::Regular(_) => DecisionResponseFrame::Regular(regular_action(legal)),
+        DecisionRequestFrame::MoveRobbers(_) => {
+            DecisionResponseFrame::MoveRobbers(MoveRobbersAction(*legal.robber_hexes.choose(rng)?))
+        }
+        DecisionRequestFrame::ChoosePlayerToRob(_) => DecisionResponseFrame::ChoosePlayerToRob(
+            ChoosePlayerToRobAction(*legal.rob_targets.choose(rng)?),
+        ),
+        DecisionRequestFrame::AnswerTrade(_) => {
+            DecisionResponseFrame::AnswerTrade(TradeAnswer::Decline)
+        }
+        DecisionRequestFrame::DropHalf(_) => {
+            let resources = envelope.view.private.as_ref()?.resources;
+            DecisionResponseFrame::DropHalf(DropHalfAction(half_of(resources)))
+        }
+    };
+    Some(response)
+}
+
+/// Anything but ending the move, while there is something to do.
+fn regular_action(legal: &LegalDecisionOptions) -> RegularAction {
+    let actions = legal
+        .regular_actions
+        .iter()
+        .filter(|action| !matches!(action, RegularAction::EndMove))
+        .collect::<Vec<_>>();
+    actions
+        .choose(&mut rand::rng())
+        .map_or(RegularAction::EndMove, |action| **action)
+}
+
+fn half_of(resources: ResourceCollection) -> ResourceCollection {
+    let mut remaining = resources.total() / 2;
+    let mut to_drop = ResourceCollection::default();
+    for (resource, number) in resources.unroll() {
+        let dropped = remaining.min(number);
+        to_drop[resource] = dropped;
+        remaining -= dropped;
+    }
+    to_drop
+}
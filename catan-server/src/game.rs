@@ -1,7 +1,147 @@
-use tokio::sync::mpsc::{Receiver, Sender};
+use std::{cell::RefCell, rc::Rc, sync::mpsc::Receiver, time::Duration};
 
-use crate::protocol::{ClientToServer, ServerToClient};
+use catan_agents::{greedy::GreedyAgent, random::RandomAgent};
+use catan_core::{
+    gameplay::{
+        agent::agent::Agent,
+        field::state::FieldBuildParam,
+        game::{
+            controller::{GameController, GameResult, RunOptions, StandIn},
+            event::{GameEvent, GameObserver, ObserverKind, ObserverNotificationContext},
+            init::GameInitializationState,
+            state::GameState,
+        },
+        primitives::player::PlayerId,
+    },
+    math::dice::RandomDiceRoller,
+};
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+use serde::{Deserialize, Serialize};
 
-pub fn spawn_game(_from_player: Receiver<ClientToServer>, _to_player: Sender<ServerToClient>) {
-    log::warn!("websocket game server is not wired for the current MVP runtime protocol");
+use crate::{
+    player::{SeatInput, SeatOutbox, WsPlayer},
+    spectate::SpectatorFeeds,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotKind {
+    Greedy,
+    Random,
+}
+
+impl BotKind {
+    fn agent(self, id: PlayerId) -> Box<dyn Agent> {
+        match self {
+            Self::Greedy => Box::new(GreedyAgent::new(id)),
+            Self::Random => Box::new(RandomAgent::new(id)),
+        }
+    }
+}
+
+/// Who plays a seat of a server-hosted game.
+pub enum SeatPlayer {
+    /// Websocket client, whose decisions fail after being away for `grace`.
+    Human {
+        inputs: Receiver<SeatInput>,
+        outbox: SeatOutbox,
+        grace: Duration,
+    },
+    Bot(BotKind),
+}
+
+/// Everything needed to replay a finished game: the seed drives the deck,
+/// the steals and the dice, the omniscient event log holds every decision.
+pub struct FinishedGame {
+    pub result: GameResult,
+    pub seed: u64,
+    pub initial_state: GameState,
+    pub events: Vec<GameEvent>,
+}
+
+/// Runs a game with one player per seat. The controller is synchronous, so it
+/// runs on a blocking thread; `on_finish` is called there with the outcome.
+/// `stand_in` takes over the seats of players who leave the game.
+pub fn spawn_game(
+    players: Vec<SeatPlayer>,
+    options: RunOptions,
+    stand_in: Option<BotKind>,
+    spectators: SpectatorFeeds,
+    on_finish: impl FnOnce(FinishedGame) + Send + 'static,
+) {
+    tokio::task::spawn_blocking(move || {
+        on_finish(run_game(players, options, stand_in, spectators))
+    });
+}
+
+fn run_game(
+    players: Vec<SeatPlayer>,
+    options: RunOptions,
+    stand_in: Option<BotKind>,
+    spectators: SpectatorFeeds,
+) -> FinishedGame {
+    let mut agents = players
+        .into_iter()
+        .enumerate()
+        .map(|(id, player)| match player {
+            SeatPlayer::Human {
+                inputs,
+                outbox,
+                grace,
+            } => Box::new(WsPlayer::new(id, inputs, outbox, grace)) as Box<dyn Agent>,
+            SeatPlayer::Bot(kind) => kind.agent(id),
+        })
+        .collect::<Vec<_>>();
+
+    let seed = rand::random();
+    let mut seeds = SmallRng::seed_from_u64(seed);
+    let init = GameInitializationState::with_seed(FieldBuildParam::default(), seeds.next_u64());
+    let initial_state = init.clone().finish();
+    let events = Rc::default();
+    let dice_seed = seeds.next_u64();
+    let mut stand_in = stand_in.map(|kind| Box::new(move |id| kind.agent(id)) as StandIn);
+    let result = {
+        let mut observers = spectators.observers();
+        observers.push(Box::new(EventLog(Rc::clone(&events))));
+        match GameController::init_with_options(
+            init,
+            &mut agents,
+            &mut observers,
+            options,
+            stand_in.as_mut(),
+        ) {
+            Ok(state) => {
+                let mut controller = GameController::new(state, agents);
+                for observer in observers {
+                    controller.add_observer(observer);
+                }
+                if let Some(stand_in) = stand_in {
+                    controller.set_stand_in(stand_in);
+                }
+                controller.run_with_options(&mut RandomDiceRoller::with_seed(dice_seed), options)
+            }
+            Err(result) => result,
+        }
+    };
+
+    FinishedGame {
+        result,
+        seed,
+        initial_state,
+        events: Rc::into_inner(events)
+            .expect("the controller is gone")
+            .into_inner(),
+    }
+}
+
+struct EventLog(Rc<RefCell<Vec<GameEvent>>>);
+
+impl GameObserver for EventLog {
+    fn kind(&self) -> ObserverKind {
+        ObserverKind::Omniscient
+    }
+
+    fn on_event(&mut self, event: &GameEvent, _: ObserverNotificationContext<'_>) {
+        self.0.borrow_mut().push(event.clone());
+    }
 }
@@ -0,0 +1,168 @@
+//! Plain HTTP access to running games, for clients that can't speak
+//! websockets: events stream as Server-Sent Events carrying the same JSON
+//! frames as the websocket endpoints.
+
+use std::{collections::VecDeque, convert::Infallible};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State, ws::Utf8Bytes},
+    http::StatusCode,
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+};
+use catan_agents::remote_agent::{CliRole, DecisionResponseFrame, UiModel};
+use catan_core::gameplay::{game::event::ObserverKind, primitives::player::PlayerId};
+use futures_util::{StreamExt, stream};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{
+    lobby::{Lobby, LobbyError, RoomId, RoomInfo, SeatLease},
+    protocol::ServerToClient,
+};
+
+pub async fn list_games(State(lobby): State<Lobby>) -> Json<Vec<RoomInfo>> {
+    Json(lobby.running_games())
+}
+
+/// Public view of the game, as far as spectators have seen it.
+pub async fn game_state(
+    State(lobby): State<Lobby>,
+    Path(room_id): Path<RoomId>,
+) -> Result<Json<UiModel>, LobbyError> {
+    let feeds = lobby
+        .spectator_feeds(room_id)
+        .ok_or(LobbyError::UnknownRoom(room_id))?;
+    feeds
+        .public_view()
+        .map(Json)
+        .ok_or(LobbyError::NotStarted(room_id))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EventsQuery {
+    /// Seat to play instead of spectating.
+    seat: Option<PlayerId>,
+    /// Session token of a player taking back its seat.
+    token: Option<String>,
+    /// Name the player is archived under.
+    name: Option<String>,
+}
+
+/// Streams the public feed of a room or, given a seat, plays it: the stream
+/// then carries the seat's events and decision requests, answered with
+/// [`submit_decision`]. Connecting counts as being ready.
+pub async fn game_events(
+    State(lobby): State<Lobby>,
+    Path(room_id): Path<RoomId>,
+    Query(query): Query<EventsQuery>,
+) -> Response {
+    let Some(seat) = query.seat else {
+        let Some(feeds) = lobby.spectator_feeds(room_id) else {
+            return LobbyError::UnknownRoom(room_id).into_response();
+        };
+        let (last, frames) = feeds.subscribe(ObserverKind::Spectator);
+        return public_events(last, frames).into_response();
+    };
+
+    match lobby.join(room_id, seat, query.token.as_deref(), query.name.as_deref()) {
+        Ok(lease) => seat_events(lease).into_response(),
+        Err(err) => err.into_response(),
+    }
+}
+
+fn public_events(
+    last: Option<Utf8Bytes>,
+    frames: broadcast::Receiver<Utf8Bytes>,
+) -> impl IntoResponse {
+    let hello = ServerToClient::hello(CliRole::Spectator, None);
+    let hello = serde_json::to_string(&hello).expect("hello frame is serializable");
+
+    let frames = stream::unfold((last, frames), |(mut last, mut frames)| async move {
+        if let Some(frame) = last.take() {
+            return Some((frame, (last, frames)));
+        }
+        loop {
+            match frames.recv().await {
+                Ok(frame) => return Some((frame, (None, frames))),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("spectator lagged behind by {skipped} frames");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    })
+    .map(|frame| Event::default().data(frame.as_str()));
+
+    let events = stream::once(async move { Event::default().data(hello) })
+        .chain(frames)
+        .map(Ok::<_, Infallible>);
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+struct SeatStream {
+    lease: SeatLease,
+    queued: VecDeque<ServerToClient>,
+    finished: bool,
+}
+
+fn seat_events(mut lease: SeatLease) -> impl IntoResponse {
+    let hello = ServerToClient::hello(
+        CliRole::Player {
+            player_id: lease.seat(),
+        },
+        Some(lease.token().to_owned()),
+    );
+    let mut queued = VecDeque::from([hello]);
+    queued.extend(lease.ready());
+
+    let seat = SeatStream {
+        lease,
+        queued,
+        finished: false,
+    };
+    // the lease lives as long as the stream, so a client going away
+    // releases the seat just like a closed websocket
+    let events = stream::unfold(seat, |mut seat| async move {
+        if seat.finished {
+            return None;
+        }
+        let msg = match seat.queued.pop_front() {
+            Some(msg) => msg,
+            None => seat.lease.recv().await?,
+        };
+        seat.finished = matches!(msg, ServerToClient::Shutdown { .. });
+        Some((msg, seat))
+    })
+    .map(|msg| Event::default().json_data(msg));
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionQuery {
+    seat: PlayerId,
+    token: String,
+    /// Id of the decision request being answered.
+    request_id: u64,
+}
+
+/// Answers the outstanding decision request of a seat; answers to any other
+/// request are refused with a conflict.
+pub async fn submit_decision(
+    State(lobby): State<Lobby>,
+    Path(room_id): Path<RoomId>,
+    Query(query): Query<DecisionQuery>,
+    Json(response): Json<DecisionResponseFrame>,
+) -> Result<StatusCode, LobbyError> {
+    lobby.submit(
+        room_id,
+        query.seat,
+        &query.token,
+        query.request_id,
+        response,
+    )?;
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,1054 @@
+//! Rooms hosted by the server.
+//!
+//! A room is created from a [`RoomConfig`], players join its human seats over
+//! websockets and mark themselves ready by answering the `Hello`. The
+//! game starts as soon as every human seat is taken and ready. A room that
+//! fills empty seats with bots can also be started earlier by its creator,
+//! with the owner token handed out on creation, once everyone who joined is
+//! ready; the seats still open then go to bots.
+//!
+//! Joining hands out a session token. Leaving before the start frees the seat;
+//! once the game runs, the seat is kept and the token lets a client take it
+//! back. Finished games go to the [`Archive`], if the lobby has one.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, mpsc},
+    time::Duration,
+};
+
+use catan_agents::remote_agent::DecisionResponseFrame;
+use catan_core::gameplay::{
+    game::{
+        controller::{AgentErrorPolicy, RunOptions},
+        init::GameInitializationState,
+    },
+    primitives::player::PlayerId,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{
+    archive::{Archive, ArchivedGame, ArchivedPlayer, GameHeader, PlayerKind},
+    game::{BotKind, FinishedGame, SeatPlayer, spawn_game},
+    player::{SeatInput, SeatOutbox},
+    protocol::{ClientToServer, ServerToClient},
+    spectate::SpectatorFeeds,
+};
+
+pub type RoomId = u64;
+
+/// Called with the outcome of a room's game before it is archived.
+type FinishHook = Box<dyn FnOnce(&FinishedGame) + Send>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoomConfig {
+    #[serde(default)]
+    pub name: String,
+    /// One entry per seat of the board. Empty means all seats are open for
+    /// humans.
+    #[serde(default)]
+    pub seats: Vec<SeatConfig>,
+    /// Bot taking the human seats still open when the creator starts the
+    /// room.
+    #[serde(default)]
+    pub fill_empty_seats: Option<BotKind>,
+    /// Bot taking over the seats of players who resign, forfeit or stay
+    /// away past the reconnect grace, the greedy bot unless set. `null`
+    /// opts out, and the game then ends when somebody leaves.
+    #[serde(default = "default_stand_in")]
+    pub stand_in: Option<BotKind>,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    /// How long a seat waits for its disconnected player before the stand-in
+    /// takes over.
+    #[serde(default = "default_reconnect_grace_secs")]
+    pub reconnect_grace_secs: u64,
+    /// Turns the public spectator feed lags behind the game.
+    #[serde(default)]
+    pub spectator_delay_turns: u64,
+}
+
+impl Default for RoomConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            seats: Vec::new(),
+            fill_empty_seats: None,
+            stand_in: default_stand_in(),
+            limits: LimitsConfig::default(),
+            reconnect_grace_secs: default_reconnect_grace_secs(),
+            spectator_delay_turns: 0,
+        }
+    }
+}
+
+fn default_stand_in() -> Option<BotKind> {
+    Some(BotKind::Greedy)
+}
+
+fn default_reconnect_grace_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeatConfig {
+    Human,
+    Greedy,
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    pub max_turns: Option<u64>,
+    pub max_invalid_actions: Option<u64>,
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        let defaults = RunOptions::default();
+        Self {
+            max_turns: defaults.max_turns,
+            max_invalid_actions: defaults.max_invalid_actions,
+        }
+    }
+}
+
+impl From<LimitsConfig> for RunOptions {
+    fn from(value: LimitsConfig) -> Self {
+        Self {
+            max_turns: value.max_turns,
+            max_invalid_actions: value.max_invalid_actions,
+            ..RunOptions::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LobbyError {
+    InvalidConfig(String),
+    UnknownRoom(RoomId),
+    UnknownSeat(PlayerId),
+    BotSeat(PlayerId),
+    SeatTaken(PlayerId),
+    InvalidToken(PlayerId),
+    AlreadyStarted(RoomId),
+    NotStarted(RoomId),
+    NotOwner(RoomId),
+    NotReady(RoomId),
+    /// A decision named a request that is not the outstanding one.
+    StaleRequest(u64),
+}
+
+impl std::fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidConfig(reason) => write!(f, "invalid room config: {reason}"),
+            Self::UnknownRoom(room_id) => write!(f, "room {room_id} does not exist"),
+            Self::UnknownSeat(seat) => write!(f, "seat {seat} does not exist"),
+            Self::BotSeat(seat) => write!(f, "seat {seat} is played by a bot"),
+            Self::SeatTaken(seat) => write!(f, "seat {seat} is already taken"),
+            Self::InvalidToken(seat) => write!(f, "invalid session token for seat {seat}"),
+            Self::AlreadyStarted(room_id) => write!(f, "room {room_id} has already started"),
+            Self::NotStarted(room_id) => write!(f, "room {room_id} has not started yet"),
+            Self::NotOwner(room_id) => {
+                write!(f, "only the creator of room {room_id} can start it")
+            }
+            Self::NotReady(room_id) => {
+                write!(
+                    f,
+                    "room {room_id} is waiting for players to join and get ready"
+                )
+            }
+            Self::StaleRequest(request_id) => {
+                write!(
+                    f,
+                    "request {request_id} is not the outstanding decision request"
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomStatus {
+    Open,
+    Running,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeatInfo {
+    Open,
+    Joined { ready: bool, connected: bool },
+    Bot { bot: BotKind },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    pub name: String,
+    pub status: RoomStatus,
+    pub seats: Vec<SeatInfo>,
+}
+
+/// What the creator of a room gets back.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedRoom {
+    #[serde(flatten)]
+    pub info: RoomInfo,
+    /// Lets the creator start the room before every human seat is taken.
+    pub owner_token: String,
+}
+
+enum Seat {
+    Open,
+    Joined(JoinedSeat),
+    Bot(BotKind),
+}
+
+struct JoinedSeat {
+    token: String,
+    name: Option<String>,
+    ready: bool,
+    connected: bool,
+    to_game: mpsc::Sender<SeatInput>,
+    outbox: SeatOutbox,
+    /// Taken by the game when it starts.
+    inputs: Option<mpsc::Receiver<SeatInput>>,
+    /// Receiving end of the outbox while no session holds it.
+    from_game: Option<UnboundedReceiver<ServerToClient>>,
+}
+
+struct Room {
+    /// Kept for the archive.
+    config: RoomConfig,
+    owner_token: String,
+    name: String,
+    status: RoomStatus,
+    seats: Vec<Seat>,
+    fill_empty_seats: Option<BotKind>,
+    stand_in: Option<BotKind>,
+    limits: LimitsConfig,
+    reconnect_grace: Duration,
+    spectators: SpectatorFeeds,
+    on_finish: Option<FinishHook>,
+}
+
+impl Room {
+    fn new(config: RoomConfig) -> Result<Self, LobbyError> {
+        let n_players = GameInitializationState::default().board.n_players;
+        let seats = if config.seats.is_empty() {
+            vec![SeatConfig::Human; n_players]
+        } else {
+            config.seats.clone()
+        };
+        if seats.len() != n_players {
+            return Err(LobbyError::InvalidConfig(format!(
+                "the board has {n_players} seats, got {}",
+                seats.len()
+            )));
+        }
+
+        Ok(Self {
+            owner_token: new_token(),
+            name: config.name.clone(),
+            status: RoomStatus::Open,
+            seats: seats
+                .into_iter()
+                .map(|seat| match seat {
+                    SeatConfig::Human => Seat::Open,
+                    SeatConfig::Greedy => Seat::Bot(BotKind::Greedy),
+                    SeatConfig::Random => Seat::Bot(BotKind::Random),
+                })
+                .collect(),
+            fill_empty_seats: config.fill_empty_seats,
+            stand_in: config.stand_in,
+            limits: config.limits,
+            reconnect_grace: Duration::from_secs(config.reconnect_grace_secs),
+            spectators: SpectatorFeeds::new(config.spectator_delay_turns),
+            config,
+            on_finish: None,
+        })
+    }
+
+    fn info(&self, id: RoomId) -> RoomInfo {
+        RoomInfo {
+            id,
+            name: self.name.clone(),
+            status: self.status,
+            seats: self
+                .seats
+                .iter()
+                .map(|seat| match seat {
+                    Seat::Open => SeatInfo::Open,
+                    Seat::Joined(seat) => SeatInfo::Joined {
+                        ready: seat.ready,
+                        connected: seat.connected,
+                    },
+                    Seat::Bot(bot) => SeatInfo::Bot { bot: *bot },
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether someone joined and everyone who did is ready. Open seats only
+    /// hold the start back unless `fill_open` asks to give them to bots.
+    fn can_start(&self, fill_open: bool) -> bool {
+        let fill_open = fill_open && self.fill_empty_seats.is_some();
+        let mut joined = false;
+        for seat in &self.seats {
+            match seat {
+                Seat::Open if !fill_open => return false,
+                Seat::Joined(seat) if !seat.ready => return false,
+                Seat::Joined(_) => joined = true,
+                Seat::Open | Seat::Bot(_) => {}
+            }
+        }
+        joined
+    }
+
+    /// Hands the seats over to the game.
+    fn start(&mut self) -> (Vec<SeatPlayer>, GameHeader) {
+        self.status = RoomStatus::Running;
+        let fill = self.fill_empty_seats;
+        let grace = self.reconnect_grace;
+        let players = self
+            .seats
+            .iter_mut()
+            .map(|seat| match seat {
+                Seat::Joined(seat) => SeatPlayer::Human {
+                    inputs: seat.inputs.take().expect("the game starts once"),
+                    outbox: seat.outbox.clone(),
+                    grace,
+                },
+                Seat::Bot(bot) => SeatPlayer::Bot(*bot),
+                Seat::Open => {
+                    let bot = fill.expect("open seats are filled with bots");
+                    *seat = Seat::Bot(bot);
+                    SeatPlayer::Bot(bot)
+                }
+            })
+            .collect();
+
+        let header = GameHeader {
+            config: self.config.clone(),
+            players: self
+                .seats
+                .iter()
+                .enumerate()
+                .map(|(id, seat)| {
+                    let (kind, name) = match seat {
+                        Seat::Joined(seat) => (PlayerKind::Human, seat.name.clone()),
+                        Seat::Bot(BotKind::Greedy) => (PlayerKind::Greedy, None),
+                        Seat::Bot(BotKind::Random) => (PlayerKind::Random, None),
+                        Seat::Open => unreachable!("open seats were filled above"),
+                    };
+                    ArchivedPlayer {
+                        seat: id,
+                        kind,
+                        name,
+                    }
+                })
+                .collect(),
+            started_at: chrono::Utc::now(),
+        };
+        (players, header)
+    }
+}
+
+#[derive(Default)]
+struct LobbyState {
+    next_room_id: RoomId,
+    rooms: BTreeMap<RoomId, Room>,
+}
+
+/// Shared handle to the rooms of the server.
+#[derive(Clone, Default)]
+pub struct Lobby {
+    state: Arc<Mutex<LobbyState>>,
+    archive: Option<Archive>,
+}
+
+impl Lobby {
+    pub fn with_archive(archive: Archive) -> Self {
+        Self {
+            state: Arc::default(),
+            archive: Some(archive),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LobbyState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn create_room(&self, config: RoomConfig) -> Result<CreatedRoom, LobbyError> {
+        self.insert_room(Room::new(config)?)
+    }
+
+    /// Creates a room whose owner wants to hear how its game ends.
+    pub fn create_room_with_hook(
+        &self,
+        config: RoomConfig,
+        on_finish: impl FnOnce(&FinishedGame) + Send + 'static,
+    ) -> Result<CreatedRoom, LobbyError> {
+        let mut room = Room::new(config)?;
+        room.on_finish = Some(Box::new(on_finish));
+        self.insert_room(room)
+    }
+
+    fn insert_room(&self, room: Room) -> Result<CreatedRoom, LobbyError> {
+        let mut state = self.lock();
+        let id = state.next_room_id;
+        state.next_room_id += 1;
+        let created = CreatedRoom {
+            info: room.info(id),
+            owner_token: room.owner_token.clone(),
+        };
+        state.rooms.insert(id, room);
+        Ok(created)
+    }
+
+    pub fn open_rooms(&self) -> Vec<RoomInfo> {
+        self.lock()
+            .rooms
+            .iter()
+            .filter(|(_, room)| room.status == RoomStatus::Open)
+            .map(|(id, room)| room.info(*id))
+            .collect()
+    }
+
+    pub fn running_games(&self) -> Vec<RoomInfo> {
+        self.lock()
+            .rooms
+            .iter()
+            .filter(|(_, room)| room.status == RoomStatus::Running)
+            .map(|(id, room)| room.info(*id))
+            .collect()
+    }
+
+    pub fn room(&self, room_id: RoomId) -> Option<RoomInfo> {
+        self.lock()
+            .rooms
+            .get(&room_id)
+            .map(|room| room.info(room_id))
+    }
+
+    /// Feeds of a room, open to spectators before and while it plays.
+    pub fn spectator_feeds(&self, room_id: RoomId) -> Option<SpectatorFeeds> {
+        self.lock()
+            .rooms
+            .get(&room_id)
+            .map(|room| room.spectators.clone())
+    }
+
+    /// Takes a human seat under an optional player `name`, or takes back the
+    /// seat of a running game with the session token handed out when it was
+    /// first joined.
+    pub fn join(
+        &self,
+        room_id: RoomId,
+        seat: PlayerId,
+        token: Option<&str>,
+        name: Option<&str>,
+    ) -> Result<SeatLease, LobbyError> {
+        let mut state = self.lock();
+        let room = state
+            .rooms
+            .get_mut(&room_id)
+            .ok_or(LobbyError::UnknownRoom(room_id))?;
+        let running = room.status == RoomStatus::Running;
+        let slot = room
+            .seats
+            .get_mut(seat)
+            .ok_or(LobbyError::UnknownSeat(seat))?;
+
+        match slot {
+            Seat::Bot(_) => Err(LobbyError::BotSeat(seat)),
+            Seat::Joined(joined) => {
+                let Some(token) = token else {
+                    return Err(LobbyError::SeatTaken(seat));
+                };
+                if token != joined.token {
+                    return Err(LobbyError::InvalidToken(seat));
+                }
+                if joined.connected {
+                    return Err(LobbyError::SeatTaken(seat));
+                }
+                joined.connected = true;
+                Ok(SeatLease {
+                    lobby: self.clone(),
+                    room_id,
+                    seat,
+                    token: joined.token.clone(),
+                    to_game: joined.to_game.clone(),
+                    outbox: joined.outbox.clone(),
+                    from_game: joined.from_game.take(),
+                    resumed: true,
+                    attached: false,
+                })
+            }
+            Seat::Open if running => Err(LobbyError::AlreadyStarted(room_id)),
+            Seat::Open => {
+                let token = new_token();
+                let (to_game, inputs) = mpsc::channel();
+                let (outbox, from_game) = SeatOutbox::channel();
+                *slot = Seat::Joined(JoinedSeat {
+                    token: token.clone(),
+                    name: name.map(str::to_owned),
+                    ready: false,
+                    connected: true,
+                    to_game: to_game.clone(),
+                    outbox: outbox.clone(),
+                    inputs: Some(inputs),
+                    from_game: None,
+                });
+                Ok(SeatLease {
+                    lobby: self.clone(),
+                    room_id,
+                    seat,
+                    token,
+                    to_game,
+                    outbox,
+                    from_game: Some(from_game),
+                    resumed: false,
+                    attached: false,
+                })
+            }
+        }
+    }
+
+    /// Answers the outstanding decision request `request_id` of a seat on
+    /// behalf of the player holding `token`, whether or not a session of the
+    /// seat is connected.
+    pub fn submit(
+        &self,
+        room_id: RoomId,
+        seat: PlayerId,
+        token: &str,
+        request_id: u64,
+        response: DecisionResponseFrame,
+    ) -> Result<(), LobbyError> {
+        let state = self.lock();
+        let room = state
+            .rooms
+            .get(&room_id)
+            .ok_or(LobbyError::UnknownRoom(room_id))?;
+        match room.seats.get(seat) {
+            None => Err(LobbyError::UnknownSeat(seat)),
+            Some(Seat::Bot(_)) => Err(LobbyError::BotSeat(seat)),
+            Some(Seat::Open) => Err(LobbyError::InvalidToken(seat)),
+            Some(Seat::Joined(joined)) if joined.token != token => {
+                Err(LobbyError::InvalidToken(seat))
+            }
+            Some(Seat::Joined(_)) if room.status == RoomStatus::Open => {
+                Err(LobbyError::NotStarted(room_id))
+            }
+            Some(Seat::Joined(joined))
+                if joined.outbox.pending_request_id() != Some(request_id) =>
+            {
+                Err(LobbyError::StaleRequest(request_id))
+            }
+            Some(Seat::Joined(joined)) => {
+                let _ = joined.to_game.send(SeatInput::Decision {
+                    request_id,
+                    response,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn ready(&self, room_id: RoomId, seat: PlayerId) {
+        let mut state = self.lock();
+        let Some(room) = state.rooms.get_mut(&room_id) else {
+            return;
+        };
+        if let Some(Seat::Joined(seat)) = room.seats.get_mut(seat) {
+            seat.ready = true;
+        }
+        if room.can_start(false) {
+            self.launch(room_id, room);
+        }
+    }
+
+    /// Starts a room on behalf of its creator, giving the seats still open
+    /// to the room's bots.
+    pub fn start(&self, room_id: RoomId, owner_token: &str) -> Result<RoomInfo, LobbyError> {
+        let mut state = self.lock();
+        let room = state
+            .rooms
+            .get_mut(&room_id)
+            .ok_or(LobbyError::UnknownRoom(room_id))?;
+        if owner_token != room.owner_token {
+            return Err(LobbyError::NotOwner(room_id));
+        }
+        if room.status == RoomStatus::Running {
+            return Err(LobbyError::AlreadyStarted(room_id));
+        }
+        if !room.can_start(true) {
+            return Err(LobbyError::NotReady(room_id));
+        }
+        self.launch(room_id, room);
+        Ok(room.info(room_id))
+    }
+
+    fn launch(&self, room_id: RoomId, room: &mut Room) {
+        let (players, header) = room.start();
+        let options = RunOptions {
+            agent_errors: AgentErrorPolicy::Fallback,
+            ..room.limits.into()
+        };
+        let stand_in = room.stand_in;
+        let lobby = self.clone();
+        log::info!("room {room_id} started");
+        let spectators = room.spectators.clone();
+        let on_finish = room.on_finish.take();
+        spawn_game(players, options, stand_in, spectators, move |game| {
+            log::info!("room {room_id} finished: {:?}", game.result);
+            if let Some(on_finish) = on_finish {
+                on_finish(&game);
+            }
+            if let Some(archive) = &lobby.archive {
+                let game = ArchivedGame::new(header, game);
+                match archive.store(&game) {
+                    Ok(()) => log::info!("room {room_id} archived as {}", game.summary.id),
+                    Err(err) => log::error!("failed to archive room {room_id}: {err}"),
+                }
+            }
+            lobby.lock().rooms.remove(&room_id);
+        });
+    }
+
+    fn leave(&self, lease: &mut SeatLease) {
+        let mut state = self.lock();
+        let Some(room) = state.rooms.get_mut(&lease.room_id) else {
+            return;
+        };
+        if room.status == RoomStatus::Open {
+            room.seats[lease.seat] = Seat::Open;
+            return;
+        }
+
+        if let Some(Seat::Joined(seat)) = room.seats.get_mut(lease.seat) {
+            seat.connected = false;
+            seat.from_game = lease.from_game.take();
+            if lease.attached {
+                seat.outbox.detach();
+                let _ = seat.to_game.send(SeatInput::Disconnected);
+            }
+        }
+    }
+}
+
+/// A human seat taken by a websocket session.
+fn new_token() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+pub struct SeatLease {
+    lobby: Lobby,
+    room_id: RoomId,
+    seat: PlayerId,
+    token: String,
+    to_game: mpsc::Sender<SeatInput>,
+    outbox: SeatOutbox,
+    from_game: Option<UnboundedReceiver<ServerToClient>>,
+    /// Whether the seat was taken back in a running game.
+    resumed: bool,
+    attached: bool,
+}
+
+impl SeatLease {
+    pub fn seat(&self) -> PlayerId {
+        self.seat
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Marks the seat ready, starting the game if it was the last one to wait
+    /// for. A client taking back its seat gets the frames it needs to catch up
+    /// with the game instead.
+    pub fn ready(&mut self) -> Vec<ServerToClient> {
+        self.attached = true;
+        if !self.resumed {
+            self.lobby.ready(self.room_id, self.seat);
+            return Vec::new();
+        }
+
+        let from_game = self
+            .from_game
+            .as_mut()
+            .expect("a lease holds the receiving end");
+        let catch_up = self.outbox.attach(from_game);
+        let _ = self.to_game.send(SeatInput::Connected);
+        catch_up
+    }
+
+    pub async fn recv(&mut self) -> Option<ServerToClient> {
+        self.from_game.as_mut()?.recv().await
+    }
+
+    /// Returns `false` once the game is over.
+    pub fn send(&self, msg: ClientToServer) -> bool {
+        self.to_game.send(SeatInput::Client(msg)).is_ok()
+    }
+}
+
+impl Drop for SeatLease {
+    fn drop(&mut self) {
+        self.lobby.clone().leave(self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catan_core::{agent::action::RegularAction, gameplay::game::controller::GameResult};
+
+    use super::*;
+    use crate::arena_bot;
+
+    fn config(seats: &[SeatConfig], fill_empty_seats: Option<BotKind>) -> RoomConfig {
+        RoomConfig {
+            name: "test".to_owned(),
+            seats: seats.to_vec(),
+            fill_empty_seats,
+            stand_in: None,
+            limits: LimitsConfig {
+                max_turns: Some(4),
+                ..LimitsConfig::default()
+            },
+            reconnect_grace_secs: 0,
+            spectator_delay_turns: 0,
+        }
+    }
+
+    async fn next_request(lease: &mut SeatLease) -> ServerToClient {
+        loop {
+            match lease.recv().await {
+                Some(msg @ ServerToClient::DecisionRequest(_)) => return msg,
+                Some(_) => {}
+                None => panic!("game closed the seat"),
+            }
+        }
+    }
+
+    /// Lets bots answer every remaining decision of the seat.
+    async fn play_out(mut lease: SeatLease) {
+        loop {
+            match lease.recv().await {
+                Some(ServerToClient::DecisionRequest(_)) => {
+                    lease.send(ClientToServer::Error {
+                        message: "no opinion".to_owned(),
+                    });
+                }
+                Some(ServerToClient::Shutdown { .. }) | None => return,
+                Some(_) => {}
+            }
+        }
+    }
+
+    #[test]
+    fn parses_room_config() {
+        let config: RoomConfig = serde_json::from_str(
+            r#"{
+              "name": "office",
+              "seats": [{ "kind": "human" }, { "kind": "human" }, { "kind": "greedy" }, { "kind": "random" }],
+              "fill_empty_seats": "greedy",
+              "limits": { "max_turns": 100 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.seats[3], SeatConfig::Random);
+        assert_eq!(config.fill_empty_seats, Some(BotKind::Greedy));
+        assert_eq!(config.stand_in, Some(BotKind::Greedy));
+        assert_eq!(config.limits.max_turns, Some(100));
+        assert_eq!(config.limits.max_invalid_actions, Some(10));
+        assert_eq!(config.reconnect_grace_secs, 60);
+
+        let config: RoomConfig = serde_json::from_str(r#"{ "stand_in": null }"#).unwrap();
+        assert_eq!(config.stand_in, None);
+    }
+
+    #[test]
+    fn rejects_wrong_seat_count() {
+        let lobby = Lobby::default();
+        let err = lobby
+            .create_room(config(&[SeatConfig::Human], None))
+            .err()
+            .unwrap();
+        assert!(matches!(err, LobbyError::InvalidConfig(_)));
+    }
+
+    #[test]
+    fn seats_are_taken_and_freed() {
+        let lobby = Lobby::default();
+        let room = lobby.create_room(RoomConfig::default()).unwrap().info;
+        assert_eq!(lobby.open_rooms().len(), 1);
+
+        let lease = lobby.join(room.id, 1, None, None).unwrap();
+        assert_eq!(
+            lobby.join(room.id, 1, None, None).err(),
+            Some(LobbyError::SeatTaken(1))
+        );
+        assert_eq!(
+            lobby.join(room.id, 9, None, None).err(),
+            Some(LobbyError::UnknownSeat(9))
+        );
+        assert_eq!(
+            lobby.room(room.id).unwrap().seats[1],
+            SeatInfo::Joined {
+                ready: false,
+                connected: true
+            }
+        );
+
+        drop(lease);
+        assert_eq!(lobby.room(room.id).unwrap().seats[1], SeatInfo::Open);
+        assert!(lobby.join(room.id, 1, None, None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn game_starts_once_every_human_seat_is_ready() {
+        let lobby = Lobby::default();
+        let seats = [
+            SeatConfig::Human,
+            SeatConfig::Human,
+            SeatConfig::Greedy,
+            SeatConfig::Random,
+        ];
+        let room = lobby.create_room(config(&seats, None)).unwrap().info;
+        assert_eq!(
+            lobby.join(room.id, 2, None, None).err(),
+            Some(LobbyError::BotSeat(2))
+        );
+
+        let mut first = lobby.join(room.id, 0, None, None).unwrap();
+        first.ready();
+        assert_eq!(lobby.room(room.id).unwrap().status, RoomStatus::Open);
+
+        let mut second = lobby.join(room.id, 1, None, None).unwrap();
+        second.ready();
+        assert_eq!(lobby.room(room.id).unwrap().status, RoomStatus::Running);
+        assert!(lobby.open_rooms().is_empty());
+    }
+
+    #[tokio::test]
+    async fn empty_seats_are_filled_with_bots() {
+        let lobby = Lobby::default();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(config(&[], Some(BotKind::Random)))
+            .unwrap();
+
+        let mut lease = lobby.join(room.id, 3, None, None).unwrap();
+        lease.ready();
+        lobby.start(room.id, &owner_token).unwrap();
+        assert!(lease.recv().await.is_some());
+        assert_eq!(
+            lobby.room(room.id).unwrap().seats[0],
+            SeatInfo::Bot {
+                bot: BotKind::Random
+            }
+        );
+
+        // without a grace period or a stand-in the game ends right away
+        drop(lease);
+        while lobby.room(room.id).is_some() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn stand_in_plays_on_for_players_who_leave() {
+        let lobby = Lobby::default();
+        let (done, result) = tokio::sync::oneshot::channel();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room_with_hook(
+                RoomConfig {
+                    stand_in: RoomConfig::default().stand_in,
+                    ..config(&[], Some(BotKind::Random))
+                },
+                move |game| {
+                    let _ = done.send(game.result.clone());
+                },
+            )
+            .unwrap();
+
+        let mut lease = lobby.join(room.id, 0, None, None).unwrap();
+        lease.ready();
+        lobby.start(room.id, &owner_token).unwrap();
+        // places the initial settlements and leaves once the game is on
+        loop {
+            let ServerToClient::DecisionRequest(request) = next_request(&mut lease).await else {
+                unreachable!()
+            };
+            if request.kind() != "init_stage" {
+                break;
+            }
+            let response = arena_bot::decide(&request).unwrap();
+            lobby
+                .submit(room.id, 0, lease.token(), request.request_id(), response)
+                .unwrap();
+        }
+        drop(lease);
+
+        let result = result.await.unwrap();
+        assert!(
+            matches!(result, GameResult::Win(_) | GameResult::LimitReached { .. }),
+            "{result:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn creator_starts_rooms_that_fill_empty_seats() {
+        let lobby = Lobby::default();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(config(&[], Some(BotKind::Random)))
+            .unwrap();
+
+        let mut first = lobby.join(room.id, 0, None, None).unwrap();
+        first.ready();
+        let second = lobby.join(room.id, 1, None, None).unwrap();
+        assert_eq!(lobby.room(room.id).unwrap().status, RoomStatus::Open);
+        assert_eq!(
+            lobby.start(room.id, "guess").err(),
+            Some(LobbyError::NotOwner(room.id))
+        );
+        assert_eq!(
+            lobby.start(room.id, &owner_token).err(),
+            Some(LobbyError::NotReady(room.id))
+        );
+
+        drop(second);
+        let started = lobby.start(room.id, &owner_token).unwrap();
+        assert_eq!(started.status, RoomStatus::Running);
+        assert_eq!(
+            started.seats[1],
+            SeatInfo::Bot {
+                bot: BotKind::Random
+            }
+        );
+        assert_eq!(
+            lobby.start(room.id, &owner_token).err(),
+            Some(LobbyError::AlreadyStarted(room.id))
+        );
+        play_out(first).await;
+    }
+
+    #[tokio::test]
+    async fn decisions_are_submitted_with_the_session_token() {
+        let lobby = Lobby::default();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(config(&[], Some(BotKind::Random)))
+            .unwrap();
+
+        let mut lease = lobby.join(room.id, 0, None, None).unwrap();
+        let token = lease.token().to_owned();
+        let end_move = || DecisionResponseFrame::Regular(RegularAction::EndMove);
+        assert_eq!(
+            lobby.submit(room.id, 0, &token, 0, end_move()).err(),
+            Some(LobbyError::NotStarted(room.id))
+        );
+        lease.ready();
+        lobby.start(room.id, &owner_token).unwrap();
+        assert!(lobby.running_games().iter().any(|game| game.id == room.id));
+        let ServerToClient::DecisionRequest(request) = next_request(&mut lease).await else {
+            unreachable!()
+        };
+        let id = request.request_id();
+
+        assert_eq!(
+            lobby.submit(room.id, 0, "guess", id, end_move()).err(),
+            Some(LobbyError::InvalidToken(0))
+        );
+        assert_eq!(
+            lobby.submit(room.id, 1, &token, id, end_move()).err(),
+            Some(LobbyError::BotSeat(1))
+        );
+        assert_eq!(
+            lobby.submit(room.id, 7, &token, id, end_move()).err(),
+            Some(LobbyError::UnknownSeat(7))
+        );
+        assert_eq!(
+            lobby.submit(room.id, 0, &token, id + 1, end_move()).err(),
+            Some(LobbyError::StaleRequest(id + 1))
+        );
+        let response = arena_bot::decide(&request).unwrap();
+        lobby.submit(room.id, 0, &token, id, response).unwrap();
+        play_out(lease).await;
+    }
+
+    #[tokio::test]
+    async fn reconnecting_player_catches_up_with_the_game() {
+        let lobby = Lobby::default();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(RoomConfig {
+                reconnect_grace_secs: 60,
+                ..config(&[], Some(BotKind::Random))
+            })
+            .unwrap();
+
+        let mut lease = lobby.join(room.id, 0, None, None).unwrap();
+        let token = lease.token().to_owned();
+        assert!(lease.ready().is_empty());
+        lobby.start(room.id, &owner_token).unwrap();
+        let ServerToClient::DecisionRequest(request) = next_request(&mut lease).await else {
+            unreachable!()
+        };
+        drop(lease);
+
+        assert_eq!(
+            lobby.room(room.id).unwrap().seats[0],
+            SeatInfo::Joined {
+                ready: true,
+                connected: false
+            }
+        );
+        assert_eq!(
+            lobby.join(room.id, 0, None, None).err(),
+            Some(LobbyError::SeatTaken(0))
+        );
+        assert_eq!(
+            lobby.join(room.id, 0, Some("guess"), None).err(),
+            Some(LobbyError::InvalidToken(0))
+        );
+
+        let mut lease = lobby.join(room.id, 0, Some(&token), None).unwrap();
+        let catch_up = lease.ready();
+        assert!(matches!(
+            catch_up.last(),
+            Some(ServerToClient::DecisionRequest(replayed))
+                if replayed.request_id() == request.request_id()
+        ));
+        assert_eq!(
+            lobby.join(room.id, 0, Some(&token), None).err(),
+            Some(LobbyError::SeatTaken(0))
+        );
+
+        lease.send(ClientToServer::Error {
+            message: "no opinion".to_owned(),
+        });
+        play_out(lease).await;
+    }
+}
@@ -1,18 +1,152 @@
+mod archive;
+mod arena;
+mod arena_bot;
 mod game;
+mod games;
+mod lobby;
+mod player;
 mod protocol;
+mod rooms;
 mod session;
+mod spectate;
 mod ws;
 
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    extract::FromRef,
+    routing::{get, post},
+};
 use tokio::net::TcpListener;
 
+use crate::{
+    archive::Archive,
+    arena::Arena,
+    lobby::{LimitsConfig, Lobby},
+};
+
+const USAGE: &str = "usage: catan-server [--port <n>] [--admin-token <token>] [--archive-dir <path>] [--ladder <path>]";
+
+#[derive(Clone)]
+pub struct AppState {
+    lobby: Lobby,
+    archive: Archive,
+    arena: Arena,
+    /// Unlocks omniscient spectating; disabled when unset.
+    admin_token: Option<String>,
+}
+
+impl FromRef<AppState> for Lobby {
+    fn from_ref(state: &AppState) -> Self {
+        state.lobby.clone()
+    }
+}
+
+impl FromRef<AppState> for Archive {
+    fn from_ref(state: &AppState) -> Self {
+        state.archive.clone()
+    }
+}
+
+impl FromRef<AppState> for Arena {
+    fn from_ref(state: &AppState) -> Self {
+        state.arena.clone()
+    }
+}
+
 #[tokio::main]
 async fn main() {
-    let app = Router::new().route("/ws", get(ws::ws_handler));
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).map(String::as_str) == Some("arena-bot") {
+        let (Some(url), Some(name), Some(key)) = (
+            arg_value(&args, "--url"),
+            arg_value(&args, "--name"),
+            arg_value(&args, "--key"),
+        ) else {
+            eprintln!(
+                "usage: catan-server arena-bot --url <ws://host:port> --name <bot> --key <key>"
+            );
+            std::process::exit(2);
+        };
+        if let Err(err) = arena_bot::run(&url, &name, &key).await {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let port = match parse_arg::<u16>(&args, "--port") {
+        Ok(port) => port.unwrap_or(8080),
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    let archive_dir = arg_value(&args, "--archive-dir").unwrap_or_else(|| "archive".to_owned());
+    let archive = match Archive::open(&archive_dir) {
+        Ok(archive) => archive,
+        Err(err) => {
+            eprintln!("failed to open archive {archive_dir}: {err}");
+            std::process::exit(1);
+        }
+    };
+    let lobby = Lobby::with_archive(archive.clone());
+    let ladder = arg_value(&args, "--ladder").unwrap_or_else(|| "ladder.json".to_owned());
+    let arena = match Arena::open(lobby.clone(), &ladder, LimitsConfig::default()) {
+        Ok(arena) => arena,
+        Err(err) => {
+            eprintln!("failed to open ladder {ladder}: {err}");
+            std::process::exit(1);
+        }
+    };
+    arena.spawn_scheduler();
+
+    let app = Router::new()
+        .route("/ws", get(ws::quick_game))
+        .route("/rooms", get(rooms::list_rooms).post(rooms::create_room))
+        .route("/rooms/{room_id}", get(rooms::get_room))
+        .route("/rooms/{room_id}/start", post(rooms::start_room))
+        .route("/rooms/{room_id}/seats/{seat}", get(ws::join_room))
+        .route("/spectate/{room_id}", get(spectate::spectate))
+        .route("/games", get(games::list_games))
+        .route("/games/{room_id}/state", get(games::game_state))
+        .route("/games/{room_id}/events", get(games::game_events))
+        .route("/games/{room_id}/decision", post(games::submit_decision))
+        .route("/archive", get(archive::list_games))
+        .route("/archive/{game_id}", get(archive::download_game))
+        .route("/arena/bots", post(arena::register_bot))
+        .route("/arena/ladder", get(arena::get_ladder))
+        .route("/arena/connect", get(arena::connect_bot))
+        .with_state(AppState {
+            lobby,
+            archive,
+            arena,
+            admin_token: arg_value(&args, "--admin-token"),
+        });
 
-    println!("Server running on ws://localhost:8080/ws");
+    let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            eprintln!("failed to bind port {port}: {err}");
+            std::process::exit(1);
+        }
+    };
 
-    let listener = TcpListener::bind("0.0.0.0:8080").await.unwrap();
+    println!("Server running on ws://localhost:{port}/ws");
 
     axum::serve(listener, app).await.unwrap();
 }
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], name: &str) -> Result<Option<T>, String> {
+    arg_value(args, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value {value:?} for {name}"))
+        })
+        .transpose()
+}
+
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    args.windows(2)
+        .find_map(|window| (window[0] == name).then(|| window[1].clone()))
+}
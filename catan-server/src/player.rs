@@ -0,0 +1,765 @@
+use std::{
+    sync::{Arc, Mutex, mpsc},
+    time::{Duration, Instant},
+};
+
+use catan_agents::remote_agent::{
+    DecisionRequestEnvelope, DecisionRequestFrame, DecisionResponseFrame, LegalDecisionOptions,
+    UiModel,
+};
+use catan_core::{
+    agent::{
+        action::{
+            ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
+            MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
+        },
+        agent::{AgentError, PlayerRuntime},
+    },
+    gameplay::{
+        game::{
+            event::{GameEvent, PlayerNotification},
+            view::{PlayerDecisionContext, PlayerNotificationContext},
+        },
+        primitives::player::PlayerId,
+    },
+    topology::Hex,
+};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::protocol::{ClientToServer, ServerToClient};
+
+/// What the sessions of a seat tell its game player.
+#[derive(Debug)]
+pub enum SeatInput {
+    Client(ClientToServer),
+    /// Answer posted over HTTP, naming the request it answers.
+    Decision {
+        request_id: u64,
+        response: DecisionResponseFrame,
+    },
+    Connected,
+    Disconnected,
+}
+
+/// Frames from the game to the client of a seat.
+///
+/// Keeps the last event and the outstanding decision request so a
+/// reconnecting client can catch up with the game.
+#[derive(Clone)]
+pub struct SeatOutbox {
+    inner: Arc<Mutex<OutboxState>>,
+}
+
+struct OutboxState {
+    sender: UnboundedSender<ServerToClient>,
+    /// Whether a session reads the receiving end.
+    attached: bool,
+    last_event: Option<ServerToClient>,
+    pending: Option<ServerToClient>,
+}
+
+impl SeatOutbox {
+    pub fn channel() -> (Self, UnboundedReceiver<ServerToClient>) {
+        let (sender, receiver) = unbounded_channel();
+        let outbox = Self {
+            inner: Arc::new(Mutex::new(OutboxState {
+                sender,
+                attached: true,
+                last_event: None,
+                pending: None,
+            })),
+        };
+        (outbox, receiver)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, OutboxState> {
+        self.inner.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Records `msg` for catching up and forwards it if a session is there to
+    /// read it.
+    fn send(&self, msg: ServerToClient) {
+        let mut state = self.lock();
+        match &msg {
+            ServerToClient::Event { .. } => state.last_event = Some(msg.clone()),
+            ServerToClient::DecisionRequest(_) => state.pending = Some(msg.clone()),
+            // deltas are made per connection by the session
+            ServerToClient::Hello { .. }
+            | ServerToClient::EventDelta { .. }
+            | ServerToClient::Error { .. }
+            | ServerToClient::Shutdown { .. } => {}
+        }
+        if state.attached {
+            let _ = state.sender.send(msg);
+        }
+    }
+
+    fn resolve(&self) {
+        self.lock().pending = None;
+    }
+
+    /// Id of the decision request waiting for an answer, if any.
+    pub fn pending_request_id(&self) -> Option<u64> {
+        match &self.lock().pending {
+            Some(ServerToClient::DecisionRequest(request)) => Some(request.request_id()),
+            _ => None,
+        }
+    }
+
+    pub fn detach(&self) {
+        self.lock().attached = false;
+    }
+
+    /// Hands the receiving end to a new session: drops the frames left over
+    /// from the previous one and returns the last event and the outstanding
+    /// request.
+    pub fn attach(&self, receiver: &mut UnboundedReceiver<ServerToClient>) -> Vec<ServerToClient> {
+        let mut state = self.lock();
+        while receiver.try_recv().is_ok() {}
+        state.attached = true;
+        state
+            .last_event
+            .iter()
+            .chain(state.pending.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+/// Responses of the wrong kind a client may send to one request before the
+/// seat stops asking it.
+pub const MAX_WRONG_RESPONSES: usize = 3;
+
+#[derive(Debug, Clone, Copy)]
+enum Connection {
+    Connected,
+    Disconnected(Instant),
+    /// No session can attach to the seat anymore.
+    Closed,
+}
+
+/// Seat of a server-hosted game played over websocket sessions.
+///
+/// Runs on the blocking game thread. Responses of the wrong kind are rejected
+/// with an error frame and the request is sent again, until the client used up
+/// [`MAX_WRONG_RESPONSES`]. While the client is away, the seat waits for it to
+/// reconnect for the grace period; after that, decisions fail with
+/// [`AgentError::Disconnected`] and the controller's agent error policy
+/// decides who plays on.
+pub struct WsPlayer {
+    player_id: PlayerId,
+    inputs: mpsc::Receiver<SeatInput>,
+    outbox: SeatOutbox,
+    next_request_id: u64,
+    connection: Connection,
+    grace: Duration,
+}
+
+impl WsPlayer {
+    /// Takes over a seat whose client already completed the handshake.
+    pub fn new(
+        player_id: PlayerId,
+        inputs: mpsc::Receiver<SeatInput>,
+        outbox: SeatOutbox,
+        grace: Duration,
+    ) -> Self {
+        Self {
+            player_id,
+            inputs,
+            outbox,
+            next_request_id: 0,
+            connection: Connection::Connected,
+            grace,
+        }
+    }
+
+    fn is_away(&self) -> bool {
+        match self.connection {
+            Connection::Connected => false,
+            Connection::Disconnected(since) => since.elapsed() >= self.grace,
+            Connection::Closed => true,
+        }
+    }
+
+    /// Returns client frames and posted decisions that need handling;
+    /// connection changes and client logs are handled on the way.
+    fn handle(&mut self, input: SeatInput) -> Option<SeatInput> {
+        match input {
+            SeatInput::Connected => {
+                log::info!("player {} reconnected", self.player_id);
+                self.connection = Connection::Connected;
+            }
+            SeatInput::Disconnected => {
+                log::info!("player {} disconnected", self.player_id);
+                self.connection = Connection::Disconnected(Instant::now());
+            }
+            SeatInput::Client(ClientToServer::Log {
+                level,
+                target,
+                message,
+            }) => log::log!(
+                level.into(),
+                "player {} {target}: {message}",
+                self.player_id
+            ),
+            input @ (SeatInput::Client(_) | SeatInput::Decision { .. }) => return Some(input),
+        }
+        None
+    }
+
+    /// Catches up with connection changes, dropping client frames nobody
+    /// asked for.
+    fn poll(&mut self) {
+        loop {
+            match self.inputs.try_recv() {
+                Ok(input) => {
+                    if let Some(input) = self.handle(input) {
+                        log::warn!("player {} sent unexpected {input:?}", self.player_id);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.connection = Connection::Closed;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Next client frame or posted decision. Fails once the `deadline` of a
+    /// timed game passes or the grace period of a disconnected client runs
+    /// out, whichever comes first.
+    fn recv(&mut self, deadline: Option<Instant>) -> Result<SeatInput, AgentError> {
+        let player_id = self.player_id;
+        let left = || AgentError::Disconnected(format!("player {player_id} left the game"));
+        loop {
+            let until = match self.connection {
+                Connection::Connected => deadline,
+                Connection::Disconnected(since) => {
+                    Some(deadline.map_or(since + self.grace, |deadline| {
+                        deadline.min(since + self.grace)
+                    }))
+                }
+                Connection::Closed => return Err(left()),
+            };
+            let input = match until {
+                None => self.inputs.recv().ok(),
+                Some(until) => match self
+                    .inputs
+                    .recv_timeout(until.saturating_duration_since(Instant::now()))
+                {
+                    Ok(input) => Some(input),
+                    Err(mpsc::RecvTimeoutError::Timeout) if deadline == Some(until) => {
+                        return Err(AgentError::TimedOut);
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        return Err(AgentError::Disconnected(format!(
+                            "player {player_id} did not come back within the grace period"
+                        )));
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => None,
+                },
+            };
+            let Some(input) = input else {
+                self.connection = Connection::Closed;
+                return Err(left());
+            };
+            if let Some(input) = self.handle(input) {
+                return Ok(input);
+            }
+        }
+    }
+
+    /// Sends a decision request and waits for a response of the matching kind.
+    /// Posted decisions naming another request are dropped. Fails when the
+    /// client is gone, ran out of time, reported an error or kept answering
+    /// with the wrong kind.
+    fn decide<T>(
+        &mut self,
+        context: &PlayerDecisionContext<'_>,
+        robber_pos: Option<Hex>,
+        frame: fn(DecisionRequestEnvelope) -> DecisionRequestFrame,
+        accept: fn(DecisionResponseFrame) -> Option<T>,
+    ) -> Result<T, AgentError> {
+        self.poll();
+        if self.is_away() {
+            return Err(AgentError::Disconnected(format!(
+                "player {} is away",
+                self.player_id
+            )));
+        }
+
+        let request_id = self.next_request_id;
+        self.next_request_id += 1;
+        let request = frame(DecisionRequestEnvelope {
+            request_id,
+            view: UiModel::from_decision(context),
+            legal: LegalDecisionOptions::from_context(context, robber_pos),
+        });
+        let kind = request.kind();
+        let deadline = context.clock().map(|left| Instant::now() + left);
+
+        self.outbox
+            .send(ServerToClient::DecisionRequest(request.clone()));
+        let mut wrong_responses = 0;
+        let decision = loop {
+            let input = match self.recv(deadline) {
+                Ok(input) => input,
+                Err(AgentError::TimedOut) => {
+                    self.outbox.send(ServerToClient::Error {
+                        message: format!("ran out of time for request id={request_id}"),
+                    });
+                    break Err(AgentError::TimedOut);
+                }
+                Err(err) => break Err(err),
+            };
+            match input {
+                SeatInput::Decision {
+                    request_id: answered,
+                    ..
+                } if answered != request_id => log::warn!(
+                    "player {} posted a decision for request id={answered} while id={request_id} is outstanding",
+                    self.player_id
+                ),
+                SeatInput::Client(ClientToServer::DecisionResponse(response))
+                | SeatInput::Decision { response, .. } => match accept(response) {
+                    Some(action) => break Ok(action),
+                    None => {
+                        wrong_responses += 1;
+                        log::warn!(
+                            "player {} answered request id={request_id} kind={kind} with a wrong response kind",
+                            self.player_id
+                        );
+                        if wrong_responses >= MAX_WRONG_RESPONSES {
+                            break Err(AgentError::Protocol(format!(
+                                "answered request id={request_id} kind={kind} with the wrong kind {wrong_responses} times"
+                            )));
+                        }
+                        self.outbox.send(ServerToClient::Error {
+                            message: format!("expected a {kind} response"),
+                        });
+                        self.outbox
+                            .send(ServerToClient::DecisionRequest(request.clone()));
+                    }
+                },
+                SeatInput::Client(ClientToServer::Error { message }) => {
+                    break Err(AgentError::Protocol(format!(
+                        "failed request id={request_id} kind={kind}: {message}"
+                    )));
+                }
+                other => log::warn!("player {} sent unexpected {other:?}", self.player_id),
+            }
+        };
+        self.outbox.resolve();
+        decision
+    }
+}
+
+impl PlayerNotification for WsPlayer {
+    fn on_event(&mut self, event: &GameEvent, context: PlayerNotificationContext<'_>) {
+        self.poll();
+        let view = UiModel::from_player_notification(&context);
+        self.outbox.send(ServerToClient::Event {
+            event: event.clone(),
+            view,
+        });
+    }
+}
+
+impl PlayerRuntime for WsPlayer {
+    fn player_id(&self) -> PlayerId {
+        self.player_id
+    }
+
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::InitStage,
+            |response| match response {
+                DecisionResponseFrame::InitStage(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::InitAction,
+            |response| match response {
+                DecisionResponseFrame::InitAction(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::PostDice,
+            |response| match response {
+                DecisionResponseFrame::PostDice(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn after_dev_card_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::PostDevCard,
+            |response| match response {
+                DecisionResponseFrame::PostDevCard(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::Regular,
+            |response| match response {
+                DecisionResponseFrame::Regular(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::MoveRobbers,
+            |response| match response {
+                DecisionResponseFrame::MoveRobbers(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn choose_player_to_rob(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+        robber_pos: Hex,
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
+        self.decide(
+            &context,
+            Some(robber_pos),
+            DecisionRequestFrame::ChoosePlayerToRob,
+            |response| match response {
+                DecisionResponseFrame::ChoosePlayerToRob(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn answer_trade(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::AnswerTrade,
+            |response| match response {
+                DecisionResponseFrame::AnswerTrade(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
+        self.decide(
+            &context,
+            None,
+            DecisionRequestFrame::DropHalf,
+            |response| match response {
+                DecisionResponseFrame::DropHalf(action) => Some(action),
+                _ => None,
+            },
+        )
+    }
+}
+
+impl Drop for WsPlayer {
+    fn drop(&mut self) {
+        self.outbox.send(ServerToClient::Shutdown {
+            reason: "game finished".to_owned(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catan_agents::lazy::LazyAgent;
+    use catan_core::{
+        gameplay::{
+            agent::agent::Agent,
+            game::{
+                controller::{
+                    AgentErrorPolicy, GameController, GameResult, RunOptions, TimeControl,
+                    TimeoutRule,
+                },
+                init::GameInitializationState,
+            },
+        },
+        math::dice::RandomDiceRoller,
+    };
+
+    use super::*;
+
+    /// Seats a [`WsPlayer`] as player 0 among lazy bots. Returns the agents
+    /// along with the client's ends of the seat's channels.
+    fn seat(
+        init: &GameInitializationState,
+    ) -> (
+        Vec<Box<dyn Agent>>,
+        mpsc::Sender<SeatInput>,
+        UnboundedReceiver<ServerToClient>,
+    ) {
+        let (to_game, inputs) = mpsc::channel();
+        let (outbox, from_game) = SeatOutbox::channel();
+        let agents = std::iter::once(
+            Box::new(WsPlayer::new(0, inputs, outbox, Duration::ZERO)) as Box<dyn Agent>
+        )
+        .chain((1..init.board.n_players).map(|id| Box::new(LazyAgent::new(id)) as Box<dyn Agent>))
+        .collect();
+        (agents, to_game, from_game)
+    }
+
+    #[test]
+    fn wrong_responses_are_rejected_and_disconnects_hand_seat_to_stand_in() {
+        let init = GameInitializationState::default();
+        let (mut agents, to_game, mut from_game) = seat(&init);
+
+        // answers the first request with the wrong kind, then places both
+        // initial settlements and leaves
+        let client = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            while requests.len() < 3 {
+                let Some(ServerToClient::DecisionRequest(request)) = from_game.blocking_recv()
+                else {
+                    continue;
+                };
+                let response = match &request {
+                    _ if requests.is_empty() => {
+                        DecisionResponseFrame::Regular(RegularAction::EndMove)
+                    }
+                    DecisionRequestFrame::InitStage(envelope) => {
+                        DecisionResponseFrame::InitStage(envelope.legal.initial_placements[0])
+                    }
+                    other => panic!("expected an initial placement request, got {other:?}"),
+                };
+                requests.push(request);
+                to_game
+                    .send(SeatInput::Client(ClientToServer::DecisionResponse(
+                        response,
+                    )))
+                    .unwrap();
+            }
+            requests
+        });
+
+        let state = GameController::init(init, &mut agents).unwrap();
+        let requests = client.join().unwrap();
+        let mut controller = GameController::new(state, agents);
+        controller.set_stand_in(|id| Box::new(LazyAgent::new(id)));
+        let result = controller.run_with_options(
+            &mut RandomDiceRoller::new(),
+            RunOptions {
+                max_turns: Some(20),
+                agent_errors: AgentErrorPolicy::Fallback,
+                ..RunOptions::default()
+            },
+        );
+
+        assert_eq!(requests[0].kind(), "init_stage");
+        assert_eq!(requests[1].request_id(), requests[0].request_id());
+        assert!(!matches!(result, GameResult::Interrupted { .. }));
+    }
+
+    #[test]
+    fn clients_that_walk_away_run_out_of_time() {
+        let init = GameInitializationState::default();
+        let (mut agents, to_game, mut from_game) = seat(&init);
+
+        // stays connected, but never answers once the game started
+        let client = std::thread::spawn(move || {
+            let mut errors = Vec::new();
+            while let Some(msg) = from_game.blocking_recv() {
+                match msg {
+                    ServerToClient::DecisionRequest(DecisionRequestFrame::InitStage(envelope)) => {
+                        let placement = envelope.legal.initial_placements[0];
+                        to_game
+                            .send(SeatInput::Client(ClientToServer::DecisionResponse(
+                                DecisionResponseFrame::InitStage(placement),
+                            )))
+                            .unwrap();
+                    }
+                    ServerToClient::Error { message } => errors.push(message),
+                    ServerToClient::Shutdown { .. } => break,
+                    _ => {}
+                }
+            }
+            errors
+        });
+
+        let state = GameController::init(init, &mut agents).unwrap();
+        let mut controller = GameController::new(state, agents);
+        let result = controller.run_with_options(
+            &mut RandomDiceRoller::new(),
+            RunOptions {
+                max_turns: Some(8),
+                time_control: Some(TimeControl {
+                    bank: Duration::from_millis(50),
+                    increment: Duration::ZERO,
+                    on_timeout: TimeoutRule::EndTurn,
+                }),
+                ..RunOptions::default()
+            },
+        );
+        drop(controller);
+
+        assert_eq!(result, GameResult::LimitReached { turns: 8 });
+        assert_eq!(client.join().unwrap(), ["ran out of time for request id=2"]);
+    }
+
+    #[test]
+    fn decisions_fail_once_the_client_is_gone() {
+        let init = GameInitializationState::default();
+        let (mut agents, to_game, _from_game) = seat(&init);
+        drop(to_game);
+
+        let Err(GameResult::Interrupted { reason }) = GameController::init(init, &mut agents)
+        else {
+            panic!("expected setup to be interrupted");
+        };
+        assert!(reason.starts_with("Player#0 failed to decide: disconnected"));
+    }
+
+    #[test]
+    fn posted_decisions_for_other_requests_are_dropped() {
+        let init = GameInitializationState::default();
+        let (mut agents, to_game, mut from_game) = seat(&init);
+
+        let client = std::thread::spawn(move || {
+            let mut chosen = None;
+            while let Some(msg) = from_game.blocking_recv() {
+                match msg {
+                    ServerToClient::DecisionRequest(DecisionRequestFrame::InitStage(envelope))
+                        if chosen.is_none() =>
+                    {
+                        let placements = &envelope.legal.initial_placements;
+                        let (placement, other) = (placements[0], placements[placements.len() - 1]);
+                        for (request_id, action) in [
+                            (envelope.request_id + 1, other),
+                            (envelope.request_id, placement),
+                        ] {
+                            to_game
+                                .send(SeatInput::Decision {
+                                    request_id,
+                                    response: DecisionResponseFrame::InitStage(action),
+                                })
+                                .unwrap();
+                        }
+                        chosen = Some((
+                            placement.establishment_position,
+                            other.establishment_position,
+                        ));
+                    }
+                    ServerToClient::DecisionRequest(DecisionRequestFrame::InitStage(envelope)) => {
+                        let (_, stale) = chosen.unwrap();
+                        let placement = envelope
+                            .legal
+                            .initial_placements
+                            .into_iter()
+                            .find(|placement| placement.establishment_position != stale)
+                            .unwrap();
+                        let _ = to_game.send(SeatInput::Decision {
+                            request_id: envelope.request_id,
+                            response: DecisionResponseFrame::InitStage(placement),
+                        });
+                    }
+                    ServerToClient::Shutdown { .. } => break,
+                    _ => {}
+                }
+            }
+            chosen
+        });
+
+        let state = GameController::init(init, &mut agents).unwrap();
+        drop(agents);
+
+        let (chosen, stale) = client.join().unwrap().unwrap();
+        let built = state.builds[0]
+            .establishments
+            .iter()
+            .map(|establishment| establishment.pos)
+            .collect::<Vec<_>>();
+        assert!(built.contains(&chosen));
+        assert!(!built.contains(&stale));
+    }
+
+    #[test]
+    fn clients_that_keep_answering_the_wrong_kind_are_given_up_on() {
+        let init = GameInitializationState::default();
+        let (mut agents, to_game, mut from_game) = seat(&init);
+
+        let client = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            let mut errors = 0;
+            while let Some(msg) = from_game.blocking_recv() {
+                match msg {
+                    ServerToClient::DecisionRequest(request) => {
+                        requests.push(request.request_id());
+                        let _ = to_game.send(SeatInput::Client(ClientToServer::DecisionResponse(
+                            DecisionResponseFrame::AnswerTrade(TradeAnswer::Decline),
+                        )));
+                    }
+                    ServerToClient::Error { .. } => errors += 1,
+                    ServerToClient::Shutdown { .. } => break,
+                    _ => {}
+                }
+            }
+            (requests, errors)
+        });
+
+        let result = GameController::init(init, &mut agents);
+        drop(agents);
+
+        let (requests, errors) = client.join().unwrap();
+        assert_eq!(requests, [requests[0]; MAX_WRONG_RESPONSES]);
+        assert_eq!(errors, MAX_WRONG_RESPONSES - 1);
+        let Err(GameResult::Interrupted { reason }) = result else {
+            panic!("expected setup to be interrupted");
+        };
+        assert!(reason.contains("protocol violation"));
+    }
+}
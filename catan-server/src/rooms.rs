@@ -0,0 +1,69 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+
+use serde::Deserialize;
+
+use crate::lobby::{CreatedRoom, Lobby, LobbyError, RoomConfig, RoomId, RoomInfo};
+
+impl IntoResponse for LobbyError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            Self::InvalidConfig(_) => StatusCode::BAD_REQUEST,
+            Self::UnknownRoom(_) | Self::UnknownSeat(_) => StatusCode::NOT_FOUND,
+            Self::InvalidToken(_) | Self::NotOwner(_) => StatusCode::FORBIDDEN,
+            Self::BotSeat(_)
+            | Self::SeatTaken(_)
+            | Self::AlreadyStarted(_)
+            | Self::NotStarted(_)
+            | Self::NotReady(_)
+            | Self::StaleRequest(_) => StatusCode::CONFLICT,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
+
+pub async fn list_rooms(State(lobby): State<Lobby>) -> Json<Vec<RoomInfo>> {
+    Json(lobby.open_rooms())
+}
+
+/// Answers with the room and the owner token its creator starts it with.
+pub async fn create_room(
+    State(lobby): State<Lobby>,
+    Json(config): Json<RoomConfig>,
+) -> Result<(StatusCode, Json<CreatedRoom>), LobbyError> {
+    let room = lobby.create_room(config)?;
+    log::info!("room {} created", room.info.id);
+    Ok((StatusCode::CREATED, Json(room)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartQuery {
+    /// Owner token handed out when the room was created.
+    token: String,
+}
+
+/// Starts a room before every human seat is taken; the open seats go to the
+/// room's `fill_empty_seats` bot.
+pub async fn start_room(
+    State(lobby): State<Lobby>,
+    Path(room_id): Path<RoomId>,
+    Query(query): Query<StartQuery>,
+) -> Result<Json<RoomInfo>, LobbyError> {
+    let room = lobby.start(room_id, &query.token)?;
+    log::info!("room {room_id} started by its creator");
+    Ok(Json(room))
+}
+
+pub async fn get_room(
+    State(lobby): State<Lobby>,
+    Path(room_id): Path<RoomId>,
+) -> Result<Json<RoomInfo>, LobbyError> {
+    lobby
+        .room(room_id)
+        .map(Json)
+        .ok_or(LobbyError::UnknownRoom(room_id))
+}
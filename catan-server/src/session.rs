@@ -1,42 +1,182 @@
-use crate::protocol::*;
+use crate::{lobby::SeatLease, protocol::*};
 use axum::extract::ws::{Message, WebSocket};
-use tokio::sync::mpsc::{Receiver, Sender};
+use catan_agents::remote_agent::{
+    Capability, DecisionRequestFrame, DecisionResponseFrame, HOST_CAPABILITIES, ViewEncoder,
+};
+use catan_core::agent::action::TradeAnswer;
 
+/// Bridges a websocket to the game thread: text frames carry JSON encoded
+/// [`ClientToServer`] and [`ServerToClient`] messages.
 pub struct PlayerSession {
     socket: WebSocket,
-    from_game: Receiver<ServerToClient>,
-    to_game: Sender<ClientToServer>,
+    seat: SeatLease,
+    /// Last decision request, sent again when the client answers it with a
+    /// malformed frame.
+    pending: Option<String>,
+    /// Negotiated in the last handshake; trade offers are declined for
+    /// clients without [`Capability::Trades`].
+    capabilities: Vec<Capability>,
+    /// Turns event views into deltas for clients with [`Capability::Diffs`].
+    views: ViewEncoder,
 }
 
 impl PlayerSession {
-    pub fn new(
-        socket: WebSocket,
-        from_game: Receiver<ServerToClient>,
-        to_game: Sender<ClientToServer>,
-    ) -> Self {
+    pub fn new(socket: WebSocket, seat: SeatLease) -> Self {
         Self {
             socket,
-            from_game,
-            to_game,
+            seat,
+            pending: None,
+            capabilities: Capability::legacy(),
+            views: ViewEncoder::default(),
         }
     }
 
-    pub async fn run(mut self) {
+    pub fn with_capabilities(mut self, capabilities: Vec<Capability>) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Sends `catch_up` and runs until either side goes away. Dropping the
+    /// session releases the seat, which waits for the player to reconnect.
+    pub async fn run(mut self, catch_up: Vec<ServerToClient>) {
+        if self.relay(catch_up, true).await {
+            let _ = self.socket.send(Message::Close(None)).await;
+        }
+    }
+
+    /// Runs one game on a socket that outlives it, so the game's `Shutdown`
+    /// is not forwarded. Hands the socket back unless it went away.
+    pub async fn run_game(mut self) -> Option<WebSocket> {
+        if self.relay(Vec::new(), false).await {
+            Some(self.socket)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `false` once the socket is gone.
+    async fn relay(&mut self, catch_up: Vec<ServerToClient>, forward_shutdown: bool) -> bool {
+        for msg in catch_up {
+            if !self.forward(msg).await {
+                return false;
+            }
+        }
+
         loop {
             tokio::select! {
-                Some(msg) = self.socket.recv() => {
-                    if let Ok(Message::Text(text)) = msg {
-                        let parsed: ClientToServer =
-                            serde_json::from_str(&text).unwrap();
-                        self.to_game.send(parsed).await.unwrap();
+                msg = self.socket.recv() => {
+                    let text = match msg {
+                        Some(Ok(Message::Text(text))) => text,
+                        Some(Ok(Message::Binary(_))) => {
+                            if !self.reject("binary frames are not supported").await {
+                                return false;
+                            }
+                            continue;
+                        }
+                        Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                        Some(Ok(Message::Close(_))) | None => return false,
+                        Some(Err(err)) => {
+                            log::info!("websocket error: {err}");
+                            return false;
+                        }
+                    };
+
+                    let msg = match serde_json::from_str::<ClientToServer>(&text) {
+                        Ok(msg) => msg,
+                        Err(err) => {
+                            if !self.reject(&format!("malformed message: {err}")).await {
+                                return false;
+                            }
+                            continue;
+                        }
+                    };
+                    // arena bots are greeted for every game and answer
+                    // each time
+                    if let Some(negotiated) = msg.negotiate(HOST_CAPABILITIES) {
+                        match negotiated {
+                            Ok(capabilities) => {
+                                self.capabilities = capabilities;
+                                self.views = ViewEncoder::default();
+                            }
+                            Err(reason) => {
+                                log::info!("refused client: {reason}");
+                                let _ = self.forward(ServerToClient::Shutdown { reason }).await;
+                                return false;
+                            }
+                        }
+                        continue;
+                    }
+                    if matches!(msg, ClientToServer::DecisionResponse(_)) {
+                        self.pending = None;
+                    }
+                    if !self.seat.send(msg) {
+                        return true;
                     }
                 }
 
-                Some(msg) = self.from_game.recv() => {
-                    let text = serde_json::to_string(&msg).unwrap();
-                    self.socket.send(Message::Text(text.into())).await.unwrap();
+                msg = self.seat.recv() => {
+                    let Some(msg) = msg else {
+                        return true;
+                    };
+                    if matches!(msg, ServerToClient::Shutdown { .. }) {
+                        return !forward_shutdown || self.forward(msg).await;
+                    }
+                    if !self.forward(msg).await {
+                        return false;
+                    }
                 }
             }
         }
     }
+
+    /// Returns `false` once the socket is gone.
+    async fn forward(&mut self, mut msg: ServerToClient) -> bool {
+        if matches!(
+            msg,
+            ServerToClient::DecisionRequest(DecisionRequestFrame::AnswerTrade(_))
+        ) && !self.capabilities.contains(&Capability::Trades)
+        {
+            self.seat.send(ClientToServer::DecisionResponse(
+                DecisionResponseFrame::AnswerTrade(TradeAnswer::Decline),
+            ));
+            return true;
+        }
+        if self.capabilities.contains(&Capability::Diffs)
+            && let ServerToClient::Event { event, view } = msg
+        {
+            msg = self.views.encode(event, view);
+        }
+        let text = match serde_json::to_string(&msg) {
+            Ok(text) => text,
+            Err(err) => {
+                log::error!("failed to encode server message: {err}");
+                return false;
+            }
+        };
+        if matches!(msg, ServerToClient::DecisionRequest(_)) {
+            self.pending = Some(text.clone());
+        }
+        self.send_text(text).await
+    }
+
+    /// Drops a frame that can't be handled, tells the client why and repeats
+    /// the outstanding request. Returns `false` once the socket is gone.
+    async fn reject(&mut self, reason: &str) -> bool {
+        log::warn!("rejected client frame: {reason}");
+        let error = ServerToClient::Error {
+            message: reason.to_owned(),
+        };
+        let text = serde_json::to_string(&error).expect("server frames are serializable");
+        if !self.send_text(text).await {
+            return false;
+        }
+        match self.pending.clone() {
+            Some(request) => self.send_text(request).await,
+            None => true,
+        }
+    }
+
+    async fn send_text(&mut self, text: String) -> bool {
+        self.socket.send(Message::Text(text.into())).await.is_ok()
+    }
 }
@@ -0,0 +1,407 @@
+//! Spectator streams of server-hosted games.
+//!
+//! Every room publishes two feeds: the public one, projected through the
+//! spectator visibility policy and optionally held back for some turns so
+//! that spectators can't tip players off, and an omniscient one for admins.
+//! Frames are serialized once and shared by all subscribers.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{
+        Path, Query, State,
+        ws::{Message, Utf8Bytes, WebSocket, WebSocketUpgrade},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use catan_agents::remote_agent::{CliRole, UiModel};
+use catan_core::gameplay::game::event::{
+    GameEvent, GameObserver, ObserverKind, ObserverNotificationContext,
+};
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::{
+    AppState,
+    lobby::{LobbyError, RoomId},
+    protocol::ServerToClient,
+};
+
+const FEED_CAPACITY: usize = 256;
+
+#[derive(Clone)]
+struct Feed {
+    sender: broadcast::Sender<Utf8Bytes>,
+    latest: Arc<Mutex<Latest>>,
+}
+
+/// Starting point of late subscribers.
+#[derive(Default)]
+struct Latest {
+    frame: Option<Utf8Bytes>,
+    view: Option<UiModel>,
+}
+
+impl Feed {
+    fn new() -> Self {
+        Self {
+            sender: broadcast::channel(FEED_CAPACITY).0,
+            latest: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Latest> {
+        self.latest.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn publish(&self, msg: ServerToClient) {
+        let Some(frame) = encode(&msg) else {
+            return;
+        };
+        let mut latest = self.lock();
+        if let ServerToClient::Event { view, .. } = msg {
+            latest.view = Some(view);
+        }
+        latest.frame = Some(frame.clone());
+        let _ = self.sender.send(frame);
+    }
+
+    fn subscribe(&self) -> (Option<Utf8Bytes>, broadcast::Receiver<Utf8Bytes>) {
+        let latest = self.lock();
+        (latest.frame.clone(), self.sender.subscribe())
+    }
+}
+
+/// Spectator feeds of one room.
+#[derive(Clone)]
+pub struct SpectatorFeeds {
+    public: Feed,
+    omniscient: Feed,
+    delay_turns: u64,
+}
+
+impl SpectatorFeeds {
+    pub fn new(delay_turns: u64) -> Self {
+        Self {
+            public: Feed::new(),
+            omniscient: Feed::new(),
+            delay_turns,
+        }
+    }
+
+    /// Latest view of the public feed, `None` until the game starts.
+    pub fn public_view(&self) -> Option<UiModel> {
+        self.public.lock().view.clone()
+    }
+
+    /// Latest frame and the frames after it, JSON encoded
+    /// [`ServerToClient`] messages.
+    pub fn subscribe(
+        &self,
+        kind: ObserverKind,
+    ) -> (Option<Utf8Bytes>, broadcast::Receiver<Utf8Bytes>) {
+        self.feed(kind).subscribe()
+    }
+
+    fn feed(&self, kind: ObserverKind) -> &Feed {
+        match kind {
+            ObserverKind::Omniscient => &self.omniscient,
+            ObserverKind::Spectator | ObserverKind::Player(_) => &self.public,
+        }
+    }
+
+    /// Observers publishing a game to the feeds.
+    pub fn observers(&self) -> Vec<Box<dyn GameObserver>> {
+        vec![
+            Box::new(FeedObserver::new(
+                ObserverKind::Spectator,
+                self.public.clone(),
+                self.delay_turns,
+            )),
+            Box::new(FeedObserver::new(
+                ObserverKind::Omniscient,
+                self.omniscient.clone(),
+                0,
+            )),
+        ]
+    }
+}
+
+struct FeedObserver {
+    kind: ObserverKind,
+    feed: Feed,
+    delay_turns: u64,
+    turn_no: u64,
+    /// Events held back, with the turn they belong to.
+    delayed: VecDeque<(u64, ServerToClient)>,
+}
+
+impl FeedObserver {
+    fn new(kind: ObserverKind, feed: Feed, delay_turns: u64) -> Self {
+        Self {
+            kind,
+            feed,
+            delay_turns,
+            turn_no: 0,
+            delayed: VecDeque::new(),
+        }
+    }
+}
+
+fn encode(msg: &ServerToClient) -> Option<Utf8Bytes> {
+    match serde_json::to_string(msg) {
+        Ok(text) => Some(text.into()),
+        Err(err) => {
+            log::error!("failed to encode spectator frame: {err}");
+            None
+        }
+    }
+}
+
+impl GameObserver for FeedObserver {
+    fn kind(&self) -> ObserverKind {
+        self.kind
+    }
+
+    fn on_event(&mut self, event: &GameEvent, context: ObserverNotificationContext<'_>) {
+        if let GameEvent::TurnStarted { turn_no, .. } = event {
+            self.turn_no = *turn_no;
+        }
+        self.delayed.push_back((
+            self.turn_no,
+            ServerToClient::Event {
+                event: event.clone(),
+                view: UiModel::from_observer(context, false),
+            },
+        ));
+
+        while let Some((turn_no, _)) = self.delayed.front()
+            && turn_no + self.delay_turns <= self.turn_no
+        {
+            let (_, msg) = self.delayed.pop_front().expect("front event exists");
+            self.feed.publish(msg);
+        }
+    }
+}
+
+impl Drop for FeedObserver {
+    /// Once the game is over, nothing is left to hide.
+    fn drop(&mut self) {
+        for (_, msg) in self.delayed.drain(..) {
+            self.feed.publish(msg);
+        }
+        self.feed.publish(ServerToClient::Shutdown {
+            reason: "game finished".to_owned(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpectateMode {
+    #[default]
+    Public,
+    Omniscient,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpectateQuery {
+    #[serde(default)]
+    mode: SpectateMode,
+    admin_token: Option<String>,
+}
+
+pub async fn spectate(
+    State(state): State<AppState>,
+    Path(room_id): Path<RoomId>,
+    Query(query): Query<SpectateQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let (kind, role) = match query.mode {
+        SpectateMode::Public => (ObserverKind::Spectator, CliRole::Spectator),
+        SpectateMode::Omniscient => {
+            let authorized = state
+                .admin_token
+                .as_deref()
+                .is_some_and(|token| query.admin_token.as_deref() == Some(token));
+            if !authorized {
+                return (
+                    StatusCode::FORBIDDEN,
+                    "omniscient spectating needs the admin token",
+                )
+                    .into_response();
+            }
+            (ObserverKind::Omniscient, CliRole::Omniscient)
+        }
+    };
+
+    let Some(feeds) = state.lobby.spectator_feeds(room_id) else {
+        return LobbyError::UnknownRoom(room_id).into_response();
+    };
+    let (last, frames) = feeds.subscribe(kind);
+    ws.on_upgrade(move |socket| stream(socket, role, last, frames))
+}
+
+async fn stream(
+    mut socket: WebSocket,
+    role: CliRole,
+    last: Option<Utf8Bytes>,
+    mut frames: broadcast::Receiver<Utf8Bytes>,
+) {
+    let hello = ServerToClient::hello(role, None);
+    let Some(hello) = encode(&hello) else {
+        return;
+    };
+    for frame in std::iter::once(hello).chain(last) {
+        if socket.send(Message::Text(frame)).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Ok(frame) => {
+                    if socket.send(Message::Text(frame)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!("spectator lagged behind by {skipped} frames");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+
+            // spectators have nothing to say, only a close is of interest
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use catan_core::gameplay::game::{
+        index::GameIndex,
+        init::GameInitializationState,
+        view::{ContextFactory, VisibilityConfig},
+    };
+
+    use super::*;
+    use crate::{
+        game::BotKind,
+        lobby::{CreatedRoom, LimitsConfig, Lobby, RoomConfig},
+    };
+
+    fn received(frames: &mut broadcast::Receiver<Utf8Bytes>) -> Vec<String> {
+        std::iter::from_fn(|| frames.try_recv().ok())
+            .map(|frame| frame.as_str().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn public_feed_is_held_back_for_delay_turns() {
+        let feeds = SpectatorFeeds::new(1);
+        let (last, mut frames) = feeds.public.subscribe();
+        assert!(last.is_none());
+
+        let state = GameInitializationState::default().finish();
+        let index = GameIndex::rebuild(&state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state: &state,
+            index: &index,
+            visibility: &visibility,
+        };
+        let mut observer = feeds
+            .observers()
+            .into_iter()
+            .find(|observer| observer.kind() == ObserverKind::Spectator)
+            .unwrap();
+        // the closure borrows the observer until the end of the block
+        {
+            let mut emit = |event: GameEvent| {
+                observer.on_event(
+                    &event,
+                    ObserverNotificationContext::Spectator {
+                        public: factory.spectator_public_view(),
+                    },
+                )
+            };
+
+            emit(GameEvent::TurnStarted {
+                player_id: 0,
+                turn_no: 1,
+            });
+            emit(GameEvent::ResourcesDistributed);
+            assert!(received(&mut frames).is_empty());
+            assert!(feeds.public_view().is_none());
+
+            emit(GameEvent::TurnStarted {
+                player_id: 1,
+                turn_no: 2,
+            });
+            let released = received(&mut frames);
+            assert_eq!(released.len(), 2);
+            assert!(released[1].contains("ResourcesDistributed"));
+            assert!(feeds.public.subscribe().0.is_some());
+            assert!(feeds.public_view().is_some());
+        }
+        drop(observer);
+        let flushed = received(&mut frames);
+        assert_eq!(flushed.len(), 2);
+        assert!(flushed[0].contains("TurnStarted"));
+        assert!(flushed[1].contains("Shutdown"));
+    }
+
+    #[tokio::test]
+    async fn spectators_follow_a_room_until_the_game_ends() {
+        let lobby = Lobby::default();
+        let CreatedRoom {
+            info: room,
+            owner_token,
+        } = lobby
+            .create_room(RoomConfig {
+                fill_empty_seats: Some(BotKind::Random),
+                limits: LimitsConfig {
+                    max_turns: Some(4),
+                    ..LimitsConfig::default()
+                },
+                reconnect_grace_secs: 0,
+                spectator_delay_turns: 2,
+                ..RoomConfig::default()
+            })
+            .unwrap();
+        assert!(lobby.spectator_feeds(room.id + 1).is_none());
+        let feeds = lobby.spectator_feeds(room.id).unwrap();
+        let (_, mut public) = feeds.public.subscribe();
+        let (_, mut omniscient) = feeds.omniscient.subscribe();
+        drop(feeds);
+
+        // the player walks away and a bot plays the seat
+        let mut lease = lobby.join(room.id, 0, None, None).unwrap();
+        lease.ready();
+        lobby.start(room.id, &owner_token).unwrap();
+        drop(lease);
+
+        for frames in [&mut public, &mut omniscient] {
+            let mut last = None;
+            loop {
+                match frames.recv().await {
+                    Ok(frame) => last = Some(frame),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            assert!(last.unwrap().as_str().contains("Shutdown"));
+        }
+    }
+}
@@ -1,16 +1,268 @@
-use axum::{extract::ws::WebSocketUpgrade, response::IntoResponse};
-use tokio::sync::mpsc;
+use axum::{
+    extract::{
+        Path, Query, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::{IntoResponse, Response},
+};
+use catan_agents::remote_agent::{CliRole, HOST_CAPABILITIES};
+use catan_core::gameplay::{game::init::GameInitializationState, primitives::player::PlayerId};
+use serde::Deserialize;
 
-use crate::{game::spawn_game, session::PlayerSession};
+use crate::{
+    lobby::{Lobby, RoomConfig, RoomId, SeatConfig, SeatLease},
+    protocol::{ClientToServer, ServerToClient},
+    session::PlayerSession,
+};
 
-pub async fn ws_handler(ws: WebSocketUpgrade) -> impl IntoResponse {
-    ws.on_upgrade(|socket| async move {
-        let (to_game_tx, to_game_rx) = mpsc::channel(32);
-        let (from_game_tx, from_game_rx) = mpsc::channel(32);
+/// Plays a fresh room against greedy bots.
+pub async fn quick_game(State(lobby): State<Lobby>, ws: WebSocketUpgrade) -> Response {
+    let n_players = GameInitializationState::default().board.n_players;
+    let config = RoomConfig {
+        name: "quick game".to_owned(),
+        seats: std::iter::once(SeatConfig::Human)
+            .chain(std::iter::repeat_n(SeatConfig::Greedy, n_players - 1))
+            .collect(),
+        ..RoomConfig::default()
+    };
+    let lease = match lobby
+        .create_room(config)
+        .and_then(|room| lobby.join(room.info.id, 0, None, None))
+    {
+        Ok(lease) => lease,
+        Err(err) => return err.into_response(),
+    };
+    ws.on_upgrade(|socket| play(socket, lease))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinQuery {
+    /// Session token of a player taking back its seat.
+    token: Option<String>,
+    /// Name the player is archived under.
+    name: Option<String>,
+}
+
+pub async fn join_room(
+    State(lobby): State<Lobby>,
+    Path((room_id, seat)): Path<(RoomId, PlayerId)>,
+    Query(query): Query<JoinQuery>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    match lobby.join(room_id, seat, query.token.as_deref(), query.name.as_deref()) {
+        Ok(lease) => ws.on_upgrade(|socket| play(socket, lease)),
+        Err(err) => err.into_response(),
+    }
+}
+
+/// Greets the client with its session token, marks its seat ready once it
+/// answers the hello and then bridges it to the game. Frames other than the
+/// hello answer are refused with an error frame; clients speaking an
+/// unsupported protocol version are told why and disconnected.
+async fn play(mut socket: WebSocket, mut lease: SeatLease) {
+    let hello = ServerToClient::hello(
+        CliRole::Player {
+            player_id: lease.seat(),
+        },
+        Some(lease.token().to_owned()),
+    );
+    let hello = serde_json::to_string(&hello).expect("hello frame is serializable");
+    if socket.send(Message::Text(hello.into())).await.is_err() {
+        return;
+    }
+
+    let capabilities = loop {
+        let message = match socket.recv().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<ClientToServer>(&text) {
+                Ok(msg) => match msg.negotiate(HOST_CAPABILITIES) {
+                    Some(Ok(capabilities)) => break capabilities,
+                    Some(Err(reason)) => {
+                        log::info!("refused client: {reason}");
+                        let shutdown = serde_json::to_string(&ServerToClient::Shutdown { reason })
+                            .expect("shutdown frame is serializable");
+                        let _ = socket.send(Message::Text(shutdown.into())).await;
+                        let _ = socket.send(Message::Close(None)).await;
+                        return;
+                    }
+                    None => format!("expected hello from client, got {msg:?}"),
+                },
+                Err(err) => format!("malformed message: {err}"),
+            },
+            Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+            Some(Ok(_)) => continue,
+        };
+        log::warn!("rejected client frame: {message}");
+        let error = serde_json::to_string(&ServerToClient::Error { message })
+            .expect("error frame is serializable");
+        if socket.send(Message::Text(error.into())).await.is_err() {
+            return;
+        }
+    };
+
+    let catch_up = lease.ready();
+    PlayerSession::new(socket, lease)
+        .with_capabilities(capabilities)
+        .run(catch_up)
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{Router, routing::get};
+    use catan_agents::remote_agent::{Capability, PROTOCOL_VERSION, ViewDecoder};
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpStream;
+    use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, tungstenite};
+
+    use super::*;
+    use crate::{arena_bot, lobby::LimitsConfig};
+
+    type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+    /// Joins a room against greedy bots and reads its hello.
+    async fn room_client() -> Socket {
+        let lobby = Lobby::default();
+        let n_players = GameInitializationState::default().board.n_players;
+        let room = lobby
+            .create_room(RoomConfig {
+                name: "test".to_owned(),
+                seats: std::iter::once(SeatConfig::Human)
+                    .chain(std::iter::repeat_n(SeatConfig::Greedy, n_players - 1))
+                    .collect(),
+                limits: LimitsConfig {
+                    max_turns: Some(4),
+                    ..LimitsConfig::default()
+                },
+                // the game ends as soon as the test drops its socket
+                reconnect_grace_secs: 0,
+                ..RoomConfig::default()
+            })
+            .unwrap()
+            .info;
+        let app = Router::new()
+            .route("/rooms/{room_id}/seats/{seat}", get(join_room))
+            .with_state(lobby);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!(
+            "ws://{}/rooms/{}/seats/0",
+            listener.local_addr().unwrap(),
+            room.id
+        );
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let (mut socket, _) = tokio_tungstenite::connect_async(&url).await.unwrap();
+        let hello = recv(&mut socket).await;
+        assert!(matches!(
+            hello,
+            ServerToClient::Hello {
+                version: PROTOCOL_VERSION,
+                ..
+            }
+        ));
+        socket
+    }
+
+    async fn recv(socket: &mut Socket) -> ServerToClient {
+        let next = async {
+            loop {
+                if let tungstenite::Message::Text(text) = socket.next().await.unwrap().unwrap() {
+                    return serde_json::from_str(&text).unwrap();
+                }
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(10), next)
+            .await
+            .expect("server answers in time")
+    }
+
+    async fn send(socket: &mut Socket, msg: &ClientToServer) {
+        let text = serde_json::to_string(msg).unwrap();
+        socket
+            .send(tungstenite::Message::Text(text.into()))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn clients_of_unsupported_versions_are_told_why() {
+        let mut socket = room_client().await;
+        send(
+            &mut socket,
+            &ClientToServer::Hello {
+                version: PROTOCOL_VERSION + 1,
+                capabilities: Vec::new(),
+            },
+        )
+        .await;
+        match recv(&mut socket).await {
+            ServerToClient::Shutdown { reason } => {
+                assert!(reason.contains("not supported"), "{reason}");
+            }
+            other => panic!("expected shutdown, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_frames_are_answered_with_the_parse_error() {
+        let mut socket = room_client().await;
+        let malformed = || tungstenite::Message::Text("{\"Nonsense\": 1}".into());
+        socket.send(malformed()).await.unwrap();
+        assert!(matches!(
+            recv(&mut socket).await,
+            ServerToClient::Error { message } if message.starts_with("malformed message")
+        ));
+
+        send(
+            &mut socket,
+            &ClientToServer::Hello {
+                version: PROTOCOL_VERSION,
+                capabilities: Vec::new(),
+            },
+        )
+        .await;
+        socket.send(malformed()).await.unwrap();
+        loop {
+            match recv(&mut socket).await {
+                ServerToClient::Error { message } => {
+                    assert!(message.starts_with("malformed message"), "{message}");
+                    break;
+                }
+                ServerToClient::Event { .. } | ServerToClient::DecisionRequest(_) => {}
+                other => panic!("unexpected frame {other:?}"),
+            }
+        }
+    }
 
-        spawn_game(to_game_rx, from_game_tx);
+    #[tokio::test]
+    async fn clients_asking_for_diffs_get_event_deltas() {
+        let mut socket = room_client().await;
+        send(
+            &mut socket,
+            &ClientToServer::Hello {
+                version: PROTOCOL_VERSION,
+                capabilities: vec![Capability::Trades, Capability::Diffs],
+            },
+        )
+        .await;
 
-        let session = PlayerSession::new(socket, from_game_rx, to_game_tx);
-        session.run().await;
-    })
+        let mut views = ViewDecoder::default();
+        loop {
+            match recv(&mut socket).await {
+                ServerToClient::Event { view, .. } => {
+                    views.keyframe(view);
+                }
+                ServerToClient::EventDelta { delta, .. } => {
+                    views.apply(&delta).unwrap();
+                    break;
+                }
+                ServerToClient::DecisionRequest(request) => {
+                    let response = arena_bot::decide(&request).unwrap();
+                    send(&mut socket, &ClientToServer::DecisionResponse(response)).await;
+                }
+                other => panic!("unexpected frame {other:?}"),
+            }
+        }
+    }
 }
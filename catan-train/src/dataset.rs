@@ -0,0 +1,128 @@
+//! CSV dataset rows produced by self-play.
+//!
+//! Columns: `game,seed,decision,player,kind,observation,legal,action,outcome`.
+//! `observation` is the space-separated output of `encode_observation`;
+//! `legal` and `action` are JSON encoded `DecisionResponseFrame`s. `legal` is
+//! empty for `drop_half`, whose choices are not enumerated.
+//! `outcome` is `1` for the winner, `-1` for the other players and `0` when the
+//! game stopped without a winner.
+
+use std::io::{self, Write};
+
+use catan_agents::remote_agent::DecisionResponseFrame;
+use catan_core::gameplay::primitives::player::PlayerId;
+
+pub const HEADER: &str = "game,seed,decision,player,kind,observation,legal,action,outcome";
+
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    pub player: PlayerId,
+    pub kind: &'static str,
+    pub observation: Vec<i32>,
+    pub legal: Option<Vec<DecisionResponseFrame>>,
+    pub action: DecisionResponseFrame,
+}
+
+pub struct DatasetWriter<W: Write> {
+    writer: W,
+    rows: u64,
+}
+
+impl<W: Write> DatasetWriter<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{HEADER}")?;
+        Ok(Self { writer, rows: 0 })
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows
+    }
+
+    pub fn write_game(
+        &mut self,
+        game: u64,
+        seed: u64,
+        winner: Option<PlayerId>,
+        records: &[DecisionRecord],
+    ) -> io::Result<()> {
+        for (decision, record) in records.iter().enumerate() {
+            let observation = record
+                .observation
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let legal = match &record.legal {
+                Some(legal) => serde_json::to_string(legal).map_err(io::Error::other)?,
+                None => String::new(),
+            };
+            let action = serde_json::to_string(&record.action).map_err(io::Error::other)?;
+            let outcome = match winner {
+                Some(winner) if winner == record.player => 1,
+                Some(_) => -1,
+                None => 0,
+            };
+
+            writeln!(
+                self.writer,
+                "{game},{seed},{decision},{},{},{observation},{},{},{outcome}",
+                record.player,
+                record.kind,
+                quote(&legal),
+                quote(&action),
+            )?;
+            self.rows += 1;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catan_core::agent::action::RegularAction;
+
+    use super::*;
+
+    #[test]
+    fn rows_quote_json_fields_and_label_outcomes() {
+        let record = |player| DecisionRecord {
+            player,
+            kind: "regular",
+            observation: vec![1, -1, 0],
+            legal: Some(vec![
+                DecisionResponseFrame::Regular(RegularAction::EndMove),
+                DecisionResponseFrame::Regular(RegularAction::BuyDevCard),
+            ]),
+            action: DecisionResponseFrame::Regular(RegularAction::EndMove),
+        };
+        let mut writer = DatasetWriter::new(Vec::new()).unwrap();
+
+        writer
+            .write_game(3, 7, Some(1), &[record(0), record(1)])
+            .unwrap();
+        assert_eq!(writer.rows(), 2);
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], HEADER);
+        assert_eq!(
+            lines[1],
+            r#"3,7,0,0,regular,1 -1 0,"[{""Regular"":""EndMove""},{""Regular"":""BuyDevCard""}]","{""Regular"":""EndMove""}",-1"#
+        );
+        assert!(lines[2].starts_with("3,7,1,1,regular,"));
+        assert!(lines[2].ends_with(",1"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Flat integer encoding of a player's decision context.
+//!
+//! Only the projected `PublicGameView` and `PrivatePlayerView` are read, so an
+//! encoded observation never contains more than the acting player could see.
+//! Seats are encoded relative to the actor: the actor is always seat 0.
+
+use catan_core::gameplay::{
+    game::view::{PlayerDecisionContext, PublicBankResources, PublicPlayerResources},
+    primitives::{
+        PortKind, Tile,
+        bank::DeckFullnessLevel,
+        build::EstablishmentType,
+        dev_card::{UsableDevCard, UsableDevCardCollection},
+        player::PlayerId,
+        resource::{Resource, ResourceMap},
+    },
+};
+
+/// Value used for features hidden by the visibility policy.
+pub const UNKNOWN: i32 = -1;
+
+const USABLE_DEV_CARDS: [UsableDevCard; 4] = [
+    UsableDevCard::Knight,
+    UsableDevCard::YearOfPlenty,
+    UsableDevCard::RoadBuild,
+    UsableDevCard::Monopoly,
+];
+
+pub fn encode_observation(context: &PlayerDecisionContext<'_>) -> Vec<i32> {
+    let public = &context.public;
+    let n_players = public.players.len();
+    let relative = |id: PlayerId| ((id + n_players - context.actor) % n_players) as i32;
+
+    let mut features = Vec::new();
+
+    features.push(n_players as i32);
+    features.push(public.turn.get_turns_played() as i32);
+    features.push(relative(public.turn.get_turn_index()));
+
+    for (hex, tile) in public.board.arrangement.hex_enum_iter() {
+        let (kind, number) = match tile {
+            Tile::Desert => (0, 0),
+            Tile::Resource { resource, number } => {
+                (1 + resource_code(resource), Into::<u8>::into(number) as i32)
+            }
+            Tile::River { number } => (6, Into::<u8>::into(number) as i32),
+        };
+        features.extend([
+            kind,
+            number,
+            i32::from(hex == public.board_state.robber_pos),
+        ]);
+    }
+
+    let ports = public.board.index().ports_intersection;
+    for intersection in public.board.arrangement.intersections() {
+        let owner = public
+            .builds
+            .players_indexed()
+            .find_map(|(id, builds)| {
+                builds
+                    .establishments
+                    .iter()
+                    .find(|est| est.pos == intersection)
+                    .map(|est| match est.stage {
+                        EstablishmentType::Settlement => 1 + relative(id),
+                        EstablishmentType::City => 1 + n_players as i32 + relative(id),
+                    })
+            })
+            .unwrap_or(0);
+        let port = match ports.get(&intersection) {
+            None => 0,
+            Some(PortKind::Special(resource)) => 1 + resource_code(*resource),
+            Some(PortKind::Universal) => 6,
+        };
+        features.extend([owner, port]);
+    }
+
+    for path in public.board.arrangement.paths() {
+        let owner = public
+            .builds
+            .players_indexed()
+            .find_map(|(id, builds)| {
+                builds
+                    .roads
+                    .edges()
+                    .contains(&path)
+                    .then(|| 1 + relative(id))
+            })
+            .unwrap_or(0);
+        features.push(owner);
+    }
+
+    features.push(public.bank.dev_card_count as i32);
+    match &public.bank.resources {
+        PublicBankResources::Exact(resources) => {
+            features.push(1);
+            extend_resources(&mut features, resources, |count| count as i32);
+        }
+        PublicBankResources::Approx(levels) => {
+            features.push(0);
+            extend_resources(&mut features, levels, level_code);
+        }
+    }
+
+    for seat in 0..n_players {
+        let id = (context.actor + seat) % n_players;
+        let player = &public.players[id];
+        let builds = public.builds.by_player(id);
+
+        if id == context.actor {
+            features.push(context.private.resources.total() as i32);
+            extend_resources(&mut features, context.private.resources, |count| {
+                count as i32
+            });
+        } else {
+            match player.resources {
+                PublicPlayerResources::Exact(resources) => {
+                    features.push(resources.total() as i32);
+                    extend_resources(&mut features, &resources, |count| count as i32);
+                }
+                PublicPlayerResources::Total(total) => {
+                    features.push(total as i32);
+                    features.extend([UNKNOWN; 5]);
+                }
+            }
+        }
+
+        features.push(player.dev_cards.queued as i32);
+        features.push(player.dev_cards.active as i32);
+        extend_dev_cards(&mut features, &player.dev_cards.played);
+        features.push(if id == context.actor {
+            context.private.dev_cards.victory_pts as i32
+        } else {
+            UNKNOWN
+        });
+
+        features.extend([
+            builds.settlements_count() as i32,
+            builds.cities_count() as i32,
+            builds.roads_count() as i32,
+            i32::from(public.longest_road_owner == Some(id)),
+            i32::from(public.largest_army_owner == Some(id)),
+        ]);
+    }
+
+    extend_dev_cards(&mut features, &context.private.dev_cards.active);
+    extend_dev_cards(&mut features, &context.private.dev_cards.queued);
+
+    features
+}
+
+fn resource_code(resource: Resource) -> i32 {
+    Resource::LIST
+        .iter()
+        .position(|candidate| *candidate == resource)
+        .expect("resource list is exhaustive") as i32
+}
+
+fn level_code(level: DeckFullnessLevel) -> i32 {
+    match level {
+        DeckFullnessLevel::Empty => 0,
+        DeckFullnessLevel::Low => 1,
+        DeckFullnessLevel::Medium => 2,
+        DeckFullnessLevel::High => 3,
+    }
+}
+
+fn extend_resources<T: Copy>(
+    features: &mut Vec<i32>,
+    resources: &ResourceMap<T>,
+    code: impl Fn(T) -> i32,
+) {
+    features.extend(Resource::LIST.map(|resource| code(resources[resource])));
+}
+
+fn extend_dev_cards(features: &mut Vec<i32>, cards: &UsableDevCardCollection) {
+    features.extend(USABLE_DEV_CARDS.map(|card| cards[card] as i32));
+}
+
+#[cfg(test)]
+mod tests {
+    use catan_core::gameplay::{
+        game::{
+            index::GameIndex,
+            init::GameInitializationState,
+            view::{ContextFactory, CountingMode, VisibilityConfig},
+        },
+        primitives::resource::ResourceCollection,
+    };
+
+    use super::*;
+
+    #[test]
+    fn encoding_length_does_not_depend_on_actor_or_hand() {
+        let mut state = GameInitializationState::default().finish();
+        state
+            .transfer_from_bank(
+                ResourceCollection {
+                    brick: 3,
+                    ..ResourceCollection::ZERO
+                },
+                2,
+            )
+            .unwrap();
+        let index = GameIndex::rebuild(&state);
+        let visibility = VisibilityConfig::default();
+        let factory = ContextFactory {
+            state: &state,
+            index: &index,
+            visibility: &visibility,
+        };
+
+        let lengths = (0..state.players.count())
+            .map(|id| encode_observation(&factory.player_decision_context(id, None)).len())
+            .collect::<Vec<_>>();
+
+        assert!(lengths.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn human_counting_hides_opponent_hands() {
+        let mut state = GameInitializationState::default().finish();
+        let hand = ResourceCollection {
+            ore: 2,
+            sheep: 1,
+            ..ResourceCollection::ZERO
+        };
+        state.transfer_from_bank(hand, 1).unwrap();
+        let index = GameIndex::rebuild(&state);
+        let encode = |counting| {
+            let visibility = VisibilityConfig {
+                player_mode: counting,
+                spectator_mode: counting,
+            };
+            let factory = ContextFactory {
+                state: &state,
+                index: &index,
+                visibility: &visibility,
+            };
+            encode_observation(&factory.player_decision_context(0, None))
+        };
+
+        let human = encode(CountingMode::Human);
+        let counting = encode(CountingMode::Counting);
+
+        assert_eq!(human.len(), counting.len());
+        let differing = human
+            .iter()
+            .zip(&counting)
+            .filter(|(human, counting)| human != counting)
+            .map(|(human, _)| *human)
+            .collect::<Vec<_>>();
+        assert!(!differing.is_empty());
+        assert!(differing.contains(&UNKNOWN));
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    rc::Rc,
+};
+
+use catan_agents::{
+    greedy::GreedyAgent, lazy::LazyAgent, random::RandomAgent, remote_agent::DecisionResponseFrame,
+};
+use catan_core::{
+    agent::{
+        Agent,
+        action::{
+            ChoosePlayerToRobAction, DropHalfAction, InitAction, InitStageAction,
+            MoveRobbersAction, PostDevCardAction, PostDiceAction, RegularAction, TradeAnswer,
+        },
+        agent::{AgentError, PlayerRuntime},
+    },
+    gameplay::{
+        field::state::FieldBuildParam,
+        game::{
+            controller::{GameController, GameResult, RunOptions},
+            event::{GameEvent, PlayerNotification},
+            init::GameInitializationState,
+            legal,
+            view::{PlayerDecisionContext, PlayerNotificationContext},
+        },
+        primitives::player::PlayerId,
+    },
+    math::dice::RandomDiceRoller,
+    topology::Hex,
+};
+
+use rand::{Rng, SeedableRng, rngs::SmallRng};
+
+use crate::{
+    dataset::{DatasetWriter, DecisionRecord},
+    encode::encode_observation,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentKind {
+    Greedy,
+    Lazy,
+    Random,
+}
+
+impl AgentKind {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "greedy" => Ok(Self::Greedy),
+            "lazy" => Ok(Self::Lazy),
+            "random" => Ok(Self::Random),
+            other => Err(format!(
+                "unknown agent {other:?}, expected greedy|lazy|random"
+            )),
+        }
+    }
+
+    /// Builds the agent; `seed` drives its choices if it has any random ones.
+    pub fn build(self, id: PlayerId, seed: u64) -> Box<dyn Agent> {
+        match self {
+            Self::Greedy => Box::new(GreedyAgent::new(id)),
+            Self::Lazy => Box::new(LazyAgent::new(id)),
+            Self::Random => Box::new(RandomAgent::with_seed(id, seed)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GenerateOptions {
+    pub games: u64,
+    pub agents: Vec<AgentKind>,
+    pub out: PathBuf,
+    pub seed: u64,
+    pub run: RunOptions,
+}
+
+pub fn run(options: &GenerateOptions) -> Result<(), String> {
+    let file = File::create(&options.out)
+        .map_err(|err| format!("failed to create {}: {err}", options.out.display()))?;
+    let writer = generate(options, BufWriter::new(file))?;
+    log::info!(
+        "wrote {} decisions from {} games to {}",
+        writer.rows(),
+        options.games,
+        options.out.display()
+    );
+    writer
+        .finish()
+        .map_err(|err| format!("failed to flush {}: {err}", options.out.display()))?;
+    Ok(())
+}
+
+pub fn generate<W: Write>(
+    options: &GenerateOptions,
+    writer: W,
+) -> Result<DatasetWriter<W>, String> {
+    let init = GameInitializationState::default();
+    if options.agents.len() != init.board.n_players {
+        return Err(format!(
+            "expected {} agents, got {}",
+            init.board.n_players,
+            options.agents.len()
+        ));
+    }
+
+    let mut writer =
+        DatasetWriter::new(writer).map_err(|err| format!("failed to write header: {err}"))?;
+    for game in 0..options.games {
+        let seed = options.seed.wrapping_add(game);
+        let (result, records) = play_game(options, seed);
+        let winner = result.winner();
+        log::info!(
+            "game {game}: {result:?}, {} decisions recorded",
+            records.len()
+        );
+        writer
+            .write_game(game, seed, winner, &records)
+            .map_err(|err| format!("failed to write game {game}: {err}"))?;
+    }
+    Ok(writer)
+}
+
+/// Plays one game whose every random outcome (deck order, stolen cards, dice
+/// and random agents' choices) derives from `seed`.
+fn play_game(options: &GenerateOptions, seed: u64) -> (GameResult, Vec<DecisionRecord>) {
+    let mut seeds = SmallRng::seed_from_u64(seed);
+    let init = GameInitializationState::with_seed(FieldBuildParam::default(), seeds.next_u64());
+    let mut dice = RandomDiceRoller::with_seed(seeds.next_u64());
+
+    let records = Rc::new(RefCell::new(Vec::new()));
+    let mut agents = options
+        .agents
+        .iter()
+        .enumerate()
+        .map(|(id, kind)| {
+            Box::new(RecordingAgent {
+                inner: kind.build(id, seeds.next_u64()),
+                records: records.clone(),
+            }) as Box<dyn Agent>
+        })
+        .collect::<Vec<_>>();
+
+    let result =
+        match GameController::init_with_options(init, &mut agents, &mut [], options.run, None) {
+            Ok(state) => {
+                let mut controller = GameController::new(state, agents);
+                controller.run_with_options(&mut dice, options.run)
+            }
+            Err(result) => {
+                drop(agents);
+                result
+            }
+        };
+
+    let records = Rc::try_unwrap(records)
+        .expect("controller owning the recorders was dropped")
+        .into_inner();
+    (result, records)
+}
+
+/// Forwards every decision to the wrapped agent and records what it saw,
+/// what it could have done and what it did.
+struct RecordingAgent {
+    inner: Box<dyn Agent>,
+    records: Rc<RefCell<Vec<DecisionRecord>>>,
+}
+
+impl RecordingAgent {
+    fn record(
+        &self,
+        kind: &'static str,
+        context: &PlayerDecisionContext<'_>,
+        legal: Option<Vec<DecisionResponseFrame>>,
+        action: DecisionResponseFrame,
+    ) {
+        self.records.borrow_mut().push(DecisionRecord {
+            player: context.actor,
+            kind,
+            observation: encode_observation(context),
+            legal,
+            action,
+        });
+    }
+}
+
+impl PlayerNotification for RecordingAgent {
+    fn on_event(&mut self, event: &GameEvent, context: PlayerNotificationContext<'_>) {
+        self.inner.on_event(event, context);
+    }
+}
+
+impl PlayerRuntime for RecordingAgent {
+    fn player_id(&self) -> PlayerId {
+        self.inner.player_id()
+    }
+
+    fn init_stage_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitStageAction, AgentError> {
+        let legal = legal::legal_initial_placements(&context)
+            .into_iter()
+            .map(|(establishment, road)| {
+                DecisionResponseFrame::InitStage(InitStageAction {
+                    establishment_position: establishment.pos,
+                    road,
+                })
+            })
+            .collect();
+        let action = self.inner.init_stage_action(context.clone())?;
+        self.record(
+            "init_stage",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::InitStage(action),
+        );
+        Ok(action)
+    }
+
+    fn init_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<InitAction, AgentError> {
+        let legal = std::iter::once(InitAction::RollDice)
+            .chain(
+                legal::legal_dev_card_usages(&context)
+                    .into_iter()
+                    .map(InitAction::UseDevCard),
+            )
+            .map(DecisionResponseFrame::InitAction)
+            .collect();
+        let action = self.inner.init_action(context.clone())?;
+        self.record(
+            "init_action",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::InitAction(action),
+        );
+        Ok(action)
+    }
+
+    fn after_dice_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDiceAction, AgentError> {
+        let legal = legal::legal_dev_card_usages(&context)
+            .into_iter()
+            .map(PostDiceAction::UseDevCard)
+            .chain(
+                legal::legal_regular_actions(&context)
+                    .into_iter()
+                    .map(PostDiceAction::RegularAction),
+            )
+            .map(DecisionResponseFrame::PostDice)
+            .collect();
+        let action = self.inner.after_dice_action(context.clone())?;
+        self.record(
+            "post_dice",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::PostDice(action),
+        );
+        Ok(action)
+    }
+
+    fn after_dev_card_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<PostDevCardAction, AgentError> {
+        let action = self.inner.after_dev_card_action(context.clone())?;
+        self.record(
+            "post_dev_card",
+            &context,
+            Some(vec![DecisionResponseFrame::PostDevCard(
+                PostDevCardAction::RollDice,
+            )]),
+            DecisionResponseFrame::PostDevCard(action),
+        );
+        Ok(action)
+    }
+
+    fn regular_action(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<RegularAction, AgentError> {
+        let legal = legal::legal_regular_actions(&context)
+            .into_iter()
+            .map(DecisionResponseFrame::Regular)
+            .collect();
+        let action = self.inner.regular_action(context.clone())?;
+        self.record(
+            "regular",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::Regular(action),
+        );
+        Ok(action)
+    }
+
+    fn move_robbers(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<MoveRobbersAction, AgentError> {
+        let legal = context
+            .public
+            .board
+            .arrangement
+            .hex_iter()
+            .filter(|hex| *hex != context.public.board_state.robber_pos)
+            .map(|hex| DecisionResponseFrame::MoveRobbers(MoveRobbersAction(hex)))
+            .collect();
+        let action = self.inner.move_robbers(context.clone())?;
+        self.record(
+            "move_robbers",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::MoveRobbers(action),
+        );
+        Ok(action)
+    }
+
+    fn choose_player_to_rob(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+        robber_pos: Hex,
+    ) -> Result<ChoosePlayerToRobAction, AgentError> {
+        let legal = legal::legal_rob_targets(&context, robber_pos)
+            .into_iter()
+            .map(|id| DecisionResponseFrame::ChoosePlayerToRob(ChoosePlayerToRobAction(id)))
+            .collect();
+        let action = self
+            .inner
+            .choose_player_to_rob(context.clone(), robber_pos)?;
+        self.record(
+            "choose_player_to_rob",
+            &context,
+            Some(legal),
+            DecisionResponseFrame::ChoosePlayerToRob(action),
+        );
+        Ok(action)
+    }
+
+    fn answer_trade(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<TradeAnswer, AgentError> {
+        let action = self.inner.answer_trade(context.clone())?;
+        self.record(
+            "answer_trade",
+            &context,
+            Some(vec![
+                DecisionResponseFrame::AnswerTrade(TradeAnswer::Accept),
+                DecisionResponseFrame::AnswerTrade(TradeAnswer::Decline),
+            ]),
+            DecisionResponseFrame::AnswerTrade(action),
+        );
+        Ok(action)
+    }
+
+    fn drop_half(
+        &mut self,
+        context: PlayerDecisionContext<'_>,
+    ) -> Result<DropHalfAction, AgentError> {
+        let action = self.inner.drop_half(context.clone())?;
+        self.record(
+            "drop_half",
+            &context,
+            None,
+            DecisionResponseFrame::DropHalf(action),
+        );
+        Ok(action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generated_rows_cover_every_recorded_decision() {
+        let options = GenerateOptions {
+            games: 2,
+            agents: vec![AgentKind::Lazy; 4],
+            out: PathBuf::new(),
+            seed: 11,
+            run: RunOptions {
+                max_turns: Some(8),
+                max_invalid_actions: Some(10),
+                ..RunOptions::default()
+            },
+        };
+
+        let writer = generate(&options, Vec::new()).unwrap();
+        let rows = writer.rows();
+        let output = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let lines = output.lines().skip(1).collect::<Vec<_>>();
+
+        assert!(rows > 0);
+        assert_eq!(lines.len() as u64, rows);
+        assert!(
+            lines
+                .iter()
+                .any(|line| line.starts_with("0,11,0,0,init_stage,"))
+        );
+        assert!(lines.iter().any(|line| line.starts_with("1,12,")));
+    }
+
+    #[test]
+    fn same_seed_writes_identical_files() {
+        let write = |name: &str, seed: u64| {
+            let out = std::env::temp_dir()
+                .join(format!("catan-generate-{}-{name}.csv", std::process::id()));
+            let options = GenerateOptions {
+                games: 1,
+                agents: vec![AgentKind::Random; 4],
+                out: out.clone(),
+                seed,
+                run: RunOptions {
+                    max_turns: Some(30),
+                    max_invalid_actions: Some(10),
+                    ..RunOptions::default()
+                },
+            };
+            run(&options).unwrap();
+            let bytes = std::fs::read(&out).unwrap();
+            std::fs::remove_file(&out).unwrap();
+            bytes
+        };
+
+        let first = write("first", 3);
+        assert_eq!(first, write("second", 3));
+        assert_ne!(first, write("other", 4));
+    }
+
+    #[test]
+    fn agent_count_must_match_board() {
+        let options = GenerateOptions {
+            games: 1,
+            agents: vec![AgentKind::Lazy; 2],
+            out: PathBuf::new(),
+            seed: 0,
+            run: RunOptions::default(),
+        };
+
+        assert!(generate(&options, Vec::new()).is_err());
+    }
+}
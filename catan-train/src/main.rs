@@ -1,3 +1,91 @@
+mod dataset;
+mod encode;
+mod generate;
+mod tune;
+
+use std::path::PathBuf;
+
+use catan_core::gameplay::game::controller::RunOptions;
+
+use crate::{
+    generate::{AgentKind, GenerateOptions},
+    tune::TuneOptions,
+};
+
+const USAGE: &str = "usage:
+  catan-train generate --games <n> --agents <a,b,c,d> --out <file.csv> [--seed <n>] \
+[--max-turns <n>] [--max-invalid-actions <n>]
+  catan-train tune --out <genome.json> [--population <n>] [--generations <n>] [--games <n>] \
+[--opponent <agent>] [--mutation <rate>] [--seed <n>] [--max-turns <n>] [--max-invalid-actions <n>]";
+
 fn main() {
-    todo!()
+    env_logger::init();
+
+    let args = std::env::args().collect::<Vec<_>>();
+    let result = match args.get(1).map(String::as_str) {
+        Some("generate") => generate_options(&args).and_then(|options| generate::run(&options)),
+        Some("tune") => tune_options(&args).and_then(|options| tune::run(&options)),
+        _ => Err(USAGE.to_owned()),
+    };
+
+    if let Err(err) = result {
+        eprintln!("{err}");
+        std::process::exit(2);
+    }
+}
+
+fn generate_options(args: &[String]) -> Result<GenerateOptions, String> {
+    Ok(GenerateOptions {
+        games: parse_arg(args, "--games")?.unwrap_or(1),
+        agents: arg_value(args, "--agents")
+            .unwrap_or_else(|| "greedy,greedy,greedy,greedy".to_owned())
+            .split(',')
+            .map(AgentKind::parse)
+            .collect::<Result<_, _>>()?,
+        out: arg_value(args, "--out")
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("missing --out\n{USAGE}"))?,
+        seed: parse_arg(args, "--seed")?.unwrap_or(0),
+        run: run_options(args)?,
+    })
+}
+
+fn tune_options(args: &[String]) -> Result<TuneOptions, String> {
+    Ok(TuneOptions {
+        population: parse_arg(args, "--population")?.unwrap_or(16),
+        generations: parse_arg(args, "--generations")?.unwrap_or(10),
+        games: parse_arg(args, "--games")?.unwrap_or(8),
+        opponent: AgentKind::parse(arg_value(args, "--opponent").as_deref().unwrap_or("greedy"))?,
+        mutation: parse_arg(args, "--mutation")?.unwrap_or(0.2),
+        out: arg_value(args, "--out")
+            .map(PathBuf::from)
+            .ok_or_else(|| format!("missing --out\n{USAGE}"))?,
+        seed: parse_arg(args, "--seed")?.unwrap_or(0),
+        run: run_options(args)?,
+    })
+}
+
+fn run_options(args: &[String]) -> Result<RunOptions, String> {
+    let defaults = RunOptions::default();
+    Ok(RunOptions {
+        max_turns: parse_arg(args, "--max-turns")?.or(defaults.max_turns),
+        max_invalid_actions: parse_arg(args, "--max-invalid-actions")?
+            .or(defaults.max_invalid_actions),
+        ..defaults
+    })
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[String], name: &str) -> Result<Option<T>, String> {
+    arg_value(args, name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value {value:?} for {name}"))
+        })
+        .transpose()
+}
+
+fn arg_value(args: &[String], name: &str) -> Option<String> {
+    args.windows(2)
+        .find_map(|window| (window[0] == name).then(|| window[1].clone()))
 }